@@ -1,6 +1,6 @@
+use game_events_sdk::{GameEventsIOClient, GameEventsIOEventBuilder, GameEventsIOSession};
 use serde_json::json;
 use std::collections::HashMap;
-use game_events_sdk::{GameEventsIOClient, GameEventsIOEventBuilder, GameEventsIOSession};
 
 fn main() {
     // Initialize the client
@@ -34,18 +34,18 @@ fn main() {
     // Example 3: Event with additional event properties
     println!("3. Logging an event with event properties...");
     let mut event_props = HashMap::new();
-    event_props.insert("level_id".to_string(), json!(5));
-    event_props.insert("score".to_string(), json!(1500));
-    event_props.insert("difficulty".to_string(), json!("hard"));
+    event_props.insert("level_id".into(), json!(5));
+    event_props.insert("score".into(), json!(1500));
+    event_props.insert("difficulty".into(), json!("hard"));
 
     session.push_event("level_completed", event_props);
 
     // Example 4: Purchase event
     println!("4. Logging a purchase event...");
     let mut purchase_props = HashMap::new();
-    purchase_props.insert("item_id".to_string(), json!("sword_legendary"));
-    purchase_props.insert("price".to_string(), json!(9.99));
-    purchase_props.insert("currency".to_string(), json!("USD"));
+    purchase_props.insert("item_id".into(), json!("sword_legendary"));
+    purchase_props.insert("price".into(), json!(9.99));
+    purchase_props.insert("currency".into(), json!("USD"));
 
     session.push_event("purchase", purchase_props);
 
@@ -62,7 +62,7 @@ fn main() {
     // Flush all events
     println!("\nSending events to backend...");
     match client.flush() {
-        Ok(response) => println!("✓ Success! Response: {}", response),
+        Ok(report) => println!("✓ Success! {} events accepted: {}", report.accepted, report),
         Err(e) => eprintln!("✗ Error: {}", e),
     }
 
@@ -0,0 +1,216 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::{validation, EventPriority, Properties, UserPropertyMutation};
+
+/// Event structure for game-events.io
+///
+/// Building an event checks it against the backend's limits (see
+/// [`MAX_EVENT_NAME_LEN`](crate::MAX_EVENT_NAME_LEN) and friends) so
+/// oversized events fail here instead of being dropped server-side.
+#[derive(Serialize, Deserialize, Clone, Debug, Builder)]
+#[cfg_attr(not(feature = "std"), builder(no_std))]
+#[builder(setter(into))]
+#[builder(default)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct GameEventsIOEvent {
+    /// Client-generated id, sent as an idempotency key so the backend can
+    /// drop duplicates of a retried request; quote it when matching events
+    /// against server logs
+    ///
+    /// Always serialized, so an event spooled to disk is retried with the
+    /// id it was first sent with.
+    #[builder(default = "new_event_id()")]
+    #[serde(default = "new_event_id")]
+    pub event_id: String,
+
+    /// Event name (e.g., "level_completed", "purchase")
+    pub event: Cow<'static, str>,
+
+    /// Unique user identifier
+    pub user_id: String,
+
+    /// Session identifier
+    pub session_id: String,
+
+    /// Unix timestamp in seconds; 0 unless set without the `std` feature
+    #[builder(default = "self.default_time()")]
+    pub time: u64,
+
+    /// The device's offset from UTC in minutes when the event was logged;
+    /// `time` is always UTC
+    #[builder(setter(strip_option))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utc_offset_minutes: Option<i32>,
+
+    /// The device's IANA time zone, e.g. "Europe/Berlin"
+    #[builder(setter(strip_option))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+
+    /// Event-specific properties
+    #[serde(default, skip_serializing_if = "Properties::is_empty")]
+    pub event_properties: Properties,
+
+    /// User properties (will be merged with existing user data)
+    #[serde(default, skip_serializing_if = "Properties::is_empty")]
+    pub user_properties: Properties,
+
+    /// SDK-generated id of the device/install, used to stitch pre-login events
+    #[builder(setter(strip_option))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anonymous_id: Option<String>,
+
+    /// Relative updates to user properties, applied in order on the server
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub user_property_ops: Vec<UserPropertyMutation>,
+
+    /// Sampling rate the event was kept at, if the client samples events;
+    /// the backend weights the event by `1 / sample_rate`
+    #[builder(setter(strip_option))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<f64>,
+
+    /// Position of the event in its session, starting at 1; set by
+    /// sessions so the backend can order events from batches
+    /// that arrive out of order
+    #[builder(setter(strip_option))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+
+    /// When the event's session started, as a Unix timestamp in
+    /// milliseconds; orders sessions of the same user ahead of `sequence`
+    #[builder(setter(strip_option))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_epoch: Option<u64>,
+
+    /// `time` corrected by the device's clock offset from the server, once a
+    /// flush response has revealed it; `time` keeps the device's reading
+    #[builder(setter(strip_option))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corrected_time: Option<u64>,
+
+    /// Whether logging the event flushes the queue right away; not sent to
+    /// the backend
+    #[serde(skip)]
+    pub priority: EventPriority,
+
+    /// Deployment stage the event was logged in, e.g. "staging"; set by the
+    /// client's environment
+    #[builder(setter(strip_option))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+
+    /// Project the event belongs to, which picks its client in a registry
+    /// of clients; not sent to the backend
+    #[builder(setter(strip_option))]
+    #[serde(skip)]
+    pub project: Option<String>,
+}
+
+impl Default for GameEventsIOEvent {
+    /// An empty event with a fresh `event_id`
+    fn default() -> Self {
+        GameEventsIOEvent {
+            event_id: new_event_id(),
+            event: Cow::Borrowed(""),
+            user_id: String::new(),
+            session_id: String::new(),
+            time: 0,
+            utc_offset_minutes: None,
+            time_zone: None,
+            event_properties: Properties::default(),
+            user_properties: Properties::default(),
+            anonymous_id: None,
+            user_property_ops: Vec::new(),
+            sample_rate: None,
+            sequence: None,
+            session_epoch: None,
+            corrected_time: None,
+            priority: EventPriority::default(),
+            environment: None,
+            project: None,
+        }
+    }
+}
+
+/// Id for new events, and for events spooled or archived before ids were
+/// generated
+#[cfg(feature = "std")]
+fn new_event_id() -> String {
+    use alloc::string::ToString;
+
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Without `std` there is no random source to make ids from: set `event_id`
+/// yourself, or leave it empty and the client assigns one when the event is
+/// logged
+#[cfg(not(feature = "std"))]
+fn new_event_id() -> String {
+    String::new()
+}
+
+impl GameEventsIOEventBuilder {
+    /// Add an event property without building a map of them first
+    pub fn property(
+        &mut self,
+        key: impl Into<Cow<'static, str>>,
+        value: impl Into<serde_json::Value>,
+    ) -> &mut Self {
+        self.event_properties
+            .get_or_insert_with(Properties::new)
+            .insert(key.into(), value.into());
+        self
+    }
+
+    /// Add a user property without building a map of them first
+    pub fn user_property(
+        &mut self,
+        key: impl Into<Cow<'static, str>>,
+        value: impl Into<serde_json::Value>,
+    ) -> &mut Self {
+        self.user_properties
+            .get_or_insert_with(Properties::new)
+            .insert(key.into(), value.into());
+        self
+    }
+
+    /// Set `time` from a point in time, e.g. to backfill historical events
+    ///
+    /// Times before the Unix epoch become 0.
+    #[cfg(feature = "std")]
+    pub fn timestamp(&mut self, time: SystemTime) -> &mut Self {
+        self.time = Some(time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()));
+        self
+    }
+
+    #[cfg(feature = "std")]
+    fn default_time(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn default_time(&self) -> u64 {
+        0
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        validation::check_event_name(self.event.as_deref().unwrap_or_default())?;
+        if let Some(properties) = &self.event_properties {
+            validation::check_properties("event_properties", properties)?;
+        }
+        if let Some(properties) = &self.user_properties {
+            validation::check_properties("user_properties", properties)?;
+        }
+        Ok(())
+    }
+}
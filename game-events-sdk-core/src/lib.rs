@@ -0,0 +1,93 @@
+//! The game-events.io event model, its builder and serialization, without
+//! `std`
+//!
+//! `game-events-sdk` layers the client, transports and persistence on top and
+//! re-exports everything here. Depend on this crate directly with
+//! `default-features = false` for targets without `std`, such as console
+//! homebrew, and hand the bytes from [`to_payload`] to whatever network stack
+//! the platform has.
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[macro_use]
+extern crate derive_builder;
+
+mod event;
+mod priority;
+mod user_ops;
+mod validation;
+
+pub use event::{GameEventsIOEvent, GameEventsIOEventBuilder, GameEventsIOEventBuilderError};
+pub use priority::EventPriority;
+pub use user_ops::{UserPropertyMutation, UserPropertyOp};
+#[doc(hidden)]
+pub use validation::check_event_name;
+pub use validation::{
+    MAX_EVENT_NAME_LEN, MAX_PROPERTY_COUNT, MAX_PROPERTY_NAME_LEN, MAX_PROPERTY_VALUE_SIZE,
+};
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+/// Event and user properties by name; static names are borrowed rather than
+/// copied
+#[cfg(feature = "std")]
+pub type Properties = std::collections::HashMap<Cow<'static, str>, serde_json::Value>;
+
+/// Event and user properties by name; static names are borrowed rather than
+/// copied
+#[cfg(not(feature = "std"))]
+pub type Properties = alloc::collections::BTreeMap<Cow<'static, str>, serde_json::Value>;
+
+/// Serialize a batch of events into the JSON request body the backend expects
+pub fn to_payload(events: &[GameEventsIOEvent]) -> Result<Vec<u8>, serde_json::Error> {
+    serde_json::to_vec(events)
+}
+
+/// Serialize a batch of events into `writer` as [`to_payload`] would, one
+/// event at a time, so the body never has to be held in memory
+#[cfg(feature = "std")]
+pub fn write_payload(
+    events: &[GameEventsIOEvent],
+    writer: impl std::io::Write,
+) -> Result<(), serde_json::Error> {
+    serde_json::to_writer(writer, events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_payload() {
+        let event = GameEventsIOEventBuilder::default()
+            .event("level_up")
+            .time(1_700_000_000u64)
+            .property("level", 3)
+            .build()
+            .unwrap();
+        // Static names are borrowed, not copied
+        assert!(matches!(event.event, Cow::Borrowed("level_up")));
+        assert!(event
+            .event_properties
+            .keys()
+            .all(|key| matches!(key, Cow::Borrowed(_))));
+
+        let body = to_payload(core::slice::from_ref(&event)).unwrap();
+        #[cfg(feature = "std")]
+        {
+            let mut written = Vec::new();
+            write_payload(&[event], &mut written).unwrap();
+            assert_eq!(written, body);
+        }
+        let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload[0]["event"], "level_up");
+        assert_eq!(payload[0]["time"], 1_700_000_000);
+        assert_eq!(payload[0]["event_properties"], json!({"level": 3}));
+        assert!(payload[0].get("priority").is_none());
+    }
+}
@@ -0,0 +1,11 @@
+/// How urgently a logged event should be sent
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EventPriority {
+    /// Wait in the queue for the next flush
+    #[default]
+    Normal,
+
+    /// Flush the queue as soon as the event is logged, whatever the client's
+    /// batch policy
+    Immediate,
+}
@@ -0,0 +1,55 @@
+use alloc::string::String;
+
+use serde::{Deserialize, Serialize};
+
+/// A mutation applied to a user property on the server
+///
+/// Unlike plain user properties, which overwrite the stored value, these are
+/// applied relative to whatever the server already has, so updates from
+/// several devices do not race.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", content = "value", rename_all = "snake_case")]
+pub enum UserPropertyOp {
+    /// Add to a numeric property
+    Increment(serde_json::Number),
+
+    /// Append a value to a list property
+    Append(serde_json::Value),
+
+    /// Set the property only if the user does not have it yet
+    SetOnce(serde_json::Value),
+}
+
+/// A [`UserPropertyOp`] targeting one property, as sent in the payload
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UserPropertyMutation {
+    /// Name of the user property
+    pub property: String,
+
+    #[serde(flatten)]
+    pub op: UserPropertyOp,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use serde_json::json;
+
+    #[test]
+    fn test_mutation_round_trip() {
+        let mutation = UserPropertyMutation {
+            property: "matches_played".to_string(),
+            op: UserPropertyOp::Increment(1.into()),
+        };
+        let value = serde_json::to_value(&mutation).unwrap();
+        assert_eq!(
+            value,
+            json!({"property": "matches_played", "op": "increment", "value": 1})
+        );
+        assert_eq!(
+            serde_json::from_value::<UserPropertyMutation>(value).unwrap(),
+            mutation
+        );
+    }
+}
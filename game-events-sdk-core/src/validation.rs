@@ -0,0 +1,98 @@
+//! Limits enforced by the backend, checked when an event is built
+
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use crate::Properties;
+
+/// Maximum length of an event name, in bytes
+pub const MAX_EVENT_NAME_LEN: usize = 64;
+
+/// Maximum length of a property name, in bytes
+pub const MAX_PROPERTY_NAME_LEN: usize = 64;
+
+/// Maximum number of event properties, and of user properties, per event
+pub const MAX_PROPERTY_COUNT: usize = 200;
+
+/// Maximum size of a single property value serialized as JSON, in bytes
+pub const MAX_PROPERTY_VALUE_SIZE: usize = 8 * 1024;
+
+/// Check that an event name is non-empty, short enough and only made of
+/// ASCII letters, digits, '_', '-' and '.'
+///
+/// Public for `game-events-sdk-derive`, which checks names at compile time.
+#[doc(hidden)]
+pub fn check_event_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("event name must not be empty".to_string());
+    }
+    if name.len() > MAX_EVENT_NAME_LEN {
+        return Err(format!(
+            "event name {:?} is longer than {} bytes",
+            name, MAX_EVENT_NAME_LEN
+        ));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+    {
+        return Err(format!(
+            "event name {:?} contains invalid character {:?}",
+            name, c
+        ));
+    }
+    Ok(())
+}
+
+/// Check the number, names and value sizes of a property map
+pub(crate) fn check_properties(kind: &str, properties: &Properties) -> Result<(), String> {
+    if properties.len() > MAX_PROPERTY_COUNT {
+        return Err(format!(
+            "{} has {} entries, more than {}",
+            kind,
+            properties.len(),
+            MAX_PROPERTY_COUNT
+        ));
+    }
+    for (key, value) in properties {
+        if key.is_empty() || key.len() > MAX_PROPERTY_NAME_LEN {
+            return Err(format!(
+                "{} name {:?} must be 1 to {} bytes long",
+                kind, key, MAX_PROPERTY_NAME_LEN
+            ));
+        }
+        let size = serde_json::to_vec(value).map(|v| v.len()).unwrap_or(0);
+        if size > MAX_PROPERTY_VALUE_SIZE {
+            return Err(format!(
+                "{} {:?} is {} bytes, more than {}",
+                kind, key, size, MAX_PROPERTY_VALUE_SIZE
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_limits() {
+        assert!(check_event_name("level_completed").is_ok());
+        assert!(check_event_name("").is_err());
+        assert!(check_event_name("level completed").is_err());
+        assert!(check_event_name(&str::repeat("a", MAX_EVENT_NAME_LEN + 1)).is_err());
+
+        let mut props = Properties::new();
+        props.insert("level".into(), json!(5));
+        assert!(check_properties("event_properties", &props).is_ok());
+
+        props.insert(
+            "blob".into(),
+            json!(str::repeat("x", MAX_PROPERTY_VALUE_SIZE)),
+        );
+        let err = check_properties("event_properties", &props).unwrap_err();
+        assert!(err.contains("\"blob\""), "{}", err);
+    }
+}
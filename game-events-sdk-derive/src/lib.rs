@@ -0,0 +1,91 @@
+//! `#[derive(TypedEvent)]` for game-events-sdk
+//!
+//! Use it through the `derive` feature of `game-events-sdk` rather than
+//! depending on this crate directly.
+
+use game_events_sdk_core::check_event_name;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, LitStr};
+
+/// Implement `game_events_sdk::TypedEvent` for a struct
+///
+/// The event name is the struct name in snake_case (`LevelCompleted` becomes
+/// `level_completed`) unless overridden with `#[event(name = "...")]`. The
+/// struct must implement `serde::Serialize`; its fields become the event
+/// properties.
+#[proc_macro_derive(TypedEvent, attributes(event))]
+pub fn derive_typed_event(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if !matches!(input.data, Data::Struct(_)) {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "TypedEvent can only be derived for structs",
+        ));
+    }
+
+    let mut name = to_snake_case(&input.ident.to_string());
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("event")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                let value: LitStr = meta.value()?.parse()?;
+                name = value.value();
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"...\"`"))
+            }
+        })?;
+    }
+    // Reject names the SDK would refuse at runtime
+    if let Err(message) = check_event_name(&name) {
+        return Err(syn::Error::new_spanned(&input.ident, message));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::game_events_sdk::TypedEvent for #ident #ty_generics #where_clause {
+            const EVENT_NAME: &'static str = #name;
+        }
+    })
+}
+
+/// "LevelCompleted" -> "level_completed", "HTTPRequest" -> "http_request"
+fn to_snake_case(ident: &str) -> String {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut out = String::with_capacity(ident.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("LevelCompleted"), "level_completed");
+        assert_eq!(to_snake_case("HTTPRequest"), "http_request");
+        assert_eq!(to_snake_case("Level2Start"), "level2_start");
+        assert_eq!(to_snake_case("Purchase"), "purchase");
+    }
+}
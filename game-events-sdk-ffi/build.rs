@@ -0,0 +1,43 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Set to copy the generated header over the checked-in one
+const UPDATE_HEADER: &str = "GAME_EVENTS_SDK_UPDATE_HEADER";
+
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed={}", UPDATE_HEADER);
+
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("game_events_sdk.h");
+
+    // The checked-in header stays usable if cbindgen fails, and the header
+    // test reports the empty file as out of date
+    let header = match generate(&crate_dir) {
+        Ok(header) => header,
+        Err(e) => {
+            println!("cargo:warning=failed to generate C header: {}", e);
+            Vec::new()
+        }
+    };
+    fs::write(&out_path, &header).expect("failed to write to OUT_DIR");
+
+    if env::var_os(UPDATE_HEADER).is_some() && !header.is_empty() {
+        fs::write(crate_dir.join("include/game_events_sdk.h"), &header)
+            .expect("failed to update include/game_events_sdk.h");
+    }
+}
+
+fn generate(crate_dir: &Path) -> Result<Vec<u8>, String> {
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))?;
+    let bindings = cbindgen::Builder::new()
+        .with_crate(crate_dir)
+        .with_config(config)
+        .generate()
+        .map_err(|e| e.to_string())?;
+    let mut header = Vec::new();
+    bindings.write(&mut header);
+    Ok(header)
+}
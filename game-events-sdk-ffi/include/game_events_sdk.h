@@ -0,0 +1,128 @@
+#ifndef GAME_EVENTS_SDK_H
+#define GAME_EVENTS_SDK_H
+
+/* Generated by cbindgen from game-events-sdk-ffi. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result of an FFI call
+ */
+typedef enum GameEventsIOStatus {
+  GAME_EVENTS_IO_STATUS_OK = 0,
+  /**
+   * A required pointer argument was NULL
+   */
+  GAME_EVENTS_IO_STATUS_NULL_ARGUMENT = 1,
+  /**
+   * A string argument was not valid UTF-8
+   */
+  GAME_EVENTS_IO_STATUS_INVALID_UTF8 = 2,
+  /**
+   * The client configuration was rejected, e.g. a malformed backend URL
+   */
+  GAME_EVENTS_IO_STATUS_INVALID_CONFIG = 3,
+  /**
+   * The event JSON could not be parsed or failed validation
+   */
+  GAME_EVENTS_IO_STATUS_INVALID_EVENT = 4,
+  /**
+   * Sending events to the backend failed; they stay queued
+   */
+  GAME_EVENTS_IO_STATUS_SEND_FAILED = 5,
+  /**
+   * The SDK panicked; the client should not be used again
+   */
+  GAME_EVENTS_IO_STATUS_PANIC = 6,
+} GameEventsIOStatus;
+
+/**
+ * Opaque client handle owned by the caller until passed to
+ * [`game_events_client_free`]
+ */
+typedef struct GameEventsIOClient GameEventsIOClient;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Describe the last failed call on this thread, NULL if none failed
+ *
+ * The string stays valid until the next failing call on the same thread.
+ */
+const char *game_events_last_error(void);
+
+/**
+ * Create a client sending to the default backend, NULL on failure
+ *
+ * # Safety
+ *
+ * `api_key` must point to a NUL-terminated string.
+ */
+struct GameEventsIOClient *game_events_client_new(const char *api_key);
+
+/**
+ * Create a client sending to `backend_url`, or the default backend if it is
+ * NULL; returns NULL on failure
+ *
+ * # Safety
+ *
+ * `api_key` must point to a NUL-terminated string and `backend_url` must be
+ * NULL or point to one.
+ */
+struct GameEventsIOClient *game_events_client_new_with_endpoint(const char *api_key,
+                                                                const char *backend_url);
+
+/**
+ * Queue an event given as a JSON object
+ *
+ * The object needs an `"event"` name and may carry `"user_id"`,
+ * `"session_id"`, `"time"`, `"event_properties"` and `"user_properties"`.
+ *
+ * # Safety
+ *
+ * `client` must come from [`game_events_client_new`] and not be freed;
+ * `event_json` must point to a NUL-terminated string.
+ */
+enum GameEventsIOStatus game_events_client_log_event(struct GameEventsIOClient *client,
+                                                     const char *event_json);
+
+/**
+ * Send every queued event, blocking until the backend answers
+ *
+ * # Safety
+ *
+ * `client` must come from [`game_events_client_new`] and not be freed.
+ */
+enum GameEventsIOStatus game_events_client_flush(struct GameEventsIOClient *client);
+
+/**
+ * Number of events waiting to be sent, 0 if `client` is NULL
+ *
+ * # Safety
+ *
+ * `client` must be NULL or come from [`game_events_client_new`] and not be
+ * freed.
+ */
+size_t game_events_client_pending_events(const struct GameEventsIOClient *client);
+
+/**
+ * Destroy a client; unsent events are lost unless a spool is configured
+ *
+ * # Safety
+ *
+ * `client` must be NULL or come from [`game_events_client_new`], and must
+ * not be used afterwards.
+ */
+void game_events_client_free(struct GameEventsIOClient *client);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GAME_EVENTS_SDK_H */
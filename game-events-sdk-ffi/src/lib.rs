@@ -0,0 +1,296 @@
+//! C ABI for game-events-sdk
+//!
+//! Builds as a `cdylib` and `staticlib` for engines that cannot link Rust
+//! directly. The header is checked in as `include/game_events_sdk.h`; builds
+//! regenerate it with cbindgen into `OUT_DIR`, and a test fails if the two
+//! differ. Build with `GAME_EVENTS_SDK_UPDATE_HEADER=1` to update it.
+//!
+//! Every function catches panics at the boundary. Functions that fail return
+//! a [`GameEventsIOStatus`] and leave a description behind for
+//! [`game_events_last_error`].
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use game_events_sdk::{GameEventsIOClientBuilder, GameEventsIOEventBuilder, Properties};
+use serde::Deserialize;
+
+/// Opaque client handle owned by the caller until passed to
+/// [`game_events_client_free`]
+pub struct GameEventsIOClient {
+    inner: game_events_sdk::GameEventsIOClient,
+}
+
+/// Result of an FFI call
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEventsIOStatus {
+    Ok = 0,
+    /// A required pointer argument was NULL
+    NullArgument = 1,
+    /// A string argument was not valid UTF-8
+    InvalidUtf8 = 2,
+    /// The client configuration was rejected, e.g. a malformed backend URL
+    InvalidConfig = 3,
+    /// The event JSON could not be parsed or failed validation
+    InvalidEvent = 4,
+    /// Sending events to the backend failed; they stay queued
+    SendFailed = 5,
+    /// The SDK panicked; the client should not be used again
+    Panic = 6,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = CString::new(message.into().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Describe the last failed call on this thread, NULL if none failed
+///
+/// The string stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn game_events_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Event as accepted by [`game_events_client_log_event`]
+///
+/// Only `event` is required; the client fills in the user, anonymous id and
+/// time like it does for Rust callers.
+#[derive(Deserialize)]
+struct EventJson {
+    event: String,
+    #[serde(default)]
+    user_id: String,
+    #[serde(default)]
+    session_id: String,
+    time: Option<u64>,
+    #[serde(default)]
+    event_properties: Properties,
+    #[serde(default)]
+    user_properties: Properties,
+}
+
+/// Read a NUL-terminated UTF-8 argument
+///
+/// # Safety
+///
+/// `value` must be NULL or point to a NUL-terminated string.
+unsafe fn str_arg<'a>(value: *const c_char, name: &str) -> Result<&'a str, GameEventsIOStatus> {
+    if value.is_null() {
+        set_last_error(format!("{} is NULL", name));
+        return Err(GameEventsIOStatus::NullArgument);
+    }
+    CStr::from_ptr(value).to_str().map_err(|_| {
+        set_last_error(format!("{} is not valid UTF-8", name));
+        GameEventsIOStatus::InvalidUtf8
+    })
+}
+
+fn guard(f: impl FnOnce() -> GameEventsIOStatus) -> GameEventsIOStatus {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| {
+        set_last_error("game-events-sdk panicked");
+        GameEventsIOStatus::Panic
+    })
+}
+
+/// Create a client sending to the default backend, NULL on failure
+///
+/// # Safety
+///
+/// `api_key` must point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn game_events_client_new(api_key: *const c_char) -> *mut GameEventsIOClient {
+    game_events_client_new_with_endpoint(api_key, ptr::null())
+}
+
+/// Create a client sending to `backend_url`, or the default backend if it is
+/// NULL; returns NULL on failure
+///
+/// # Safety
+///
+/// `api_key` must point to a NUL-terminated string and `backend_url` must be
+/// NULL or point to one.
+#[no_mangle]
+pub unsafe extern "C" fn game_events_client_new_with_endpoint(
+    api_key: *const c_char,
+    backend_url: *const c_char,
+) -> *mut GameEventsIOClient {
+    let mut client = ptr::null_mut();
+    guard(|| {
+        let mut builder = GameEventsIOClientBuilder::default();
+        match str_arg(api_key, "api_key") {
+            Ok(api_key) => builder.api_key(api_key),
+            Err(status) => return status,
+        };
+        if !backend_url.is_null() {
+            match str_arg(backend_url, "backend_url") {
+                Ok(url) => builder.backend_url(url),
+                Err(status) => return status,
+            };
+        }
+        match builder.build() {
+            Ok(inner) => {
+                client = Box::into_raw(Box::new(GameEventsIOClient { inner }));
+                GameEventsIOStatus::Ok
+            }
+            Err(e) => {
+                set_last_error(e.to_string());
+                GameEventsIOStatus::InvalidConfig
+            }
+        }
+    });
+    client
+}
+
+/// Queue an event given as a JSON object
+///
+/// The object needs an `"event"` name and may carry `"user_id"`,
+/// `"session_id"`, `"time"`, `"event_properties"` and `"user_properties"`.
+///
+/// # Safety
+///
+/// `client` must come from [`game_events_client_new`] and not be freed;
+/// `event_json` must point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn game_events_client_log_event(
+    client: *mut GameEventsIOClient,
+    event_json: *const c_char,
+) -> GameEventsIOStatus {
+    guard(|| {
+        let Some(client) = client.as_mut() else {
+            set_last_error("client is NULL");
+            return GameEventsIOStatus::NullArgument;
+        };
+        let json = match str_arg(event_json, "event_json") {
+            Ok(json) => json,
+            Err(status) => return status,
+        };
+        let parsed: EventJson = match serde_json::from_str(json) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                set_last_error(format!("invalid event JSON: {}", e));
+                return GameEventsIOStatus::InvalidEvent;
+            }
+        };
+
+        let mut builder = GameEventsIOEventBuilder::default();
+        builder
+            .event(parsed.event)
+            .user_id(parsed.user_id)
+            .session_id(parsed.session_id)
+            .event_properties(parsed.event_properties)
+            .user_properties(parsed.user_properties);
+        if let Some(time) = parsed.time {
+            builder.time(time);
+        }
+        match builder.build() {
+            Ok(event) => {
+                client.inner.log_event(event);
+                GameEventsIOStatus::Ok
+            }
+            Err(e) => {
+                set_last_error(e.to_string());
+                GameEventsIOStatus::InvalidEvent
+            }
+        }
+    })
+}
+
+/// Send every queued event, blocking until the backend answers
+///
+/// # Safety
+///
+/// `client` must come from [`game_events_client_new`] and not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn game_events_client_flush(
+    client: *mut GameEventsIOClient,
+) -> GameEventsIOStatus {
+    guard(|| {
+        let Some(client) = client.as_mut() else {
+            set_last_error("client is NULL");
+            return GameEventsIOStatus::NullArgument;
+        };
+        match client.inner.flush() {
+            Ok(_) => GameEventsIOStatus::Ok,
+            Err(e) => {
+                set_last_error(e.to_string());
+                GameEventsIOStatus::SendFailed
+            }
+        }
+    })
+}
+
+/// Number of events waiting to be sent, 0 if `client` is NULL
+///
+/// # Safety
+///
+/// `client` must be NULL or come from [`game_events_client_new`] and not be
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn game_events_client_pending_events(
+    client: *const GameEventsIOClient,
+) -> usize {
+    client
+        .as_ref()
+        .map_or(0, |client| client.inner.pending_events_count())
+}
+
+/// Destroy a client; unsent events are lost unless a spool is configured
+///
+/// # Safety
+///
+/// `client` must be NULL or come from [`game_events_client_new`], and must
+/// not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn game_events_client_free(client: *mut GameEventsIOClient) {
+    if !client.is_null() {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(client))));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_is_up_to_date() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/game_events_sdk.h"));
+        assert!(
+            generated == include_str!("../include/game_events_sdk.h"),
+            "include/game_events_sdk.h is out of date; rebuild with \
+             GAME_EVENTS_SDK_UPDATE_HEADER=1"
+        );
+    }
+
+    #[test]
+    fn test_log_event_from_json() {
+        unsafe {
+            let client = game_events_client_new(c"test_api_key".as_ptr());
+            assert!(!client.is_null());
+
+            let status = game_events_client_log_event(
+                client,
+                cr#"{"event": "level_up", "event_properties": {"level": 3}}"#.as_ptr(),
+            );
+            assert_eq!(status, GameEventsIOStatus::Ok);
+            assert_eq!(game_events_client_pending_events(client), 1);
+
+            let status = game_events_client_log_event(client, c"{\"event\": \"\"}".as_ptr());
+            assert_eq!(status, GameEventsIOStatus::InvalidEvent);
+            assert!(!game_events_last_error().is_null());
+
+            let status = game_events_client_log_event(client, ptr::null());
+            assert_eq!(status, GameEventsIOStatus::NullArgument);
+            assert_eq!(game_events_client_pending_events(client), 1);
+
+            game_events_client_free(client);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use serde::Serialize;
+
+/// Stage of an ad's lifecycle, logged with [`GameEventsIOSession::ad_event`]
+///
+/// [`GameEventsIOSession::ad_event`]: crate::GameEventsIOSession::ad_event
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdAction {
+    /// The ad was shown to the player
+    Impression,
+
+    /// The player clicked the ad
+    Click,
+
+    /// The player earned the reward of a rewarded ad
+    RewardGranted,
+}
+
+impl AdAction {
+    /// Event name used for this action
+    pub fn event_name(self) -> &'static str {
+        match self {
+            AdAction::Impression => "ad_impression",
+            AdAction::Click => "ad_click",
+            AdAction::RewardGranted => "ad_reward_granted",
+        }
+    }
+}
+
+/// Details shared by every ad lifecycle event
+#[derive(Clone, Debug, Builder, Serialize)]
+#[builder(setter(into))]
+pub struct AdEvent {
+    /// Ad network or mediation source, e.g. "admob"
+    pub network: String,
+
+    /// Placement the ad was shown in, e.g. "level_end_rewarded"
+    pub placement: String,
+
+    /// Estimated revenue reported by the network for this ad
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_revenue: Option<f64>,
+
+    /// ISO 4217 currency code of `estimated_revenue`
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revenue_currency: Option<String>,
+}
@@ -0,0 +1,66 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Serialize;
+
+/// Identifies the build of the game sending events
+///
+/// Set fields are attached to every event and sent as request headers so the
+/// backend can validate and slice by version.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Builder, Serialize)]
+#[builder(setter(into, strip_option))]
+#[builder(default)]
+pub struct AppInfo {
+    /// User-facing version, e.g. "1.4.2"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_version: Option<String>,
+
+    /// Internal build number, e.g. "1234"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_number: Option<String>,
+
+    /// Bundle / package identifier, e.g. "com.studio.game"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle_id: Option<String>,
+
+    /// Engine wrapper around this SDK, e.g. "unity/1.0.0"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sdk_wrapper: Option<String>,
+}
+
+impl AppInfo {
+    /// Add a header for every set field
+    ///
+    /// Values that are not valid header values are skipped.
+    pub(crate) fn insert_headers(&self, headers: &mut HeaderMap) {
+        let fields = [
+            ("x-app-version", &self.app_version),
+            ("x-build-number", &self.build_number),
+            ("x-bundle-id", &self.bundle_id),
+            ("x-sdk-wrapper", &self.sdk_wrapper),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(HeaderName::from_static(name), value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_headers() {
+        let app_info = AppInfoBuilder::default()
+            .app_version("1.4.2")
+            .bundle_id("com.studio.game")
+            .build()
+            .unwrap();
+
+        let mut headers = HeaderMap::new();
+        app_info.insert_headers(&mut headers);
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers["x-app-version"], "1.4.2");
+        assert_eq!(headers["x-bundle-id"], "com.studio.game");
+    }
+}
@@ -0,0 +1,163 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{FlushPolicy, GameEventsIOClient, GameEventsIOEvent, GameEventsIOProducer};
+
+/// How often the task checks for `max_pending_events` and a stop request
+const TICK: Duration = Duration::from_millis(250);
+
+/// Handle to a client flushing from a task on the client's
+/// [`AsyncRuntime`](crate::AsyncRuntime)
+///
+/// Dropping the worker asks the task to make a final flush and finish. The
+/// client's `flush_on_drop` deadline applies to that flush, which never
+/// blocks the executor.
+#[derive(Debug)]
+pub struct GameEventsIOAsyncWorker {
+    producer: GameEventsIOProducer,
+    stop: Arc<AtomicBool>,
+}
+
+impl GameEventsIOAsyncWorker {
+    pub(crate) fn spawn(mut client: GameEventsIOClient, policy: FlushPolicy) -> Self {
+        let producer = client.producer();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        let runtime = Arc::clone(&client.async_runtime);
+        let tick = TICK.min(policy.interval);
+        // Dropping the client in the task must not run its blocking flush
+        let final_flush_deadline = client.flush_on_drop.take();
+        let task = {
+            let runtime = Arc::clone(&runtime);
+            async move {
+                let mut next_flush = Instant::now() + policy.interval;
+                loop {
+                    runtime.sleep(tick).await;
+                    client.drain_producers();
+                    if stopped.load(Ordering::Relaxed) {
+                        let _ = match final_flush_deadline {
+                            Some(deadline) => client.shutdown_async(deadline).await,
+                            None => client.flush_async().await,
+                        };
+                        break;
+                    }
+                    if Instant::now() >= next_flush
+                        || client.pending_events_count() >= policy.max_pending_events
+                    {
+                        let _ = client.flush_async().await;
+                        next_flush = Instant::now() + policy.interval;
+                    }
+                }
+            }
+        };
+        runtime.spawn(Box::pin(task));
+        GameEventsIOAsyncWorker { producer, stop }
+    }
+
+    /// Queue an event on the worker
+    pub fn log_event(&self, event: GameEventsIOEvent) {
+        self.producer.log_event(event);
+    }
+
+    /// Get a sender other systems can log through
+    pub fn producer(&self) -> GameEventsIOProducer {
+        self.producer.clone()
+    }
+}
+
+impl Drop for GameEventsIOAsyncWorker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::{GameEventsIOClientBuilder, GameEventsIOEventBuilder, MemorySink};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_flushes_on_stop() {
+        let sink = MemorySink::new();
+        let worker = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(sink.clone())
+            .build()
+            .unwrap()
+            .spawn_async_worker(FlushPolicy::default());
+        let event = GameEventsIOEventBuilder::default()
+            .event("level_up")
+            .build()
+            .unwrap();
+        worker.log_event(event);
+        drop(worker);
+
+        for _ in 0..40 {
+            if !sink.events().is_empty() {
+                break;
+            }
+            tokio::time::sleep(TICK / 2).await;
+        }
+        sink.assert_event_logged("level_up", |_| true);
+    }
+
+    #[tokio::test]
+    async fn test_final_flush_keeps_to_deadline_without_blocking() {
+        use std::sync::atomic::AtomicUsize;
+
+        use crate::{EventSink, GameEventsIOError, RetryPolicy};
+
+        #[derive(Clone, Default)]
+        struct Down(Arc<AtomicUsize>);
+        impl EventSink for Down {
+            fn send(
+                &self,
+                _events: &[GameEventsIOEvent],
+                _timeout: Option<Duration>,
+            ) -> Result<String, GameEventsIOError> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Err(GameEventsIOError::Sink("queue unavailable".into()))
+            }
+        }
+
+        let sink = Down::default();
+        let worker = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(sink.clone())
+            .flush_on_drop(Duration::from_millis(500))
+            .retry_policy(RetryPolicy {
+                max_attempts: 1000,
+                initial_backoff: Duration::from_millis(50),
+                max_backoff: Duration::from_millis(50),
+            })
+            .build()
+            .unwrap()
+            .spawn_async_worker(FlushPolicy::default());
+        worker.log_event(
+            GameEventsIOEventBuilder::default()
+                .event("level_up")
+                .build()
+                .unwrap(),
+        );
+        drop(worker);
+
+        // This single-threaded executor keeps ticking while the task retries,
+        // and the retries stop at the deadline
+        let mut longest_tick = Duration::ZERO;
+        for _ in 0..40 {
+            let started = Instant::now();
+            tokio::time::sleep(Duration::from_millis(25)).await;
+            longest_tick = longest_tick.max(started.elapsed());
+        }
+        let attempts = sink.0.load(Ordering::SeqCst);
+        assert!(attempts > 1);
+        assert!(
+            longest_tick < Duration::from_millis(200),
+            "{:?}",
+            longest_tick
+        );
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(sink.0.load(Ordering::SeqCst), attempts);
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+/// Thresholds at which a client flushes on its own while events are logged
+///
+/// Every threshold is optional and they combine: the client flushes as soon
+/// as any one of them is reached. The default policy has none, so events are
+/// only sent by an explicit flush (or a worker).
+#[derive(Clone, Debug, Default, Builder)]
+#[builder(setter(into, strip_option))]
+#[builder(default)]
+pub struct BatchPolicy {
+    /// Flush once this many events are pending
+    pub max_events: Option<usize>,
+
+    /// Flush once the pending events add up to more than this many bytes of JSON
+    pub max_bytes: Option<usize>,
+
+    /// Flush once the oldest pending event's `time` is this far in the past
+    pub max_age: Option<Duration>,
+}
+
+impl BatchPolicy {
+    /// Whether a queue in this state should be flushed
+    ///
+    /// `oldest` and `now` are Unix timestamps in seconds.
+    pub(crate) fn is_due(&self, count: usize, bytes: usize, oldest: Option<u64>, now: u64) -> bool {
+        if count == 0 {
+            return false;
+        }
+        self.max_events.is_some_and(|max| count >= max)
+            || self.max_bytes.is_some_and(|max| bytes > max)
+            || self
+                .max_age
+                .zip(oldest)
+                .is_some_and(|(max, oldest)| Duration::from_secs(now.saturating_sub(oldest)) >= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thresholds_combine() {
+        let policy = BatchPolicyBuilder::default()
+            .max_events(10usize)
+            .max_bytes(4096usize)
+            .max_age(Duration::from_secs(60))
+            .build()
+            .unwrap();
+
+        assert!(!policy.is_due(0, 0, None, 1000));
+        assert!(!policy.is_due(9, 4096, Some(941), 1000));
+        assert!(policy.is_due(10, 0, Some(1000), 1000));
+        assert!(policy.is_due(1, 4097, Some(1000), 1000));
+        assert!(policy.is_due(1, 0, Some(940), 1000));
+
+        assert!(!BatchPolicy::default().is_due(1000, usize::MAX, Some(0), 1000));
+    }
+}
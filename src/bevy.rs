@@ -0,0 +1,171 @@
+//! Bevy integration (feature `bevy`)
+//!
+//! [`GameEventsIOBevyPlugin`] inserts the client as a resource, so systems
+//! can use `ResMut<GameEventsIOClient>` directly, or send an
+//! [`AnalyticsEvent`] through an `EventWriter`.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use bevy_app::{App, AppExit, Plugin, PostUpdate};
+use bevy_ecs::prelude::*;
+use bevy_time::{Time, Timer, TimerMode};
+use bevy_window::WindowFocused;
+
+use crate::{GameEventsIOClient, GameEventsIOEvent, GameEventsIOEventBuilder};
+
+impl Resource for GameEventsIOClient {}
+
+/// Bevy event that is queued on the client at the end of the frame
+#[derive(Event, Clone, Debug)]
+pub struct AnalyticsEvent(pub GameEventsIOEvent);
+
+impl From<GameEventsIOEvent> for AnalyticsEvent {
+    fn from(event: GameEventsIOEvent) -> Self {
+        AnalyticsEvent(event)
+    }
+}
+
+/// Adds a [`GameEventsIOClient`] resource to the app
+///
+/// Logs `app_start` when added, `app_focus` / `app_background` when a window
+/// gains or loses focus and `app_quit` on [`AppExit`], after which the client
+/// is shut down. Pending events are flushed every `flush_interval` from
+/// `PostUpdate`; the flush blocks that frame, so keep the interval long.
+pub struct GameEventsIOBevyPlugin {
+    client: Mutex<Option<GameEventsIOClient>>,
+    flush_interval: Duration,
+    shutdown_deadline: Duration,
+}
+
+impl GameEventsIOBevyPlugin {
+    /// Wrap a client, flushing every 30 seconds
+    pub fn new(client: GameEventsIOClient) -> Self {
+        GameEventsIOBevyPlugin {
+            client: Mutex::new(Some(client)),
+            flush_interval: Duration::from_secs(30),
+            shutdown_deadline: Duration::from_secs(2),
+        }
+    }
+
+    /// How often pending events are flushed
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// How long the final flush on [`AppExit`] may take
+    pub fn shutdown_deadline(mut self, deadline: Duration) -> Self {
+        self.shutdown_deadline = deadline;
+        self
+    }
+}
+
+#[derive(Resource)]
+struct FlushTimer(Timer);
+
+#[derive(Resource)]
+struct ShutdownDeadline(Duration);
+
+impl Plugin for GameEventsIOBevyPlugin {
+    fn build(&self, app: &mut App) {
+        let mut client = self
+            .client
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .expect("GameEventsIOBevyPlugin can only be added once");
+        log_lifecycle(&mut client, "app_start");
+
+        app.insert_resource(client)
+            .insert_resource(FlushTimer(Timer::new(
+                self.flush_interval,
+                TimerMode::Repeating,
+            )))
+            .insert_resource(ShutdownDeadline(self.shutdown_deadline))
+            .init_resource::<Time>()
+            .add_event::<AnalyticsEvent>()
+            .add_event::<WindowFocused>()
+            .add_systems(
+                PostUpdate,
+                (forward_events, track_focus, flush_on_timer, flush_on_exit).chain(),
+            );
+    }
+}
+
+fn log_lifecycle(client: &mut GameEventsIOClient, name: &'static str) {
+    if let Ok(event) = GameEventsIOEventBuilder::default().event(name).build() {
+        client.log_event(event);
+    }
+}
+
+fn forward_events(mut events: EventReader<AnalyticsEvent>, mut client: ResMut<GameEventsIOClient>) {
+    for AnalyticsEvent(event) in events.read() {
+        client.log_event(event.clone());
+    }
+}
+
+fn track_focus(mut focus: EventReader<WindowFocused>, mut client: ResMut<GameEventsIOClient>) {
+    for event in focus.read() {
+        let name = if event.focused {
+            "app_focus"
+        } else {
+            "app_background"
+        };
+        log_lifecycle(&mut client, name);
+    }
+}
+
+fn flush_on_timer(
+    time: Res<Time>,
+    mut timer: ResMut<FlushTimer>,
+    mut client: ResMut<GameEventsIOClient>,
+) {
+    if timer.0.tick(time.delta()).just_finished() && client.pending_events_count() > 0 {
+        let _ = client.flush();
+    }
+}
+
+fn flush_on_exit(
+    mut exit: EventReader<AppExit>,
+    deadline: Res<ShutdownDeadline>,
+    mut client: ResMut<GameEventsIOClient>,
+    mut done: Local<bool>,
+) {
+    if exit.read().next().is_some() && !*done {
+        *done = true;
+        log_lifecycle(&mut client, "app_quit");
+        let _ = client.shutdown(deadline.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOClientBuilder;
+
+    #[test]
+    fn test_plugin_forwards_events_and_focus() {
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .build()
+            .unwrap();
+        let mut app = App::new();
+        app.add_plugins(GameEventsIOBevyPlugin::new(client));
+
+        let event = GameEventsIOEventBuilder::default()
+            .event("level_up")
+            .build()
+            .unwrap();
+        app.world_mut().send_event(AnalyticsEvent(event));
+        app.world_mut().send_event(WindowFocused {
+            window: Entity::PLACEHOLDER,
+            focused: false,
+        });
+        app.update();
+
+        let client = app.world().resource::<GameEventsIOClient>();
+        let names: Vec<_> = client.events.iter().map(|e| e.event.as_ref()).collect();
+        assert_eq!(names, ["app_start", "level_up", "app_background"]);
+    }
+}
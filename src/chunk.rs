@@ -0,0 +1,119 @@
+//! Splitting flushed batches into requests under a size limit
+
+use std::io;
+
+use serde::Serialize;
+
+use crate::transport::Received;
+use crate::{GameEventsIOError, GameEventsIOEvent};
+
+/// What happened to one request of the most recent flush
+///
+/// A flush sends one request per chunk of at most `max_request_bytes`. It
+/// stops at the first chunk that fails; later chunks stay queued and are not
+/// listed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkOutcome {
+    /// Number of events in the request
+    pub events: usize,
+
+    /// How many of them the backend rejected; see
+    /// [`last_flush_rejected`](crate::GameEventsIOClient::last_flush_rejected)
+    pub rejected: usize,
+
+    /// Size of the JSON body before compression
+    pub bytes: usize,
+
+    /// The response, or the message of the error the request failed with
+    pub result: Result<String, String>,
+}
+
+impl ChunkOutcome {
+    pub(crate) fn new(
+        events: usize,
+        rejected: usize,
+        bytes: usize,
+        result: &Result<Received, GameEventsIOError>,
+    ) -> Self {
+        ChunkOutcome {
+            events,
+            rejected,
+            bytes,
+            result: match result {
+                Ok(received) => Ok(received.body.clone()),
+                Err(e) => Err(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Remove the longest prefix of `events` whose JSON array fits in `max_bytes`
+///
+/// Always takes at least one event, however large. Returns the prefix and the
+/// size of its JSON array.
+pub(crate) fn take_chunk(
+    events: &mut Vec<GameEventsIOEvent>,
+    max_bytes: Option<usize>,
+) -> (Vec<GameEventsIOEvent>, usize) {
+    let mut count = 0;
+    // Brackets, plus a comma before every event but the first
+    let mut bytes = 1;
+    for event in events.iter() {
+        let len = json_len(event) + 1;
+        if count > 0 && max_bytes.is_some_and(|max| bytes + len > max) {
+            break;
+        }
+        bytes += len;
+        count += 1;
+    }
+    (events.drain(..count).collect(), bytes.max(2))
+}
+
+/// Size of `value`'s JSON, counted without storing it; 0 if it cannot be
+/// serialized
+pub(crate) fn json_len<T: Serialize + ?Sized>(value: &T) -> usize {
+    let mut count = ByteCount(0);
+    serde_json::to_writer(&mut count, value).map_or(0, |()| count.0)
+}
+
+/// Writer that only counts the bytes written to it
+struct ByteCount(usize);
+
+impl io::Write for ByteCount {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+
+    #[test]
+    fn test_take_chunk_stays_under_limit() {
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .user_id("user123")
+            .build()
+            .unwrap();
+        let len = serde_json::to_vec(&event).unwrap().len();
+        let mut events = vec![event; 5];
+
+        let (chunk, bytes) = take_chunk(&mut events, Some(2 * len + 3));
+        assert_eq!(chunk.len(), 2);
+        assert_eq!(bytes, serde_json::to_vec(&chunk).unwrap().len());
+        assert_eq!(json_len(&chunk), bytes);
+        assert_eq!(events.len(), 3);
+
+        // An event larger than the limit is still sent on its own
+        assert_eq!(take_chunk(&mut events, Some(1)).0.len(), 1);
+        assert_eq!(take_chunk(&mut events, None).0.len(), 2);
+        assert!(events.is_empty());
+    }
+}
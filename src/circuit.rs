@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+/// Stops flushing for a while once the destination keeps failing
+///
+/// After `failure_threshold` consecutive failed attempts (retries included)
+/// flushes fail fast with [`GameEventsIOError::CircuitOpen`] until
+/// `cool_down` has passed. The next flush then makes a single trial attempt:
+/// success closes the breaker, failure opens it for another `cool_down`.
+/// Only errors for which [`GameEventsIOError::is_retryable`] is true count.
+///
+/// [`GameEventsIOError::CircuitOpen`]: crate::GameEventsIOError::CircuitOpen
+/// [`GameEventsIOError::is_retryable`]: crate::GameEventsIOError::is_retryable
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(into))]
+#[builder(default)]
+pub struct CircuitBreaker {
+    /// Consecutive failed attempts that open the breaker
+    pub failure_threshold: u32,
+
+    /// How long the breaker stays open
+    pub cool_down: Duration,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        CircuitBreaker {
+            failure_threshold: 5,
+            cool_down: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Whether a client's circuit breaker lets flushes through
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Flushes go through as usual
+    Closed,
+
+    /// Flushes fail without trying for this much longer
+    Open { retry_in: Duration },
+
+    /// The cool-down is over; the next attempt decides whether to close or
+    /// reopen the breaker
+    HalfOpen,
+}
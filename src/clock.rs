@@ -0,0 +1,80 @@
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// Source of the wall-clock time events are stamped with
+///
+/// Clients and sessions use [`SystemClock`] unless given another one; tests
+/// can pass a [`FixedClock`] to freeze time.
+pub trait Clock: Send + Sync {
+    /// The current time
+    fn now(&self) -> SystemTime;
+}
+
+impl fmt::Debug for dyn Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Clock")
+    }
+}
+
+/// The system's wall clock
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that only moves when told to
+///
+/// Clones share the same time: give one to the client or session and move
+/// the other.
+#[derive(Clone, Debug)]
+pub struct FixedClock {
+    now: Arc<Mutex<SystemTime>>,
+}
+
+impl FixedClock {
+    /// Freeze time at `now`
+    pub fn new(now: SystemTime) -> Self {
+        FixedClock {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    /// Freeze time at a Unix timestamp in seconds
+    pub fn at_unix_secs(secs: u64) -> Self {
+        FixedClock::new(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    /// Jump to `now`
+    pub fn set(&self, now: SystemTime) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) = now;
+    }
+
+    /// Move time forward by `by`
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Seconds since the Unix epoch, or 0 for earlier times
+pub(crate) fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Milliseconds since the Unix epoch, or 0 for earlier times
+pub(crate) fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
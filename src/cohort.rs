@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+/// User property [`attach_cohorts`](crate::GameEventsIOClient::attach_cohorts)
+/// stores a user's cohort ids in
+pub const COHORTS_PROPERTY: &str = "cohorts";
+
+/// Body of a cohort membership request
+#[derive(Serialize)]
+pub(crate) struct CohortRequest<'a> {
+    pub user_id: &'a str,
+}
+
+/// The backend's answer to a cohort membership request
+#[derive(Deserialize)]
+pub(crate) struct CohortResponse {
+    #[serde(default)]
+    pub cohorts: Vec<String>,
+}
+
+/// Derive the cohorts endpoint from the events endpoint
+///
+/// "https://host/v1/events" -> "https://host/v1/cohorts"
+pub(crate) fn default_url(backend_url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(backend_url).ok()?;
+    url.join("cohorts").ok().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_url() {
+        assert_eq!(
+            default_url("https://api.game-events.io/v1/events").as_deref(),
+            Some("https://api.game-events.io/v1/cohorts")
+        );
+
+        let response: CohortResponse =
+            serde_json::from_str(r#"{"cohorts":["lapsed_spenders"]}"#).unwrap();
+        assert_eq!(response.cohorts, ["lapsed_spenders"]);
+    }
+}
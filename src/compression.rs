@@ -0,0 +1,113 @@
+use serde::Serialize;
+
+use crate::PayloadFormat;
+
+/// Encoding applied to the request body when flushing events
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Send the payload as it is
+    #[default]
+    None,
+
+    /// Gzip the body and set `Content-Encoding: gzip`
+    #[cfg(feature = "compression")]
+    Gzip,
+}
+
+impl Compression {
+    /// Value for the `Content-Encoding` header, if any
+    pub(crate) fn content_encoding(self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            #[cfg(feature = "compression")]
+            Compression::Gzip => Some("gzip"),
+        }
+    }
+
+    /// Serialize a batch of events into a request body, in one pass
+    ///
+    /// With gzip the events are written into the encoder as it goes, so the
+    /// uncompressed payload is never held in memory.
+    pub(crate) fn encode<T: Serialize + ?Sized>(
+        self,
+        format: PayloadFormat,
+        events: &T,
+    ) -> Result<Vec<u8>, serde_json::Error> {
+        match self {
+            Compression::None => {
+                let mut body = Vec::new();
+                format.write(&mut body, events)?;
+                Ok(body)
+            }
+            #[cfg(feature = "compression")]
+            Compression::Gzip => {
+                let encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                // serde_json makes many small writes, which the encoder
+                // would otherwise compress one at a time
+                let mut writer = std::io::BufWriter::new(encoder);
+                format.write(&mut writer, events)?;
+                // Writing into a Vec cannot fail
+                let encoder = writer.into_inner().expect("failed to gzip request body");
+                Ok(encoder.finish().expect("failed to gzip request body"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Counts how many times it is serialized
+    struct Passes(Cell<usize>);
+
+    impl Serialize for Passes {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.set(self.0.get() + 1);
+            serializer.serialize_str("level_completed")
+        }
+    }
+
+    #[test]
+    fn test_body_serialized_once() {
+        let passes = Passes(Cell::new(0));
+        let body = Compression::None
+            .encode(PayloadFormat::Json, &passes)
+            .unwrap();
+        assert_eq!(body, br#""level_completed""#);
+        assert_eq!(passes.0.get(), 1);
+
+        #[cfg(feature = "compression")]
+        {
+            Compression::Gzip
+                .encode(PayloadFormat::Json, &passes)
+                .unwrap();
+            assert_eq!(passes.0.get(), 2);
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_gzip_round_trip() {
+        use std::io::Read;
+
+        let event = crate::GameEventsIOEventBuilder::default()
+            .event("level_completed")
+            .build()
+            .unwrap();
+        let events = vec![event; 100];
+        let body = crate::to_payload(&events).unwrap();
+        let encoded = Compression::Gzip
+            .encode(PayloadFormat::Json, &events)
+            .unwrap();
+        assert!(encoded.len() < body.len());
+
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(encoded.as_slice())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, body);
+    }
+}
@@ -0,0 +1,230 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::{BatchPolicy, Environment, GameEventsIOClientBuilder, Properties, Region};
+
+/// Client settings read from a TOML file, e.g. one shipped per title
+///
+/// ```toml
+/// api_key = "YOUR_API_KEY"
+/// region = "eu" # or `endpoint = "https://collector.example.com/v1/events"`
+/// environment = "staging"
+/// sample_rate = 0.5
+/// max_pending_events = 10000
+/// event_ttl_days = 7
+/// max_retained_bytes = 10485760
+///
+/// [batch]
+/// max_events = 100
+/// max_bytes = 65536
+/// flush_interval = 30 # seconds
+///
+/// [persistence]
+/// spool_path = "saves/analytics.spool"
+/// state_dir = "saves/analytics"
+/// crash_dir = "saves/crashes"
+/// # or, with the `sqlite` feature, instead of `spool_path` and `state_dir`:
+/// # sqlite_path = "saves/analytics.db"
+/// # sqlite_retention_days = 30
+///
+/// [super_properties]
+/// title = "space-game"
+/// ```
+///
+/// Every key is optional; unknown keys are an error, so typos do not go
+/// unnoticed.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    pub api_key: Option<String>,
+
+    /// Sets `backend_url`
+    pub endpoint: Option<String>,
+
+    /// "us", "eu", "apac" or an events URL; conflicts with `endpoint`
+    pub region: Option<String>,
+
+    pub environment: Option<String>,
+
+    pub sample_rate: Option<f64>,
+
+    pub max_pending_events: Option<usize>,
+
+    /// Days, read into `event_ttl`
+    pub event_ttl_days: Option<u64>,
+
+    pub max_retained_bytes: Option<usize>,
+
+    pub batch: BatchConfig,
+
+    pub persistence: PersistenceConfig,
+
+    pub super_properties: Properties,
+}
+
+/// `[batch]` table of a [`ClientConfig`], read into a [`BatchPolicy`]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BatchConfig {
+    pub max_events: Option<usize>,
+
+    pub max_bytes: Option<usize>,
+
+    /// Seconds, read into `max_age`
+    pub flush_interval: Option<u64>,
+}
+
+/// `[persistence]` table of a [`ClientConfig`]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PersistenceConfig {
+    pub spool_path: Option<PathBuf>,
+
+    pub state_dir: Option<PathBuf>,
+
+    /// Ignored on the web, which has no panic hook
+    pub crash_dir: Option<PathBuf>,
+
+    /// SQLite database replacing `spool_path` and `state_dir`; ignored
+    /// without the `sqlite` feature
+    pub sqlite_path: Option<PathBuf>,
+
+    /// Days, read into `sqlite_retention`
+    pub sqlite_retention_days: Option<u64>,
+}
+
+impl ClientConfig {
+    /// Read a config file
+    ///
+    /// Errors name the file and, for invalid values, the offending key.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        Self::from_toml(&text).map_err(|e| format!("invalid {}: {}", path.display(), e))
+    }
+
+    /// Parse a config from TOML text
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// A client builder with these settings
+    ///
+    /// Setters called on it override the file, and
+    /// [`env_overrides`](GameEventsIOClientBuilder::env_overrides) can
+    /// override both.
+    pub fn builder(&self) -> GameEventsIOClientBuilder {
+        let mut builder = GameEventsIOClientBuilder::default();
+        if let Some(api_key) = &self.api_key {
+            builder.api_key(api_key);
+        }
+        if let Some(endpoint) = &self.endpoint {
+            builder.backend_url(endpoint);
+        }
+        if let Some(region) = &self.region {
+            builder.region(Region::from(region.as_str()));
+        }
+        if let Some(environment) = &self.environment {
+            builder.environment(Environment::from(environment.as_str()));
+        }
+        if let Some(sample_rate) = self.sample_rate {
+            builder.sample_rate(sample_rate);
+        }
+        if let Some(max_pending_events) = self.max_pending_events {
+            builder.max_pending_events(max_pending_events);
+        }
+        if let Some(days) = self.event_ttl_days {
+            builder.event_ttl(Duration::from_secs(days.saturating_mul(24 * 60 * 60)));
+        }
+        if let Some(max_retained_bytes) = self.max_retained_bytes {
+            builder.max_retained_bytes(max_retained_bytes);
+        }
+        if self.batch != BatchConfig::default() {
+            builder.batch_policy(BatchPolicy {
+                max_events: self.batch.max_events,
+                max_bytes: self.batch.max_bytes,
+                max_age: self.batch.flush_interval.map(Duration::from_secs),
+            });
+        }
+        #[cfg(feature = "persistence")]
+        if let Some(path) = &self.persistence.spool_path {
+            builder.spool_path(path);
+        }
+        #[cfg(feature = "persistence")]
+        if let Some(dir) = &self.persistence.state_dir {
+            builder.state_dir(dir);
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some(path) = &self.persistence.sqlite_path {
+            builder.sqlite_path(path);
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some(days) = self.persistence.sqlite_retention_days {
+            builder.sqlite_retention(Duration::from_secs(days.saturating_mul(24 * 60 * 60)));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(dir) = &self.persistence.crash_dir {
+            builder.crash_dir(dir);
+        }
+        if !self.super_properties.is_empty() {
+            builder.super_properties(self.super_properties.clone());
+        }
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_from_toml() {
+        let config = ClientConfig::from_toml(
+            r#"
+            api_key = "key-1"
+            endpoint = "https://collector.local/v1/events"
+            environment = "qa"
+            sample_rate = 0.5
+            event_ttl_days = 7
+
+            [batch]
+            max_events = 100
+            flush_interval = 30
+
+            [super_properties]
+            title = "space-game"
+            season = 3
+            "#,
+        )
+        .unwrap();
+
+        let client = config.builder().build().unwrap();
+        assert_eq!(client.backend_url(), "https://collector.local/v1/events");
+        assert_eq!(client.environment(), &Environment::Custom("qa".to_string()));
+        assert_eq!(client.sample_rate, 0.5);
+        assert_eq!(client.batch_policy.max_events, Some(100));
+        assert_eq!(client.batch_policy.max_age, Some(Duration::from_secs(30)));
+        assert_eq!(client.event_ttl, Some(Duration::from_secs(7 * 24 * 60 * 60)));
+        assert_eq!(client.super_properties()["title"], "space-game");
+        assert_eq!(client.super_properties()["season"], 3);
+
+        // Code overrides the file
+        let client = config.builder().sample_rate(1.0).build().unwrap();
+        assert_eq!(client.sample_rate, 1.0);
+    }
+
+    #[test]
+    fn test_errors_name_the_key() {
+        let error = ClientConfig::from_toml("[batch]\nmax_events = \"lots\"\n").unwrap_err();
+        assert!(error.contains("max_events"), "{}", error);
+
+        let error = ClientConfig::from_toml("sample_rat = 0.5\n").unwrap_err();
+        assert!(error.contains("sample_rat"), "{}", error);
+
+        let error = ClientConfig::from_file("/nonexistent/game-events.toml").unwrap_err();
+        assert!(error.contains("/nonexistent/game-events.toml"), "{}", error);
+    }
+}
@@ -0,0 +1,33 @@
+/// Whether the player has agreed to analytics tracking
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConsentState {
+    /// The player has not decided yet; events are buffered but not sent
+    Pending,
+
+    /// Events are buffered and sent as usual
+    #[default]
+    Granted,
+
+    /// Nothing is sent; buffered events are purged and new ones dropped
+    Denied,
+}
+
+impl ConsentState {
+    /// Value stored in the state directory
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            ConsentState::Pending => "pending",
+            ConsentState::Granted => "granted",
+            ConsentState::Denied => "denied",
+        }
+    }
+
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(ConsentState::Pending),
+            "granted" => Some(ConsentState::Granted),
+            "denied" => Some(ConsentState::Denied),
+            _ => None,
+        }
+    }
+}
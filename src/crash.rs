@@ -0,0 +1,227 @@
+//! Opt-in panic hook that saves crashes for the next launch
+//!
+//! A panic cannot reliably reach the network, so the hook only writes a
+//! `crash` event to `crash_dir`. The next client built with the same
+//! `crash_dir` queues every saved crash, and deletes each file once its event
+//! is in the client's storage or has been sent.
+
+use std::backtrace::Backtrace;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use uuid::Uuid;
+
+use crate::MAX_PROPERTY_VALUE_SIZE;
+use crate::{GameEventsIOEvent, GameEventsIOEventBuilder, GameEventsIOSession};
+
+/// Name of the events saved by the panic hook
+pub const CRASH_EVENT: &str = "crash";
+
+#[derive(Debug)]
+struct CrashContext {
+    user_id: String,
+    session_id: String,
+}
+
+/// Handle to an installed panic hook
+///
+/// Crashes are attributed to the session passed to
+/// [`GameEventsIOClient::install_panic_hook`](crate::GameEventsIOClient::install_panic_hook);
+/// call [`set_session`](Self::set_session) when the player switches sessions.
+#[derive(Clone, Debug)]
+pub struct CrashReporter {
+    context: Arc<Mutex<CrashContext>>,
+}
+
+impl CrashReporter {
+    pub(crate) fn install(dir: PathBuf, session: &GameEventsIOSession) -> Self {
+        let context = Arc::new(Mutex::new(CrashContext {
+            user_id: session.user_id().to_string(),
+            session_id: session.session_id().to_string(),
+        }));
+
+        let hook_context = Arc::clone(&context);
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let context = hook_context.lock().unwrap_or_else(|e| e.into_inner());
+            let _ = save(&dir, crash_event(&context, info));
+            drop(context);
+            previous(info);
+        }));
+
+        CrashReporter { context }
+    }
+
+    /// Attribute future crashes to `session`
+    pub fn set_session(&self, session: &GameEventsIOSession) {
+        let mut context = self.context.lock().unwrap_or_else(|e| e.into_inner());
+        context.user_id = session.user_id().to_string();
+        context.session_id = session.session_id().to_string();
+    }
+}
+
+fn crash_event(context: &CrashContext, info: &PanicHookInfo<'_>) -> Option<GameEventsIOEvent> {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+
+    let mut props = HashMap::new();
+    props.insert("message".into(), truncate(message).into());
+    if let Some(location) = info.location() {
+        props.insert("location".into(), location.to_string().into());
+    }
+    if let Some(thread) = std::thread::current().name() {
+        props.insert("thread".into(), thread.into());
+    }
+    props.insert(
+        "backtrace".into(),
+        truncate(&Backtrace::force_capture().to_string()).into(),
+    );
+
+    GameEventsIOEventBuilder::default()
+        .event(CRASH_EVENT)
+        .user_id(context.user_id.clone())
+        .session_id(context.session_id.clone())
+        .event_properties(props)
+        .build()
+        .ok()
+}
+
+/// Shorten a string so it stays under the property value limit once quoted
+/// and escaped
+pub(crate) fn truncate(text: &str) -> String {
+    let limit = MAX_PROPERTY_VALUE_SIZE / 2;
+    if text.len() <= limit {
+        return text.to_string();
+    }
+    let mut end = limit;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text[..end].to_string()
+}
+
+fn save(dir: &Path, event: Option<GameEventsIOEvent>) -> io::Result<()> {
+    let Some(event) = event else {
+        return Ok(());
+    };
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("crash-{}.json", Uuid::new_v4()));
+    fs::write(path, serde_json::to_vec(&event)?)
+}
+
+/// Every crash saved in `dir` with the file it is in, oldest first
+///
+/// Files that cannot be read are left for the next launch; files that do not
+/// parse never will, and are deleted.
+pub(crate) fn saved(dir: &Path) -> Vec<(PathBuf, GameEventsIOEvent)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut crashes = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_crash = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("crash-") && name.ends_with(".json"));
+        if !is_crash {
+            continue;
+        }
+        let Ok(bytes) = fs::read(&path) else {
+            continue;
+        };
+        match serde_json::from_slice::<GameEventsIOEvent>(&bytes) {
+            Ok(event) => crashes.push((path, event)),
+            Err(_) => {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+    crashes.sort_by_key(|(_, event)| event.time);
+    crashes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOClientBuilder;
+
+    #[test]
+    fn test_saved_crash_is_queued_on_next_launch() {
+        let dir = std::env::temp_dir().join(format!("crash-{}", Uuid::new_v4()));
+        let mut props = HashMap::new();
+        props.insert("message".into(), "boom".into());
+        let event = GameEventsIOEventBuilder::default()
+            .event(CRASH_EVENT)
+            .user_id("user123")
+            .session_id("session456")
+            .event_properties(props)
+            .build()
+            .unwrap();
+        save(&dir, Some(event)).unwrap();
+        fs::write(dir.join("crash-torn.json"), b"{\"event\":").unwrap();
+
+        // Sampling does not drop crashes
+        let sink = crate::MemorySink::new();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .crash_dir(&dir)
+            .sample_rate(0.0)
+            .sink(sink.clone())
+            .build()
+            .unwrap();
+        assert_eq!(client.pending_events_count(), 1);
+        let crash = client.events.front().unwrap();
+        assert_eq!(crash.event, CRASH_EVENT);
+        assert_eq!(crash.session_id, "session456");
+        assert_eq!(crash.event_properties["message"], "boom");
+
+        // Without storage the file is the only other copy until it is sent
+        assert_eq!(saved(&dir).len(), 1);
+        assert!(!dir.join("crash-torn.json").exists());
+        client.flush().unwrap();
+        assert_eq!(sink.events_named(CRASH_EVENT).len(), 1);
+        assert!(saved(&dir).is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_saved_crash_is_deleted_once_spooled() {
+        let dir = std::env::temp_dir().join(format!("crash-{}", Uuid::new_v4()));
+        let event = GameEventsIOEventBuilder::default()
+            .event(CRASH_EVENT)
+            .build()
+            .unwrap();
+        save(&dir, Some(event)).unwrap();
+
+        let spool = dir.join("spool.bin");
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .crash_dir(&dir)
+            .spool_path(&spool)
+            .build()
+            .unwrap();
+        assert!(saved(&dir).is_empty());
+        drop(client);
+
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .crash_dir(&dir)
+            .spool_path(&spool)
+            .build()
+            .unwrap();
+        assert_eq!(client.pending_events_count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
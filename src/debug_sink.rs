@@ -0,0 +1,126 @@
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::{EventSink, GameEventsIOError, GameEventsIOEvent};
+
+/// Environment variable that makes clients print events instead of sending them
+///
+/// Any value other than empty, "0" or "false" enables the [`DebugSink`] for
+/// clients built without an explicit sink.
+pub const DEBUG_ENV_VAR: &str = "GAME_EVENTS_IO_DEBUG";
+
+/// Pretty-prints flushed events instead of sending them, for local development
+///
+/// Output goes to stderr and is colorized when stderr is a terminal and
+/// `NO_COLOR` is not set.
+pub struct DebugSink {
+    out: Mutex<Box<dyn Write + Send>>,
+    color: bool,
+}
+
+impl DebugSink {
+    /// Print events to stderr
+    pub fn new() -> Self {
+        let color = io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        DebugSink {
+            out: Mutex::new(Box::new(io::stderr())),
+            color,
+        }
+    }
+
+    /// Print events to `out` without colors
+    pub fn to_writer(out: impl Write + Send + 'static) -> Self {
+        DebugSink {
+            out: Mutex::new(Box::new(out)),
+            color: false,
+        }
+    }
+
+    /// Whether the debug environment variable is set
+    pub(crate) fn enabled_by_env() -> bool {
+        std::env::var(DEBUG_ENV_VAR).is_ok_and(|value| !matches!(value.trim(), "" | "0" | "false"))
+    }
+}
+
+impl Default for DebugSink {
+    fn default() -> Self {
+        DebugSink::new()
+    }
+}
+
+impl fmt::Debug for DebugSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebugSink")
+            .field("color", &self.color)
+            .finish()
+    }
+}
+
+impl EventSink for DebugSink {
+    fn send(
+        &self,
+        events: &[GameEventsIOEvent],
+        _timeout: Option<Duration>,
+    ) -> Result<String, GameEventsIOError> {
+        let mut text = String::new();
+        for event in events {
+            text.push_str(&render(event, self.color)?);
+        }
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        out.write_all(text.as_bytes())
+            .and_then(|_| out.flush())
+            .map_err(|e| GameEventsIOError::Sink(Box::new(e)))?;
+        Ok(format!("Printed {} events", events.len()))
+    }
+}
+
+/// One event as a header line followed by its indented JSON
+fn render(event: &GameEventsIOEvent, color: bool) -> Result<String, GameEventsIOError> {
+    let (bold, dim, reset) = if color {
+        ("\x1b[1;36m", "\x1b[2m", "\x1b[0m")
+    } else {
+        ("", "", "")
+    };
+    let body = serde_json::to_string_pretty(event)?;
+    let mut text = format!(
+        "{}{}{} {}user_id={} session_id={}{}\n",
+        bold, event.event, reset, dim, event.user_id, event.session_id, reset
+    );
+    for line in body.lines() {
+        text.push_str("  ");
+        text.push_str(line);
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+
+    #[test]
+    fn test_render() {
+        let event = GameEventsIOEventBuilder::default()
+            .event("level_completed")
+            .user_id("user123")
+            .session_id("session456")
+            .time(0u64)
+            .build()
+            .unwrap();
+
+        let plain = render(&event, false).unwrap();
+        let mut lines = plain.lines();
+        assert_eq!(
+            lines.next(),
+            Some("level_completed user_id=user123 session_id=session456")
+        );
+        assert_eq!(lines.next(), Some("  {"));
+        assert!(plain.contains("  \"event\": \"level_completed\","));
+
+        let colored = render(&event, true).unwrap();
+        assert!(colored.starts_with("\x1b[1;36mlevel_completed\x1b[0m"));
+    }
+}
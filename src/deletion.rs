@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// Body of a user data deletion request
+#[derive(Serialize)]
+pub(crate) struct DeletionRequest<'a> {
+    pub user_id: &'a str,
+}
+
+/// Acknowledgement of a user data deletion request
+///
+/// Deletion is processed asynchronously by the backend; keep the ticket id
+/// to follow up on the request.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct DeletionReceipt {
+    /// Identifier of the deletion ticket
+    pub ticket_id: String,
+
+    /// Processing status reported by the backend, e.g. "pending"
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+/// Derive the deletion endpoint from the events endpoint
+///
+/// "https://host/v1/events" -> "https://host/v1/deletion-requests"
+pub(crate) fn default_url(backend_url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(backend_url).ok()?;
+    url.join("deletion-requests").ok().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_url() {
+        assert_eq!(
+            default_url("https://api.game-events.io/v1/events").as_deref(),
+            Some("https://api.game-events.io/v1/deletion-requests")
+        );
+
+        let receipt: DeletionReceipt = serde_json::from_str(r#"{"ticket_id":"del_1"}"#).unwrap();
+        assert_eq!(receipt.ticket_id, "del_1");
+        assert_eq!(receipt.status, None);
+    }
+}
@@ -0,0 +1,147 @@
+
+use serde::Serialize;
+
+use crate::Properties;
+
+/// Information about the device the game runs on
+///
+/// Collected on a best-effort basis from the standard library, environment
+/// variables and well-known system files; fields that cannot be determined
+/// on the current platform are left out.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct DeviceMetadata {
+    /// Operating system, e.g. "linux", "macos", "windows"
+    pub os: String,
+
+    /// Operating system version, e.g. "22.04" or "14.2.1"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os_version: Option<String>,
+
+    /// CPU architecture, e.g. "x86_64", "aarch64"
+    pub arch: String,
+
+    /// BCP 47 style locale, e.g. "en-US"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+
+    /// Hardware model, e.g. "MacBookPro18,3"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_model: Option<String>,
+}
+
+impl DeviceMetadata {
+    /// Collect metadata for the current device
+    pub fn collect() -> Self {
+        DeviceMetadata {
+            os: std::env::consts::OS.to_string(),
+            os_version: os_version(),
+            arch: std::env::consts::ARCH.to_string(),
+            locale: locale_from_env(|key| std::env::var(key).ok()),
+            device_model: device_model(),
+        }
+    }
+
+    /// The metadata as event properties
+    pub fn to_properties(&self) -> Properties {
+        crate::to_properties(self)
+    }
+}
+
+/// Read the locale from the POSIX locale variables
+fn locale_from_env(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let raw = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(var)
+        .find(|value| !value.is_empty())?;
+
+    // "en_US.UTF-8@euro" -> "en-US"
+    let locale = raw.split(['.', '@']).next().unwrap_or_default();
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return None;
+    }
+    Some(locale.replace('_', "-"))
+}
+
+#[cfg(target_os = "linux")]
+fn os_version() -> Option<String> {
+    let os_release = std::fs::read_to_string("/etc/os-release").ok()?;
+    os_release.lines().find_map(|line| {
+        line.strip_prefix("VERSION_ID=")
+            .map(|v| v.trim_matches('"').to_string())
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn os_version() -> Option<String> {
+    command_output("sw_vers", &["-productVersion"])
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn os_version() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn device_model() -> Option<String> {
+    let model = std::fs::read_to_string("/sys/devices/virtual/dmi/id/product_name").ok()?;
+    Some(model.trim().to_string()).filter(|m| !m.is_empty())
+}
+
+#[cfg(target_os = "macos")]
+fn device_model() -> Option<String> {
+    command_output("sysctl", &["-n", "hw.model"])
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn device_model() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8(output.stdout).ok()?;
+    Some(value.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_from_env() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                pairs
+                    .iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+
+        assert_eq!(
+            locale_from_env(env(&[("LANG", "en_US.UTF-8")])).as_deref(),
+            Some("en-US")
+        );
+        assert_eq!(
+            locale_from_env(env(&[("LC_ALL", "de_DE@euro"), ("LANG", "en_US")])).as_deref(),
+            Some("de-DE")
+        );
+        assert_eq!(locale_from_env(env(&[("LANG", "C.UTF-8")])), None);
+        assert_eq!(locale_from_env(env(&[])), None);
+    }
+
+    #[test]
+    fn test_collect_has_os_and_arch() {
+        let metadata = DeviceMetadata::collect();
+        let props = metadata.to_properties();
+        assert_eq!(props.get("os").unwrap(), std::env::consts::OS);
+        assert_eq!(props.get("arch").unwrap(), std::env::consts::ARCH);
+    }
+}
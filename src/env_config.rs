@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use crate::{Environment, GameEventsIOClientBuilder, Region};
+
+const API_KEY: &str = "GAME_EVENTS_IO_API_KEY";
+const ENDPOINT: &str = "GAME_EVENTS_IO_ENDPOINT";
+const REGION: &str = "GAME_EVENTS_IO_REGION";
+const FLUSH_INTERVAL: &str = "GAME_EVENTS_IO_FLUSH_INTERVAL";
+const BATCH_SIZE: &str = "GAME_EVENTS_IO_BATCH_SIZE";
+const ENVIRONMENT: &str = "GAME_EVENTS_IO_ENVIRONMENT";
+
+/// Override `builder` with the variables `lookup` finds
+///
+/// Unset and empty variables leave the builder as it is.
+pub(crate) fn apply(
+    builder: &mut GameEventsIOClientBuilder,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<(), String> {
+    let var = |key| lookup(key).filter(|value| !value.trim().is_empty());
+    let number = |key| {
+        var(key)
+            .map(|value| {
+                value
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|e| format!("invalid {} {:?}: {}", key, value, e))
+            })
+            .transpose()
+    };
+
+    if let Some(api_key) = var(API_KEY) {
+        builder.api_key(api_key);
+    }
+    let endpoint = var(ENDPOINT);
+    let region = var(REGION);
+    // Either replaces both the endpoint and the region set in code
+    if endpoint.is_some() || region.is_some() {
+        builder.backend_url = None;
+        builder.region = None;
+    }
+    if let Some(endpoint) = endpoint {
+        builder.backend_url(endpoint.trim());
+    }
+    if let Some(region) = region {
+        builder.region(Region::from(region.trim()));
+    }
+    if let Some(environment) = var(ENVIRONMENT) {
+        builder.environment(Environment::from(environment.trim()));
+    }
+    let max_age = number(FLUSH_INTERVAL)?.map(Duration::from_secs);
+    let max_events = number(BATCH_SIZE)?.map(|size| size as usize);
+    if max_age.is_some() || max_events.is_some() {
+        let mut policy = builder.batch_policy.clone().unwrap_or_default();
+        policy.max_age = max_age.or(policy.max_age);
+        policy.max_events = max_events.or(policy.max_events);
+        builder.batch_policy = Some(policy);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::BatchPolicyBuilder;
+
+    #[test]
+    fn test_variables_override_builder() {
+        let vars = HashMap::from([
+            (ENDPOINT, "https://collector.local/v1/events"),
+            (FLUSH_INTERVAL, "30"),
+            (ENVIRONMENT, "staging"),
+            (API_KEY, ""),
+        ]);
+        let mut builder = GameEventsIOClientBuilder::default();
+        builder
+            .api_key("from-code")
+            .backend_url("https://other.local/v1/events")
+            .batch_policy(
+                BatchPolicyBuilder::default()
+                    .max_events(50usize)
+                    .build()
+                    .unwrap(),
+            );
+        apply(&mut builder, |key| {
+            vars.get(key).map(|value| value.to_string())
+        })
+        .unwrap();
+
+        let client = builder.build().unwrap();
+        assert_eq!(client.api_key, "from-code");
+        assert_eq!(client.backend_url(), "https://collector.local/v1/events");
+        assert_eq!(client.environment(), &Environment::Staging);
+        assert_eq!(client.batch_policy.max_age, Some(Duration::from_secs(30)));
+        assert_eq!(client.batch_policy.max_events, Some(50));
+
+        let mut builder = GameEventsIOClientBuilder::default();
+        builder
+            .api_key("from-code")
+            .backend_url("https://other.local/v1/events");
+        apply(&mut builder, |key| {
+            (key == REGION).then(|| "eu".to_string())
+        })
+        .unwrap();
+        let client = builder.build().unwrap();
+        assert_eq!(client.region(), Some(&Region::Eu));
+        assert_eq!(
+            client.backend_url(),
+            "https://eu.api.game-events.io/v1/events"
+        );
+
+        let error = apply(&mut builder, |key| {
+            (key == BATCH_SIZE).then(|| "lots".to_string())
+        })
+        .unwrap_err();
+        assert!(error.contains(BATCH_SIZE), "{}", error);
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+
+/// Deployment stage a client reports from
+///
+/// Stamped onto every event so dashboards can filter out test traffic, and
+/// used to pick a backend URL registered with
+/// [`GameEventsIOClientBuilder::environment_endpoint`](crate::GameEventsIOClientBuilder::environment_endpoint).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Environment {
+    Development,
+    Staging,
+    #[default]
+    Production,
+
+    /// Any other stage, e.g. "qa"
+    Custom(String),
+}
+
+impl Environment {
+    /// Value sent with events
+    pub fn as_str(&self) -> &str {
+        match self {
+            Environment::Development => "development",
+            Environment::Staging => "staging",
+            Environment::Production => "production",
+            Environment::Custom(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for Environment {
+    fn from(name: &str) -> Self {
+        match name {
+            "development" | "dev" => Environment::Development,
+            "staging" => Environment::Staging,
+            "production" | "prod" => Environment::Production,
+            _ => Environment::Custom(name.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(Environment::from("dev"), Environment::Development);
+        assert_eq!(Environment::from("prod").as_str(), "production");
+        assert_eq!(
+            Environment::from("qa"),
+            Environment::Custom("qa".to_string())
+        );
+        assert_eq!(Environment::Staging.to_string(), "staging");
+    }
+}
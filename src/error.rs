@@ -0,0 +1,270 @@
+use std::fmt;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::time_format;
+
+/// Errors returned when sending events to the backend
+#[derive(Debug)]
+pub enum GameEventsIOError {
+    /// The request could not be sent or the response could not be read
+    Network(reqwest::Error),
+
+    /// The request could not be serialized or the response could not be parsed
+    Serialization(serde_json::Error),
+
+    /// The backend rejected the API key (401 or 403)
+    Auth { status: u16, body: String },
+
+    /// The backend asked the client to slow down (429)
+    RateLimited { retry_after: Option<Duration> },
+
+    /// The backend failed to process the request (5xx); a 503 usually says
+    /// when to try again
+    Server {
+        status: u16,
+        body: String,
+        retry_after: Option<Duration>,
+    },
+
+    /// The backend rejected the request for any other reason (4xx)
+    Rejected { status: u16, body: String },
+
+    /// A custom [`EventSink`](crate::EventSink) failed to deliver the events
+    Sink(Box<dyn std::error::Error + Send + Sync>),
+
+    /// An [`HttpTransport`](crate::HttpTransport) other than reqwest could
+    /// not send the request or read the response
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+
+    /// The request hit one of the client's [`Timeouts`](crate::Timeouts);
+    /// the events stay queued
+    Timeout(Box<dyn std::error::Error + Send + Sync>),
+
+    /// Stored events could not be read
+    Io(std::io::Error),
+
+    /// The [`CircuitBreaker`](crate::CircuitBreaker) is open; nothing was sent
+    CircuitOpen { retry_after: Duration },
+
+    /// A remote configuration document was unsigned or its signature did
+    /// not verify
+    InvalidSignature,
+}
+
+impl GameEventsIOError {
+    /// Classify a non-success response from the backend
+    pub(crate) fn from_response(status: StatusCode, headers: &HeaderMap, body: String) -> Self {
+        let code = status.as_u16();
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                GameEventsIOError::Auth { status: code, body }
+            }
+            StatusCode::TOO_MANY_REQUESTS => GameEventsIOError::RateLimited {
+                retry_after: retry_after(headers),
+            },
+            _ if status.is_server_error() => GameEventsIOError::Server {
+                status: code,
+                body,
+                retry_after: retry_after(headers),
+            },
+            _ => GameEventsIOError::Rejected { status: code, body },
+        }
+    }
+
+    /// Whether sending the same request again might succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            GameEventsIOError::Network(_)
+                | GameEventsIOError::RateLimited { .. }
+                | GameEventsIOError::Server { .. }
+                | GameEventsIOError::Sink(_)
+                | GameEventsIOError::Transport(_)
+                | GameEventsIOError::Timeout(_)
+        )
+    }
+
+    /// How long the backend asked the client to wait before trying again
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            GameEventsIOError::RateLimited { retry_after }
+            | GameEventsIOError::Server { retry_after, .. } => *retry_after,
+            GameEventsIOError::CircuitOpen { retry_after } => Some(*retry_after),
+            _ => None,
+        }
+    }
+
+    /// HTTP status code returned by the backend, if the request got that far
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            GameEventsIOError::Network(e) => e.status().map(|s| s.as_u16()),
+            GameEventsIOError::Serialization(_)
+            | GameEventsIOError::Sink(_)
+            | GameEventsIOError::Transport(_)
+            | GameEventsIOError::Timeout(_)
+            | GameEventsIOError::Io(_)
+            | GameEventsIOError::CircuitOpen { .. }
+            | GameEventsIOError::InvalidSignature => None,
+            GameEventsIOError::RateLimited { .. } => Some(429),
+            GameEventsIOError::Auth { status, .. }
+            | GameEventsIOError::Server { status, .. }
+            | GameEventsIOError::Rejected { status, .. } => Some(*status),
+        }
+    }
+}
+
+/// Longest wait a `Retry-After` header is taken at; longer ones are cut to it
+pub(crate) const MAX_RETRY_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Parse a `Retry-After` header given in seconds or as an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    let delay = match value.parse() {
+        Ok(secs) => Duration::from_secs(secs),
+        Err(_) => {
+            let at = UNIX_EPOCH.checked_add(Duration::from_secs(time_format::parse_http_date(
+                value,
+            )?))?;
+            at.duration_since(SystemTime::now()).unwrap_or_default()
+        }
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+impl fmt::Display for GameEventsIOError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameEventsIOError::Network(e) => write!(f, "network error: {}", e),
+            GameEventsIOError::Serialization(e) => write!(f, "serialization error: {}", e),
+            GameEventsIOError::Auth { status, .. } => {
+                write!(f, "authentication failed (HTTP {})", status)
+            }
+            GameEventsIOError::RateLimited {
+                retry_after: Some(delay),
+            } => write!(f, "rate limited, retry after {}s", delay.as_secs()),
+            GameEventsIOError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            GameEventsIOError::Server { status, body, .. } => {
+                write!(f, "server error (HTTP {}): {}", status, body)
+            }
+            GameEventsIOError::Rejected { status, body } => {
+                write!(f, "request rejected (HTTP {}): {}", status, body)
+            }
+            GameEventsIOError::Sink(e) => write!(f, "sink error: {}", e),
+            GameEventsIOError::Transport(e) => write!(f, "network error: {}", e),
+            GameEventsIOError::Timeout(e) => write!(f, "request timed out: {}", e),
+            GameEventsIOError::Io(e) => write!(f, "I/O error: {}", e),
+            GameEventsIOError::CircuitOpen { retry_after } => write!(
+                f,
+                "circuit breaker open, retry after {}s",
+                retry_after.as_secs()
+            ),
+            GameEventsIOError::InvalidSignature => {
+                write!(f, "remote configuration signature is invalid")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameEventsIOError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GameEventsIOError::Network(e) => Some(e),
+            GameEventsIOError::Serialization(e) => Some(e),
+            GameEventsIOError::Sink(e)
+            | GameEventsIOError::Transport(e)
+            | GameEventsIOError::Timeout(e) => Some(e.as_ref()),
+            GameEventsIOError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for GameEventsIOError {
+    fn from(e: reqwest::Error) -> Self {
+        match e.is_timeout() {
+            true => GameEventsIOError::Timeout(Box::new(e)),
+            false => GameEventsIOError::Network(e),
+        }
+    }
+}
+
+impl From<serde_json::Error> for GameEventsIOError {
+    fn from(e: serde_json::Error) -> Self {
+        GameEventsIOError::Serialization(e)
+    }
+}
+
+impl From<std::io::Error> for GameEventsIOError {
+    fn from(e: std::io::Error) -> Self {
+        GameEventsIOError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_response_classification() {
+        let mut headers = HeaderMap::new();
+        let error =
+            GameEventsIOError::from_response(StatusCode::UNAUTHORIZED, &headers, String::new());
+        assert!(matches!(error, GameEventsIOError::Auth { status: 401, .. }));
+        assert!(!error.is_retryable());
+
+        headers.insert(RETRY_AFTER, "30".parse().unwrap());
+        let error = GameEventsIOError::from_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            &headers,
+            String::new(),
+        );
+        assert!(matches!(
+            error,
+            GameEventsIOError::RateLimited {
+                retry_after: Some(d)
+            } if d == Duration::from_secs(30)
+        ));
+        assert!(error.is_retryable());
+
+        let error = GameEventsIOError::from_response(
+            StatusCode::BAD_GATEWAY,
+            &headers,
+            "upstream down".to_string(),
+        );
+        assert_eq!(error.status(), Some(502));
+        assert!(error.is_retryable());
+
+        headers.insert(
+            RETRY_AFTER,
+            "Thu, 01 Jan 1970 00:00:00 GMT".parse().unwrap(),
+        );
+        let error = GameEventsIOError::from_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            &headers,
+            String::new(),
+        );
+        assert_eq!(error.retry_after(), Some(Duration::ZERO));
+
+        // Absurd delays are cut to a day
+        for absurd in ["18446744073709551615", "Fri, 31 Dec 9999 23:59:59 GMT"] {
+            headers.insert(RETRY_AFTER, absurd.parse().unwrap());
+            let error = GameEventsIOError::from_response(
+                StatusCode::TOO_MANY_REQUESTS,
+                &headers,
+                String::new(),
+            );
+            assert_eq!(error.retry_after(), Some(MAX_RETRY_AFTER));
+        }
+
+        let error =
+            GameEventsIOError::from_response(StatusCode::BAD_REQUEST, &headers, String::new());
+        assert!(matches!(
+            error,
+            GameEventsIOError::Rejected { status: 400, .. }
+        ));
+    }
+}
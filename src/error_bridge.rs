@@ -0,0 +1,448 @@
+//! Turn ERROR-level `tracing` events and `log` records into `error` events
+//!
+//! [`GameEventsIOLayer`] (feature `tracing`) and [`GameEventsIOLogger`]
+//! (feature `log`) log through a [`GameEventsIOHandle`]. Each call site is
+//! rate limited on its own, so an error raised every frame does not flood the
+//! queue.
+//! Long messages and fields are shortened to fit the property size limit,
+//! and fields that still do not fit are left out.
+//!
+//! Do not combine either with [`OverflowPolicy::Block`](crate::OverflowPolicy):
+//! an error logged while the same thread is flushing would wait forever.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use web_time::Instant;
+
+use crate::crash::truncate;
+use crate::rate_limit::Bucket;
+use crate::{GameEventsIOEventBuilder, GameEventsIOHandle, RateLimit, MAX_PROPERTY_VALUE_SIZE};
+
+/// Name of the events logged by the bridges
+pub const ERROR_EVENT: &str = "error";
+
+/// Rate limit applied to each call site unless configured otherwise
+const DEFAULT_RATE_LIMIT: RateLimit = RateLimit {
+    max_per_minute: 10,
+    burst: 10,
+};
+
+/// Event source shared by both bridges
+#[derive(Debug)]
+struct Reporter {
+    handle: GameEventsIOHandle,
+    limit: RateLimit,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+/// Where an error was raised and what it said
+struct Record<'a> {
+    message: String,
+    target: &'a str,
+    file: Option<&'a str>,
+    line: Option<u32>,
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Reporter {
+    fn new(handle: GameEventsIOHandle) -> Self {
+        Reporter {
+            handle,
+            limit: DEFAULT_RATE_LIMIT,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn report(&self, record: Record<'_>) {
+        let call_site = format!(
+            "{}:{}:{}",
+            record.target,
+            record.file.unwrap_or_default(),
+            record.line.unwrap_or_default()
+        );
+        let now = Instant::now();
+        let allowed = self
+            .buckets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(call_site)
+            .or_insert_with(|| Bucket::new(&self.limit, now))
+            .try_acquire(&self.limit, now);
+        if !allowed {
+            return;
+        }
+
+        let mut props = HashMap::new();
+        props.insert("message".into(), truncate(&record.message).into());
+        props.insert("target".into(), truncate(record.target).into());
+        if let Some(file) = record.file {
+            props.insert("file".into(), truncate(file).into());
+        }
+        if let Some(line) = record.line {
+            props.insert("line".into(), line.into());
+        }
+        let fields = fit_fields(record.fields);
+        if !fields.is_empty() {
+            props.insert("fields".into(), fields.into());
+        }
+        if let Ok(event) = GameEventsIOEventBuilder::default()
+            .event(ERROR_EVENT)
+            .event_properties(props)
+            .build()
+        {
+            self.handle.log_event(event);
+        }
+    }
+}
+
+/// Shorten long string fields and leave out the fields that would take the
+/// map over the property value limit
+fn fit_fields(
+    fields: serde_json::Map<String, serde_json::Value>,
+) -> serde_json::Map<String, serde_json::Value> {
+    let size = |value: &serde_json::Value| serde_json::to_vec(value).map_or(0, |v| v.len());
+    let mut fitted = serde_json::Map::new();
+    // The braces, then a colon and a comma per field
+    let mut total = 2;
+    for (key, value) in fields {
+        let value = match value {
+            serde_json::Value::String(text) => truncate(&text).into(),
+            value => value,
+        };
+        let entry = size(&key.as_str().into()) + size(&value) + 2;
+        if total + entry <= MAX_PROPERTY_VALUE_SIZE {
+            total += entry;
+            fitted.insert(key, value);
+        }
+    }
+    fitted
+}
+
+#[cfg(feature = "tracing")]
+pub use self::tracing::GameEventsIOLayer;
+
+#[cfg(feature = "tracing")]
+mod tracing {
+    use std::fmt;
+
+    use tracing_core::field::{Field, Visit};
+    use tracing_core::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer};
+
+    use super::{Record, Reporter};
+    use crate::{GameEventsIOHandle, RateLimit};
+
+    /// `tracing_subscriber` layer logging ERROR events as `error` events
+    ///
+    /// The event's `message` becomes the `message` property and its other
+    /// fields are collected under `fields`.
+    #[derive(Debug)]
+    pub struct GameEventsIOLayer {
+        reporter: Reporter,
+    }
+
+    impl GameEventsIOLayer {
+        /// Log through `handle`, at most 10 events per minute per call site
+        pub fn new(handle: GameEventsIOHandle) -> Self {
+            GameEventsIOLayer {
+                reporter: Reporter::new(handle),
+            }
+        }
+
+        /// Change the per call site rate limit
+        pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+            self.reporter.limit = limit;
+            self
+        }
+    }
+
+    #[derive(Default)]
+    struct FieldVisitor {
+        message: String,
+        fields: serde_json::Map<String, serde_json::Value>,
+    }
+
+    impl FieldVisitor {
+        fn insert(&mut self, field: &Field, value: serde_json::Value) {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+
+    impl Visit for FieldVisitor {
+        fn record_bool(&mut self, field: &Field, value: bool) {
+            self.insert(field, value.into());
+        }
+
+        fn record_i64(&mut self, field: &Field, value: i64) {
+            self.insert(field, value.into());
+        }
+
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            self.insert(field, value.into());
+        }
+
+        fn record_f64(&mut self, field: &Field, value: f64) {
+            self.insert(field, value.into());
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "message" {
+                self.message = value.to_string();
+            } else {
+                self.insert(field, value.into());
+            }
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "message" {
+                self.message = format!("{:?}", value);
+            } else {
+                self.insert(field, format!("{:?}", value).into());
+            }
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for GameEventsIOLayer {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            let metadata = event.metadata();
+            if *metadata.level() != Level::ERROR {
+                return;
+            }
+            let mut visitor = FieldVisitor::default();
+            event.record(&mut visitor);
+            self.reporter.report(Record {
+                message: visitor.message,
+                target: metadata.target(),
+                file: metadata.file(),
+                line: metadata.line(),
+                fields: visitor.fields,
+            });
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+pub use self::log::GameEventsIOLogger;
+
+#[cfg(feature = "log")]
+mod log {
+    use log::{Level, Log, Metadata};
+
+    use super::{Record, Reporter};
+    use crate::{GameEventsIOHandle, RateLimit};
+
+    /// `log` logger sending Error records as `error` events
+    ///
+    /// Wrap the logger you already use with [`wrap`](Self::wrap) so records
+    /// still reach it, then install the result with `log::set_boxed_logger`.
+    pub struct GameEventsIOLogger {
+        reporter: Reporter,
+        inner: Option<Box<dyn Log>>,
+    }
+
+    impl GameEventsIOLogger {
+        /// Log through `handle`, at most 10 events per minute per call site
+        pub fn new(handle: GameEventsIOHandle) -> Self {
+            GameEventsIOLogger {
+                reporter: Reporter::new(handle),
+                inner: None,
+            }
+        }
+
+        /// Forward every record to `inner` as well
+        pub fn wrap(mut self, inner: impl Log + 'static) -> Self {
+            self.inner = Some(Box::new(inner));
+            self
+        }
+
+        /// Change the per call site rate limit
+        pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+            self.reporter.limit = limit;
+            self
+        }
+    }
+
+    impl Log for GameEventsIOLogger {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.level() == Level::Error
+                || self
+                    .inner
+                    .as_ref()
+                    .is_some_and(|inner| inner.enabled(metadata))
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            if let Some(inner) = &self.inner {
+                inner.log(record);
+            }
+            if record.level() != Level::Error {
+                return;
+            }
+            let mut fields = serde_json::Map::new();
+            if let Some(module) = record.module_path() {
+                fields.insert("module_path".into(), module.into());
+            }
+            self.reporter.report(Record {
+                message: record.args().to_string(),
+                target: record.target(),
+                file: record.file(),
+                line: record.line(),
+                fields,
+            });
+        }
+
+        fn flush(&self) {
+            if let Some(inner) = &self.inner {
+                inner.flush();
+            }
+        }
+    }
+
+    impl std::fmt::Debug for GameEventsIOLogger {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("GameEventsIOLogger")
+                .field("reporter", &self.reporter)
+                .finish_non_exhaustive()
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "tracing", feature = "log")))]
+mod tests {
+    #[cfg(feature = "tracing")]
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+    use crate::GameEventsIOClientBuilder;
+
+    #[test]
+    fn test_long_errors_are_truncated() {
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .build()
+            .unwrap();
+        let handle = GameEventsIOHandle::new(client);
+        let reporter = Reporter::new(handle.clone());
+        let long = "x".repeat(MAX_PROPERTY_VALUE_SIZE * 2);
+        let mut fields = serde_json::Map::new();
+        fields.insert("response".into(), long.clone().into());
+        fields.insert("request".into(), long.clone().into());
+        fields.insert("status".into(), 500.into());
+
+        reporter.report(Record {
+            message: long,
+            target: "game::net",
+            file: None,
+            line: None,
+            fields,
+        });
+
+        let client = handle.client();
+        let event = client.events.front().unwrap();
+        let message = event.event_properties["message"].as_str().unwrap();
+        assert!(message.len() < MAX_PROPERTY_VALUE_SIZE);
+        let fields = event.event_properties["fields"].as_object().unwrap();
+        assert_eq!(fields["status"], 500);
+        assert!(serde_json::to_vec(fields).unwrap().len() <= MAX_PROPERTY_VALUE_SIZE);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_layer_logs_rate_limited_errors() {
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .build()
+            .unwrap();
+        let handle = GameEventsIOHandle::new(client);
+        let layer = GameEventsIOLayer::new(handle.clone()).rate_limit(RateLimit::per_minute(2));
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        ::tracing::subscriber::with_default(subscriber, || {
+            ::tracing::warn!("not an error");
+            for attempt in 0..5 {
+                ::tracing::error!(attempt, "save failed");
+            }
+        });
+
+        let client = handle.client();
+        assert_eq!(client.pending_events_count(), 2);
+        let event = client.events.front().unwrap();
+        assert_eq!(event.event, ERROR_EVENT);
+        assert_eq!(event.event_properties["message"], "save failed");
+        assert_eq!(event.event_properties["fields"]["attempt"], 0);
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_logger_logs_rate_limited_errors() {
+        use std::sync::{Arc, Mutex};
+
+        use ::log::{Level, Log};
+
+        /// Keeps the messages of the records it is given
+        #[derive(Clone, Default)]
+        struct Inner(Arc<Mutex<Vec<String>>>);
+        impl Log for Inner {
+            fn enabled(&self, metadata: &::log::Metadata<'_>) -> bool {
+                metadata.level() <= Level::Info
+            }
+            fn log(&self, record: &::log::Record<'_>) {
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+            fn flush(&self) {}
+        }
+
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .build()
+            .unwrap();
+        let handle = GameEventsIOHandle::new(client);
+        let inner = Inner::default();
+        let logger = GameEventsIOLogger::new(handle.clone())
+            .wrap(inner.clone())
+            .rate_limit(RateLimit::per_minute(2));
+        let log = |level: Level, message: &str, line: u32| {
+            logger.log(
+                &::log::Record::builder()
+                    .level(level)
+                    .target("game::save")
+                    .module_path(Some("game::save"))
+                    .file(Some("src/save.rs"))
+                    .line(Some(line))
+                    .args(format_args!("{}", message))
+                    .build(),
+            );
+        };
+
+        let metadata = |level: Level| ::log::Metadata::builder().level(level).build();
+        assert!(logger.enabled(&metadata(Level::Error)));
+        assert!(logger.enabled(&metadata(Level::Info)));
+        assert!(!logger.enabled(&metadata(Level::Debug)));
+
+        log(Level::Warn, "not an error", 10);
+        for _ in 0..5 {
+            log(Level::Error, "save failed", 20);
+        }
+        // Another call site has its own limit
+        log(Level::Error, "load failed", 30);
+
+        // Every record reaches the wrapped logger, rate limited or not
+        assert_eq!(inner.0.lock().unwrap().len(), 7);
+        let client = handle.client();
+        let messages: Vec<_> = client
+            .events
+            .iter()
+            .map(|event| event.event_properties["message"].clone())
+            .collect();
+        assert_eq!(messages, ["save failed", "save failed", "load failed"]);
+        let event = client.events.front().unwrap();
+        assert_eq!(event.event, ERROR_EVENT);
+        assert_eq!(event.event_properties["file"], "src/save.rs");
+        assert_eq!(event.event_properties["line"], 20);
+        assert_eq!(
+            event.event_properties["fields"]["module_path"],
+            "game::save"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the super property an assigned variant is attached as, e.g.
+/// `experiment_new_shop` for the experiment "new_shop"
+pub const EXPERIMENT_PROPERTY_PREFIX: &str = "experiment_";
+
+/// Body of an experiment definition request
+#[derive(Serialize)]
+pub(crate) struct ExperimentRequest<'a> {
+    pub experiment_key: &'a str,
+}
+
+/// An experiment as defined on the backend; no variants means it is not
+/// running
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Experiment {
+    #[serde(default)]
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Variant {
+    pub name: String,
+
+    /// Share of users assigned to this variant, relative to the others
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+impl Experiment {
+    /// The variant `user_id` is in
+    ///
+    /// Users are bucketed by a hash of the experiment key and user id, so a
+    /// user gets the same variant on every device and every run, and
+    /// different experiments split users independently.
+    pub(crate) fn assign(&self, key: &str, user_id: &str) -> Option<&str> {
+        let total: u64 = self.variants.iter().map(|v| u64::from(v.weight)).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut bucket = fnv1a(format!("{}:{}", key, user_id).as_bytes()) % total;
+        for variant in &self.variants {
+            let weight = u64::from(variant.weight);
+            if bucket < weight {
+                return Some(&variant.name);
+            }
+            bucket -= weight;
+        }
+        None
+    }
+}
+
+/// 64-bit FNV-1a, stable across platforms and releases unlike std's hasher
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Derive the experiments endpoint from the events endpoint
+///
+/// "https://host/v1/events" -> "https://host/v1/experiments"
+pub(crate) fn default_url(backend_url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(backend_url).ok()?;
+    url.join("experiments").ok().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assignment_is_deterministic() {
+        let experiment: Experiment = serde_json::from_str(
+            r#"{"variants": [{"name": "control", "weight": 3}, {"name": "new_shop"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+
+        let assigned: Vec<_> = (0..1000)
+            .map(|i| experiment.assign("shop", &format!("user-{}", i)).unwrap())
+            .collect();
+        let new_shop = assigned.iter().filter(|v| **v == "new_shop").count();
+        assert!((150..350).contains(&new_shop), "{}", new_shop);
+        assert_eq!(experiment.assign("shop", "user-7"), Some(assigned[7]));
+
+        assert_eq!(Experiment::default().assign("shop", "user-1"), None);
+        assert_eq!(
+            default_url("https://api.game-events.io/v1/events").as_deref(),
+            Some("https://api.game-events.io/v1/experiments")
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use serde_json::{json, Map, Value};
+
+use crate::GameEventsIOEvent;
+
+/// Shape events are written in by sinks that support several formats,
+/// such as [`FileSink`](crate::FileSink)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// The SDK's own event model, as sent to game-events.io
+    #[default]
+    Native,
+
+    /// Amplitude HTTP V2 API events: `event_type`, `device_id`, `insert_id`
+    /// and `time` in milliseconds
+    Amplitude,
+
+    /// Mixpanel `/import` events: everything but the name lives in
+    /// `properties`, with `distinct_id`, `$insert_id` and `time` in
+    /// milliseconds.
+    /// Mixpanel keeps user properties separately, so they are left out.
+    Mixpanel,
+}
+
+impl ExportFormat {
+    /// Convert an event to this format
+    pub fn to_value(self, event: &GameEventsIOEvent) -> Result<Value, serde_json::Error> {
+        let time_ms = event.time.saturating_mul(1000);
+        let mut properties: Map<String, Value> = event
+            .event_properties
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect();
+        if !event.session_id.is_empty() {
+            properties
+                .entry("session_id")
+                .or_insert_with(|| event.session_id.clone().into());
+        }
+
+        Ok(match self {
+            ExportFormat::Native => serde_json::to_value(event)?,
+            ExportFormat::Amplitude => {
+                let mut value = json!({
+                    "event_type": event.event,
+                    "user_id": event.user_id,
+                    "time": time_ms,
+                    "event_properties": properties,
+                });
+                if let Some(id) = &event.anonymous_id {
+                    value["device_id"] = id.clone().into();
+                }
+                if !event.event_id.is_empty() {
+                    value["insert_id"] = event.event_id.clone().into();
+                }
+                if !event.user_properties.is_empty() {
+                    value["user_properties"] = serde_json::to_value(&event.user_properties)?;
+                }
+                value
+            }
+            ExportFormat::Mixpanel => {
+                properties.insert("distinct_id".into(), event.user_id.clone().into());
+                properties.insert("time".into(), time_ms.into());
+                if let Some(id) = &event.anonymous_id {
+                    properties.insert("$device_id".into(), id.clone().into());
+                }
+                if !event.event_id.is_empty() {
+                    properties.insert("$insert_id".into(), event.event_id.clone().into());
+                }
+                json!({ "event": event.event, "properties": properties })
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_amplitude_and_mixpanel() {
+        let mut props = HashMap::new();
+        props.insert("level".into(), json!(5));
+        let event = GameEventsIOEventBuilder::default()
+            .event("level_completed")
+            .user_id("user123")
+            .session_id("session456")
+            .anonymous_id("anon-1")
+            .event_id("evt-1")
+            .time(1_700_000_000u64)
+            .event_properties(props)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            ExportFormat::Amplitude.to_value(&event).unwrap(),
+            json!({
+                "event_type": "level_completed",
+                "user_id": "user123",
+                "device_id": "anon-1",
+                "insert_id": "evt-1",
+                "time": 1_700_000_000_000u64,
+                "event_properties": {"level": 5, "session_id": "session456"}
+            })
+        );
+        assert_eq!(
+            ExportFormat::Mixpanel.to_value(&event).unwrap(),
+            json!({
+                "event": "level_completed",
+                "properties": {
+                    "level": 5,
+                    "session_id": "session456",
+                    "distinct_id": "user123",
+                    "$device_id": "anon-1",
+                    "$insert_id": "evt-1",
+                    "time": 1_700_000_000_000u64
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use web_time::SystemTime;
+
+use crate::{time_format, EventSink, ExportFormat, GameEventsIOError, GameEventsIOEvent};
+
+/// Writes flushed events to newline-delimited JSON files
+///
+/// Files are named `<prefix>-<YYYY-MM-DD>.ndjson` (UTC) when rotating daily,
+/// `<prefix>.ndjson` otherwise. Once a file would grow past the size limit
+/// the sink moves on to `<name>.1.ndjson`, `<name>.2.ndjson` and so on.
+#[derive(Debug)]
+pub struct FileSink {
+    dir: PathBuf,
+    prefix: String,
+    daily: bool,
+    max_file_size: Option<u64>,
+    format: ExportFormat,
+
+    /// Date tag and index of the file currently written to
+    current: Mutex<(String, u32)>,
+}
+
+impl FileSink {
+    /// Write `events-<date>.ndjson` files into `dir`, one per day, without
+    /// a size limit
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FileSink {
+            dir: dir.into(),
+            prefix: "events".to_string(),
+            daily: true,
+            max_file_size: None,
+            format: ExportFormat::Native,
+            current: Mutex::new((String::new(), 0)),
+        }
+    }
+
+    /// Change the file name prefix (default: "events")
+    pub fn prefix(self, prefix: impl Into<String>) -> Self {
+        FileSink {
+            prefix: prefix.into(),
+            ..self
+        }
+    }
+
+    /// Start a new file every day (default: `true`)
+    pub fn rotate_daily(self, daily: bool) -> Self {
+        FileSink { daily, ..self }
+    }
+
+    /// Start a new file once the current one would grow past `bytes`
+    pub fn max_file_size(self, bytes: u64) -> Self {
+        FileSink {
+            max_file_size: Some(bytes),
+            ..self
+        }
+    }
+
+    /// Write events in another vendor's format (default: [`ExportFormat::Native`])
+    pub fn format(self, format: ExportFormat) -> Self {
+        FileSink { format, ..self }
+    }
+
+    fn path(&self, date: &str, index: u32) -> PathBuf {
+        let mut name = self.prefix.clone();
+        if !date.is_empty() {
+            name.push('-');
+            name.push_str(date);
+        }
+        if index > 0 {
+            name.push_str(&format!(".{}", index));
+        }
+        name.push_str(".ndjson");
+        self.dir.join(name)
+    }
+
+    fn write(&self, lines: &[u8]) -> std::io::Result<PathBuf> {
+        let date = if self.daily {
+            time_format::utc_date(SystemTime::now())
+        } else {
+            String::new()
+        };
+
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        if current.0 != date {
+            *current = (date, 0);
+        }
+        if let Some(max) = self.max_file_size {
+            loop {
+                let len = file_len(&self.path(&current.0, current.1));
+                if len == 0 || len + lines.len() as u64 <= max {
+                    break;
+                }
+                current.1 += 1;
+            }
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(&current.0, current.1);
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        file.write_all(lines)?;
+        Ok(path)
+    }
+}
+
+impl EventSink for FileSink {
+    fn send(
+        &self,
+        events: &[GameEventsIOEvent],
+        _timeout: Option<Duration>,
+    ) -> Result<String, GameEventsIOError> {
+        let mut lines = Vec::new();
+        for event in events {
+            serde_json::to_writer(&mut lines, &self.format.to_value(event)?)?;
+            lines.push(b'\n');
+        }
+        let path = self
+            .write(&lines)
+            .map_err(|e| GameEventsIOError::Sink(Box::new(e)))?;
+        Ok(format!(
+            "Wrote {} events to {}",
+            events.len(),
+            path.display()
+        ))
+    }
+}
+
+fn file_len(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_rotates_by_size() {
+        let dir = std::env::temp_dir().join(format!("file-sink-{}", Uuid::new_v4()));
+        let sink = FileSink::new(&dir).rotate_daily(false).max_file_size(200);
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .user_id("user123")
+            .build()
+            .unwrap();
+
+        for _ in 0..3 {
+            sink.send(&[event.clone(), event.clone()], None).unwrap();
+        }
+
+        let first = fs::read_to_string(dir.join("events.ndjson")).unwrap();
+        assert_eq!(first.lines().count(), 2);
+        assert!(dir.join("events.1.ndjson").exists());
+        assert!(dir.join("events.2.ndjson").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
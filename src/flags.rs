@@ -0,0 +1,121 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::experiment::fnv1a;
+use crate::Properties;
+
+/// Event logged the first time a flag is evaluated for a user, or when its
+/// value for them changes
+///
+/// Its properties are `flag` and `enabled`.
+pub const FLAG_EXPOSURE_EVENT: &str = "flag_exposure";
+
+/// A feature flag as set in the remote configuration
+///
+/// The flag is on for the listed `users`, and otherwise for the `rollout`
+/// share of users whose context matches every entry of `properties`.
+/// `enabled: false` turns it off for everyone.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlagRule {
+    pub enabled: bool,
+
+    /// Fraction of users the flag is on for, from 0.0 to 1.0 (default: all)
+    pub rollout: Option<f64>,
+
+    /// Users the flag is always on for
+    pub users: HashSet<String>,
+
+    /// Allowed values per context property
+    pub properties: HashMap<String, Vec<serde_json::Value>>,
+}
+
+impl FlagRule {
+    /// Whether the flag named `flag` is on in `context`
+    pub(crate) fn evaluate(&self, flag: &str, user_id: &str, context: &FlagContext) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if self.users.contains(user_id) {
+            return true;
+        }
+        let matches = self.properties.iter().all(|(key, allowed)| {
+            context
+                .properties
+                .get(key.as_str())
+                .is_some_and(|value| allowed.contains(value))
+        });
+        // Users are bucketed like experiment variants, so a rollout only grows
+        let in_rollout = self.rollout.is_none_or(|rollout| {
+            let bucket = fnv1a(format!("{}:{}", flag, user_id).as_bytes()) % 10_000;
+            (bucket as f64) < rollout * 10_000.0
+        });
+        matches && in_rollout
+    }
+}
+
+/// Who a flag is evaluated for
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FlagContext {
+    /// Defaults to the identified user, or the anonymous id
+    pub user_id: Option<String>,
+
+    /// Attributes flag rules can target, e.g. "platform"
+    pub properties: Properties,
+}
+
+impl FlagContext {
+    pub fn new() -> Self {
+        FlagContext::default()
+    }
+
+    /// Evaluate flags for `user_id`
+    pub fn user(user_id: impl Into<String>) -> Self {
+        FlagContext {
+            user_id: Some(user_id.into()),
+            ..FlagContext::default()
+        }
+    }
+
+    pub fn property(mut self, key: impl Into<Cow<'static, str>>, value: impl Into<serde_json::Value>) -> Self {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        let rule: FlagRule = serde_json::from_str(
+            r#"{"enabled": true, "rollout": 0.2, "users": ["tester"],
+                "properties": {"platform": ["ios", "android"]}}"#,
+        )
+        .unwrap();
+        let mobile = |user: &str| FlagContext::user(user).property("platform", "ios");
+
+        assert!(rule.evaluate("new_shop", "tester", &FlagContext::new()));
+        let on = (0..1000)
+            .filter(|i| {
+                let user = format!("user-{}", i);
+                rule.evaluate("new_shop", &user, &mobile(&user))
+            })
+            .count();
+        assert!((100..300).contains(&on), "{}", on);
+        assert!(!(0..100).any(|i| {
+            let user = format!("user-{}", i);
+            let desktop = FlagContext::user(&user).property("platform", "windows");
+            rule.evaluate("new_shop", &user, &desktop)
+        }));
+
+        let off = FlagRule {
+            users: HashSet::from(["tester".to_string()]),
+            ..FlagRule::default()
+        };
+        assert!(!off.evaluate("new_shop", "tester", &FlagContext::new()));
+    }
+}
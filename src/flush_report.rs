@@ -0,0 +1,47 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::RejectedEvent;
+
+/// What a flush sent and how the backend answered
+///
+/// A flush that had nothing to send returns an empty report whose
+/// `responses` say why, e.g. "No events to send".
+#[derive(Clone, Debug, Default)]
+pub struct FlushReport {
+    /// Number of events the backend (or sink) accepted
+    pub accepted: usize,
+
+    /// Number of events the backend rejected; see `rejections`
+    pub rejected: usize,
+
+    /// Each rejected event and why it was rejected
+    pub rejections: Vec<RejectedEvent>,
+
+    /// `X-Request-Id` of every request that had one, for support tickets
+    pub request_ids: Vec<String>,
+
+    /// Time spent waiting for responses, summed over requests and retries
+    /// but not over backoff
+    pub latency: Duration,
+
+    /// Response body of every request, in order
+    pub responses: Vec<String>,
+}
+
+impl FlushReport {
+    /// A report for a flush that sent nothing, for `reason`
+    pub(crate) fn skipped(reason: impl Into<String>) -> Self {
+        FlushReport {
+            responses: vec![reason.into()],
+            ..FlushReport::default()
+        }
+    }
+}
+
+/// The responses, one per line
+impl fmt::Display for FlushReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.responses.join("\n"))
+    }
+}
@@ -0,0 +1,131 @@
+//! Godot 4 bindings through gdext (feature `godot`)
+//!
+//! Registers a `GameEventsIO` class for GDScript. Events are queued on a
+//! session and handed to a [`GameEventsIOWorker`], which batches and flushes
+//! them from a background thread so the game loop never waits on the
+//! network.
+//!
+//! ```gdscript
+//! var analytics = GameEventsIO.create("YOUR_API_KEY", "player-42")
+//! analytics.log_event("level_up", {"level": 3})
+//! ```
+
+use godot::builtin::{GString, VarArray, VarDictionary, Variant, VariantType};
+use godot::classes::RefCounted;
+use godot::prelude::{godot_api, Base, Gd, GodotClass};
+
+use crate::{
+    FlushPolicy, GameEventsIOClient, GameEventsIOClientBuilder, GameEventsIOSession,
+    GameEventsIOWorker, Properties,
+};
+
+/// GDScript-facing wrapper around a client and its current session
+#[derive(GodotClass)]
+#[class(no_init, base = RefCounted, rename = GameEventsIO)]
+pub struct GameEventsIOGodotClient {
+    worker: Option<GameEventsIOWorker>,
+    session: GameEventsIOSession,
+    base: Base<RefCounted>,
+}
+
+#[godot_api]
+impl GameEventsIOGodotClient {
+    /// Start a session for `user_id`, or `null` if the client cannot be built
+    #[func]
+    fn create(api_key: GString, user_id: GString) -> Option<Gd<Self>> {
+        let client = GameEventsIOClientBuilder::default()
+            .api_key(api_key.to_string())
+            .build()
+            .ok()?;
+        Some(Self::wrap(client, user_id.to_string()))
+    }
+
+    /// Queue an event; `properties` keys are converted to strings
+    #[func]
+    fn log_event(&mut self, name: GString, properties: VarDictionary) {
+        self.session
+            .push_event(name.to_string(), to_properties(&properties));
+        self.forward();
+    }
+
+    /// Set a user property sent with every following event
+    #[func]
+    fn set_user_property(&mut self, key: GString, value: Variant) {
+        self.session
+            .set_user_property(key.to_string(), to_json(&value));
+    }
+
+    /// Ask the background worker to flush now
+    #[func]
+    fn flush(&mut self) {
+        if let Some(worker) = &self.worker {
+            worker.flush();
+        }
+    }
+
+    /// Log `session_end` and flush
+    #[func]
+    fn end_session(&mut self) {
+        self.session.end();
+        self.forward();
+        self.flush();
+    }
+
+    /// Current session id
+    #[func]
+    fn session_id(&self) -> GString {
+        self.session.session_id().into()
+    }
+}
+
+impl GameEventsIOGodotClient {
+    /// Wrap an already configured client with the default [`FlushPolicy`]
+    pub fn wrap(client: GameEventsIOClient, user_id: impl Into<String>) -> Gd<Self> {
+        let worker = client.spawn_worker(FlushPolicy::default());
+        Gd::from_init_fn(|base| {
+            let mut this = GameEventsIOGodotClient {
+                worker: Some(worker),
+                session: GameEventsIOSession::start(user_id),
+                base,
+            };
+            this.forward();
+            this
+        })
+    }
+
+    fn forward(&mut self) {
+        let Some(worker) = &self.worker else {
+            return;
+        };
+        for event in self.session.take_events(usize::MAX) {
+            worker.log_event(event);
+        }
+    }
+}
+
+fn to_properties(dict: &VarDictionary) -> Properties {
+    dict.iter_shared()
+        .map(|(key, value)| (key.stringify().to_string().into(), to_json(&value)))
+        .collect()
+}
+
+fn to_json(value: &Variant) -> serde_json::Value {
+    match value.get_type() {
+        VariantType::NIL => serde_json::Value::Null,
+        VariantType::BOOL => value.to::<bool>().into(),
+        VariantType::INT => value.to::<i64>().into(),
+        VariantType::FLOAT => value.to::<f64>().into(),
+        VariantType::ARRAY => value
+            .to::<VarArray>()
+            .iter_shared()
+            .map(|item| to_json(&item))
+            .collect(),
+        VariantType::DICTIONARY => serde_json::Value::Object(
+            to_properties(&value.to::<VarDictionary>())
+                .into_iter()
+                .map(|(key, value)| (key.into_owned(), value))
+                .collect(),
+        ),
+        _ => value.stringify().to_string().into(),
+    }
+}
@@ -0,0 +1,352 @@
+//! gRPC transport for collectors serving the ingest service in
+//! `proto/ingest.proto`
+//!
+//! The messages below are kept in sync with that file by hand, so building
+//! needs no `protoc`.
+
+use std::sync::{Mutex, PoisonError};
+
+use reqwest::header::CONTENT_ENCODING;
+#[cfg(feature = "msgpack")]
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::StatusCode;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::MetadataMap;
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tonic::{Code, Status};
+
+use crate::transport::{AsyncHttpTransport, HttpRequest, HttpResponse, TransportFuture};
+use crate::{GameEventsIOError, GameEventsIOEvent};
+
+const INGEST_PATH: &str = "/gameevents.ingest.v1.IngestService/Ingest";
+
+/// Async transport that sends each flushed batch as a unary gRPC call, for
+/// use inside a Tokio runtime
+///
+/// The batch is read back from the client's request body and sent as an
+/// `EventBatch` to the origin of `backend_url`; its path is ignored. Request
+/// headers, such as `Authorization`, become call metadata, and the request's
+/// timeout becomes the call's deadline. gRPC status codes are turned into the
+/// HTTP statuses the client already retries on, e.g. `UNAVAILABLE` into 503,
+/// with a `retry-after` trailer kept as the `Retry-After` header.
+///
+/// Compressed bodies cannot be sent, and request signatures cover the HTTP
+/// body, which a gRPC collector never sees.
+#[derive(Debug, Default)]
+pub struct GrpcTransport {
+    /// Lazily connected channel, and the origin it connects to
+    channel: Mutex<Option<(String, Channel)>>,
+}
+
+impl GrpcTransport {
+    pub fn new() -> Self {
+        GrpcTransport::default()
+    }
+
+    /// The channel to `url`'s origin, reusing the last one if it matches
+    fn channel(&self, url: &str) -> Result<Channel, GameEventsIOError> {
+        let url = reqwest::Url::parse(url).map_err(transport_error)?;
+        let origin = url.origin().ascii_serialization();
+        let mut cached = self.channel.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((cached_origin, channel)) = &*cached {
+            if *cached_origin == origin {
+                return Ok(channel.clone());
+            }
+        }
+
+        let mut endpoint = Endpoint::from_shared(origin.clone()).map_err(transport_error)?;
+        if url.scheme() == "https" {
+            endpoint = endpoint
+                .tls_config(ClientTlsConfig::new())
+                .map_err(transport_error)?;
+        }
+        let channel = endpoint.connect_lazy();
+        *cached = Some((origin, channel.clone()));
+        Ok(channel)
+    }
+}
+
+impl AsyncHttpTransport for GrpcTransport {
+    fn name(&self) -> &str {
+        "grpc"
+    }
+
+    fn post(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let batch = EventBatch::from_request(&request)?;
+            let mut grpc = tonic::client::Grpc::new(self.channel(&request.url)?);
+            let timeout = request.timeout.unwrap_or(crate::REQUEST_TIMEOUT);
+            let mut call = tonic::Request::new(batch);
+            call.set_timeout(timeout);
+            let mut headers = request.headers;
+            headers.remove(CONTENT_ENCODING);
+            *call.metadata_mut() = MetadataMap::from_headers(headers);
+
+            let exchange = async {
+                grpc.ready().await.map_err(transport_error)?;
+                let codec = ProstCodec::<EventBatch, IngestResponse>::default();
+                let path = PathAndQuery::from_static(INGEST_PATH);
+                into_http_response(grpc.unary(call, path, codec).await)
+            };
+            tokio::time::timeout(timeout, exchange)
+                .await
+                .map_err(|e| GameEventsIOError::Timeout(Box::new(e)))?
+        })
+    }
+}
+
+fn transport_error(e: impl std::error::Error + Send + Sync + 'static) -> GameEventsIOError {
+    GameEventsIOError::Transport(Box::new(e))
+}
+
+/// The gRPC outcome as the response of the HTTP API: rejections as its JSON
+/// body, and failures as the closest HTTP status
+fn into_http_response(
+    result: Result<tonic::Response<IngestResponse>, Status>,
+) -> Result<HttpResponse, GameEventsIOError> {
+    let status = match result {
+        Ok(response) => {
+            let (metadata, response, _) = response.into_parts();
+            let body = if response.rejected.is_empty() {
+                String::new()
+            } else {
+                serde_json::json!({ "rejected": response.rejected }).to_string()
+            };
+            return Ok(HttpResponse {
+                status: StatusCode::OK,
+                headers: metadata.into_headers(),
+                body,
+            });
+        }
+        Err(status) => status,
+    };
+    let http_status = match status.code() {
+        // The deadline set from the request's timeout
+        Code::DeadlineExceeded | Code::Cancelled => {
+            return Err(GameEventsIOError::Timeout(Box::new(status)))
+        }
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+            StatusCode::BAD_REQUEST
+        }
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::Ok | Code::Unknown | Code::Internal | Code::DataLoss => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    Ok(HttpResponse {
+        status: http_status,
+        headers: status.metadata().clone().into_headers(),
+        body: status.message().to_string(),
+    })
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct EventBatch {
+    #[prost(message, repeated, tag = "1")]
+    events: Vec<Event>,
+}
+
+impl EventBatch {
+    /// Read the batch back from the body the client serialized it into
+    fn from_request(request: &HttpRequest) -> Result<Self, GameEventsIOError> {
+        if let Some(encoding) = request.headers.get(CONTENT_ENCODING) {
+            return Err(GameEventsIOError::Transport(
+                format!("gRPC transport cannot send {:?} bodies", encoding).into(),
+            ));
+        }
+        #[cfg(feature = "msgpack")]
+        let events: Vec<GameEventsIOEvent> = if is_message_pack(&request.headers) {
+            rmp_serde::from_slice(&request.body).map_err(transport_error)?
+        } else {
+            serde_json::from_slice(&request.body)?
+        };
+        #[cfg(not(feature = "msgpack"))]
+        let events: Vec<GameEventsIOEvent> = serde_json::from_slice(&request.body)?;
+        let events = events
+            .into_iter()
+            .map(Event::try_from)
+            .collect::<Result<_, _>>()?;
+        Ok(EventBatch { events })
+    }
+}
+
+#[cfg(feature = "msgpack")]
+fn is_message_pack(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .is_some_and(|content_type| content_type == "application/msgpack")
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Event {
+    #[prost(string, tag = "1")]
+    event_id: String,
+    #[prost(string, tag = "2")]
+    event: String,
+    #[prost(string, tag = "3")]
+    user_id: String,
+    #[prost(string, tag = "4")]
+    session_id: String,
+    #[prost(uint64, tag = "5")]
+    time: u64,
+    #[prost(sint32, optional, tag = "6")]
+    utc_offset_minutes: Option<i32>,
+    #[prost(string, optional, tag = "7")]
+    time_zone: Option<String>,
+    #[prost(string, tag = "8")]
+    event_properties: String,
+    #[prost(string, tag = "9")]
+    user_properties: String,
+    #[prost(string, optional, tag = "10")]
+    anonymous_id: Option<String>,
+    #[prost(string, tag = "11")]
+    user_property_ops: String,
+    #[prost(double, optional, tag = "12")]
+    sample_rate: Option<f64>,
+    #[prost(uint64, optional, tag = "13")]
+    sequence: Option<u64>,
+    #[prost(uint64, optional, tag = "14")]
+    session_epoch: Option<u64>,
+    #[prost(uint64, optional, tag = "15")]
+    corrected_time: Option<u64>,
+    #[prost(string, optional, tag = "16")]
+    environment: Option<String>,
+    #[prost(string, optional, tag = "17")]
+    project: Option<String>,
+}
+
+impl TryFrom<GameEventsIOEvent> for Event {
+    type Error = serde_json::Error;
+
+    fn try_from(event: GameEventsIOEvent) -> Result<Self, Self::Error> {
+        Ok(Event {
+            event_properties: serde_json::to_string(&event.event_properties)?,
+            user_properties: serde_json::to_string(&event.user_properties)?,
+            user_property_ops: serde_json::to_string(&event.user_property_ops)?,
+            event_id: event.event_id,
+            event: event.event.into_owned(),
+            user_id: event.user_id,
+            session_id: event.session_id,
+            time: event.time,
+            utc_offset_minutes: event.utc_offset_minutes,
+            time_zone: event.time_zone,
+            anonymous_id: event.anonymous_id,
+            sample_rate: event.sample_rate,
+            sequence: event.sequence,
+            session_epoch: event.session_epoch,
+            corrected_time: event.corrected_time,
+            environment: event.environment,
+            project: event.project,
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct IngestResponse {
+    #[prost(message, repeated, tag = "1")]
+    rejected: Vec<Rejection>,
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, prost::Message)]
+struct Rejection {
+    #[prost(uint32, tag = "1")]
+    index: u32,
+    #[prost(string, tag = "2")]
+    code: String,
+    #[prost(string, tag = "3")]
+    message: String,
+    #[prost(bool, tag = "4")]
+    retryable: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+    use prost::Message;
+    use reqwest::header::HeaderMap;
+    use tonic::metadata::MetadataValue;
+
+    #[test]
+    fn test_batch_from_request() {
+        let event = GameEventsIOEventBuilder::default()
+            .event("level_up")
+            .user_id("user123")
+            .property("level", 3)
+            .build()
+            .unwrap();
+        let request = HttpRequest {
+            url: "https://collector.internal/v1/events".to_string(),
+            headers: HeaderMap::new(),
+            body: crate::to_payload(std::slice::from_ref(&event)).unwrap(),
+            timeout: None,
+        };
+
+        let batch = EventBatch::from_request(&request).unwrap();
+        let decoded = EventBatch::decode(batch.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded.events[0].event_id, event.event_id);
+        assert_eq!(decoded.events[0].event, "level_up");
+        assert_eq!(decoded.events[0].event_properties, r#"{"level":3}"#);
+
+        let mut gzipped = request;
+        gzipped
+            .headers
+            .insert(CONTENT_ENCODING, "gzip".parse().unwrap());
+        assert!(EventBatch::from_request(&gzipped).is_err());
+    }
+
+    #[test]
+    fn test_status_mapping() {
+        let response = tonic::Response::new(IngestResponse {
+            rejected: vec![Rejection {
+                index: 0,
+                code: "invalid_property".to_string(),
+                message: String::new(),
+                retryable: false,
+            }],
+        });
+        let response = into_http_response(Ok(response)).unwrap();
+        assert_eq!(response.status, StatusCode::OK);
+        assert!(response.body.contains(r#""code":"invalid_property""#));
+
+        let mut unavailable = Status::unavailable("draining");
+        unavailable
+            .metadata_mut()
+            .insert("retry-after", MetadataValue::from_static("5"));
+        let error = into_http_response(Err(unavailable))
+            .unwrap()
+            .into_result()
+            .unwrap_err();
+        assert!(error.is_retryable());
+        assert_eq!(error.retry_after(), Some(std::time::Duration::from_secs(5)));
+
+        let error = into_http_response(Err(Status::unauthenticated("bad key")))
+            .unwrap()
+            .into_result()
+            .unwrap_err();
+        assert_eq!(error.status(), Some(401));
+
+        let error = into_http_response(Err(Status::deadline_exceeded("late"))).unwrap_err();
+        assert!(matches!(error, GameEventsIOError::Timeout(_)));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_collector_is_retried() {
+        let request = HttpRequest {
+            url: "http://127.0.0.1:9/v1/events".to_string(),
+            headers: HeaderMap::new(),
+            body: b"[]".to_vec(),
+            timeout: None,
+        };
+        let response = GrpcTransport::new().post(request).await.unwrap();
+        assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(response.into_result().unwrap_err().is_retryable());
+    }
+}
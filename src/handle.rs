@@ -0,0 +1,296 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+use crate::{
+    ClientMetrics, FlushReport, GameEventsIOClient, GameEventsIOError, GameEventsIOEvent,
+    OverflowPolicy,
+};
+
+struct Shared {
+    client: Mutex<GameEventsIOClient>,
+
+    /// Copied from the client so loggers can check it without locking
+    overflow_policy: OverflowPolicy,
+
+    /// Copied from the client so parked events count against it
+    max_pending_events: Option<usize>,
+
+    /// The client's metrics, for its queue depth and drop count without
+    /// locking it
+    metrics: Arc<ClientMetrics>,
+
+    /// Signalled after every flush so blocked loggers can re-check for space
+    flushed: Condvar,
+
+    /// Events logged while another thread held the client (usually while flushing)
+    inbox: Mutex<VecDeque<GameEventsIOEvent>>,
+}
+
+impl Drop for Shared {
+    /// Queue parked events before the client is dropped, so its
+    /// flush-on-drop sends them too
+    fn drop(&mut self) {
+        let client = self
+            .client
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        let inbox = self.inbox.get_mut().unwrap_or_else(PoisonError::into_inner);
+        for event in inbox.drain(..) {
+            client.log_event(event);
+        }
+    }
+}
+
+/// Cheap-to-clone, thread-safe handle to a client
+///
+/// Every clone logs into the same queue. Logging never waits for a flush
+/// in progress on another thread; such events are parked and moved into the
+/// client's queue the next time it is used, or when the last handle is
+/// dropped. Parked events count against `max_pending_events` and are dropped
+/// by the overflow policy like queued ones.
+///
+/// The exception is [`OverflowPolicy::Block`]: logging then always goes
+/// through the client and, once it is full, waits until another thread
+/// flushes through the handle.
+#[derive(Clone)]
+pub struct GameEventsIOHandle {
+    shared: Arc<Shared>,
+}
+
+impl GameEventsIOHandle {
+    /// Wrap a client so it can be shared between threads
+    pub fn new(client: GameEventsIOClient) -> Self {
+        GameEventsIOHandle {
+            shared: Arc::new(Shared {
+                overflow_policy: client.overflow_policy(),
+                max_pending_events: client.max_pending_events,
+                metrics: client.metrics(),
+                client: Mutex::new(client),
+                flushed: Condvar::new(),
+                inbox: Mutex::new(VecDeque::new()),
+            }),
+        }
+    }
+
+    /// Log an event from any thread
+    pub fn log_event(&self, event: GameEventsIOEvent) {
+        if self.shared.overflow_policy != OverflowPolicy::Block {
+            match self.shared.client.try_lock() {
+                Ok(mut client) => {
+                    self.drain_inbox(&mut client);
+                    client.log_event(event);
+                }
+                Err(_) => self.park(event),
+            }
+            return;
+        }
+
+        let mut client = self.client();
+        while client.is_full() {
+            client = self
+                .shared
+                .flushed
+                .wait(client)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        client.log_event(event);
+    }
+
+    /// Send all buffered events to the backend
+    pub fn flush(&self) -> Result<FlushReport, GameEventsIOError> {
+        let result = self.client().flush();
+        self.shared.flushed.notify_all();
+        result
+    }
+
+    /// Send up to `batch_size` buffered events to the backend
+    pub fn flush_batch(&self, batch_size: usize) -> Result<FlushReport, GameEventsIOError> {
+        let result = self.client().flush_batch(batch_size);
+        self.shared.flushed.notify_all();
+        result
+    }
+
+    /// Get the number of buffered events
+    pub fn pending_events_count(&self) -> usize {
+        self.client().pending_events_count()
+    }
+
+    /// Lock the underlying client for direct access
+    pub fn client(&self) -> MutexGuard<'_, GameEventsIOClient> {
+        let mut client = self
+            .shared
+            .client
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.drain_inbox(&mut client);
+        client
+    }
+
+    /// Hold an event for the client while another thread has it
+    ///
+    /// With [`OverflowPolicy::DropOldest`] only the newest
+    /// `max_pending_events` parked events can survive being queued, so older
+    /// ones are dropped here; with [`OverflowPolicy::DropNewest`] the event
+    /// is dropped once the queue and the parked events fill the limit.
+    fn park(&self, event: GameEventsIOEvent) {
+        let mut inbox = self.inbox();
+        let parked = inbox.len();
+        match (self.shared.max_pending_events, self.shared.overflow_policy) {
+            (Some(max), OverflowPolicy::DropOldest) if parked >= max => {
+                inbox.pop_front();
+                self.shared.metrics.add_dropped();
+            }
+            (Some(max), OverflowPolicy::DropNewest)
+                if self.shared.metrics.queue_depth() as usize + parked >= max =>
+            {
+                self.shared.metrics.add_dropped();
+                return;
+            }
+            _ => {}
+        }
+        inbox.push_back(event);
+    }
+
+    fn inbox(&self) -> MutexGuard<'_, VecDeque<GameEventsIOEvent>> {
+        self.shared
+            .inbox
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn drain_inbox(&self, client: &mut GameEventsIOClient) {
+        for event in self.inbox().drain(..) {
+            client.log_event(event);
+        }
+    }
+}
+
+impl From<GameEventsIOClient> for GameEventsIOHandle {
+    fn from(client: GameEventsIOClient) -> Self {
+        GameEventsIOHandle::new(client)
+    }
+}
+
+impl std::fmt::Debug for GameEventsIOHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GameEventsIOHandle").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+
+    #[test]
+    fn test_concurrent_logging() {
+        let handle = GameEventsIOHandle::new(GameEventsIOClient::new("test_api_key"));
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let handle = handle.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        let event = GameEventsIOEventBuilder::default()
+                            .event("test_event")
+                            .build()
+                            .unwrap();
+                        handle.log_event(event);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(handle.pending_events_count(), 100);
+    }
+
+    #[test]
+    fn test_block_waits_for_flush() {
+        let client = crate::GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .max_pending_events(1usize)
+            .overflow_policy(OverflowPolicy::Block)
+            .build()
+            .unwrap();
+        let handle = GameEventsIOHandle::new(client);
+        let event = || {
+            GameEventsIOEventBuilder::default()
+                .event("test_event")
+                .build()
+                .unwrap()
+        };
+        handle.log_event(event());
+
+        let logger = {
+            let handle = handle.clone();
+            std::thread::spawn(move || handle.log_event(event()))
+        };
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!logger.is_finished());
+
+        // Make room the way a successful flush would
+        handle.client().take_batch(1);
+        handle.shared.flushed.notify_all();
+        logger.join().unwrap();
+        assert_eq!(handle.pending_events_count(), 1);
+    }
+
+    #[test]
+    fn test_parked_events_are_bounded_and_kept() {
+        let event = |name: &'static str| {
+            GameEventsIOEventBuilder::default()
+                .event(name)
+                .build()
+                .unwrap()
+        };
+        let handle = |policy| {
+            let client = crate::GameEventsIOClientBuilder::default()
+                .api_key("test_api_key")
+                .max_pending_events(2usize)
+                .overflow_policy(policy)
+                .build()
+                .unwrap();
+            GameEventsIOHandle::new(client)
+        };
+        let names = |handle: &GameEventsIOHandle| -> Vec<_> {
+            let client = handle.client();
+            client.events.iter().map(|e| e.event.to_string()).collect()
+        };
+
+        // Holding the client parks events the way a flush in progress does
+        for (policy, kept) in [
+            (OverflowPolicy::DropNewest, ["queued", "a"]),
+            (OverflowPolicy::DropOldest, ["b", "c"]),
+        ] {
+            let handle = handle(policy);
+            handle.log_event(event("queued"));
+            let held = handle.shared.client.lock().unwrap();
+            for name in ["a", "b", "c"] {
+                handle.log_event(event(name));
+            }
+            assert!(handle.inbox().len() <= 2);
+            drop(held);
+            assert_eq!(names(&handle), kept);
+            assert_eq!(handle.client().dropped_events_count(), 2);
+        }
+
+        // Events still parked when the last handle goes are flushed with the
+        // client's queue
+        let sink = crate::MemorySink::new();
+        let client = crate::GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(sink.clone())
+            .flush_on_drop(std::time::Duration::from_secs(1))
+            .build()
+            .unwrap();
+        let handle = GameEventsIOHandle::new(client);
+        let held = handle.shared.client.lock().unwrap();
+        handle.log_event(event("parked"));
+        drop(held);
+        drop(handle);
+        assert_eq!(sink.events().len(), 1);
+    }
+}
@@ -0,0 +1,59 @@
+use std::fmt;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+/// Adds headers to each request the client sends to the backend
+///
+/// Called for every request, retries included, after the static headers
+/// are added.
+pub(crate) trait HeaderCallback: Send + Sync {
+    fn add_headers(&self, headers: &mut HeaderMap);
+}
+
+impl<F> HeaderCallback for F
+where
+    F: Fn(&mut HeaderMap) + Send + Sync,
+{
+    fn add_headers(&self, headers: &mut HeaderMap) {
+        self(headers)
+    }
+}
+
+impl fmt::Debug for dyn HeaderCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HeaderCallback")
+    }
+}
+
+/// Parse static headers given as strings
+pub(crate) fn parse(headers: &[(String, String)]) -> Result<HeaderMap, String> {
+    let mut parsed = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| format!("invalid header name {:?}", name))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| format!("invalid value for header {:?}", name.as_str()))?;
+        parsed.append(name, value);
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let headers = parse(&[
+            ("X-Tenant-Id".to_string(), "studio-42".to_string()),
+            ("x-tag".to_string(), "a".to_string()),
+            ("x-tag".to_string(), "b".to_string()),
+        ])
+        .unwrap();
+        assert_eq!(headers["x-tenant-id"], "studio-42");
+        assert_eq!(headers.get_all("x-tag").iter().count(), 2);
+
+        assert!(parse(&[("bad name".to_string(), "x".to_string())]).is_err());
+        assert!(parse(&[("x-ok".to_string(), "line\nbreak".to_string())]).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use crate::GameEventsIOError;
+
+/// Outcome of a [`health_check`](crate::GameEventsIOClient::health_check)
+#[derive(Debug)]
+pub struct HealthStatus {
+    /// Whether the collector answered at all
+    pub reachable: bool,
+
+    /// Whether the collector accepted the API key
+    pub auth_ok: bool,
+
+    /// Time until the collector answered, or until the probe gave up
+    pub latency: Duration,
+
+    /// What went wrong, if anything did
+    pub error: Option<GameEventsIOError>,
+}
+
+impl HealthStatus {
+    pub(crate) fn new(result: Result<String, GameEventsIOError>, latency: Duration) -> Self {
+        let Err(error) = result else {
+            return HealthStatus {
+                reachable: true,
+                auth_ok: true,
+                latency,
+                error: None,
+            };
+        };
+        let reachable = error.status().is_some();
+        // Only the collector's own verdict on the key counts; a 5xx may come
+        // from before authentication
+        let auth_ok = matches!(
+            error,
+            GameEventsIOError::RateLimited { .. } | GameEventsIOError::Rejected { .. }
+        );
+        HealthStatus {
+            reachable,
+            auth_ok,
+            latency,
+            error: Some(error),
+        }
+    }
+
+    /// Whether the whole path to the collector works
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Derive the health endpoint from the events endpoint
+///
+/// "https://host/v1/events" -> "https://host/v1/health"
+pub(crate) fn default_url(backend_url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(backend_url).ok()?;
+    url.join("health").ok().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let latency = Duration::from_millis(40);
+        let status = HealthStatus::new(Ok("{}".to_string()), latency);
+        assert!(status.is_healthy() && status.reachable && status.auth_ok);
+        assert_eq!(status.latency, latency);
+
+        let auth = GameEventsIOError::Auth {
+            status: 401,
+            body: String::new(),
+        };
+        let status = HealthStatus::new(Err(auth), latency);
+        assert!(status.reachable && !status.auth_ok);
+
+        let timeout = GameEventsIOError::Timeout("connect".into());
+        let status = HealthStatus::new(Err(timeout), latency);
+        assert!(!status.reachable && !status.auth_ok);
+
+        assert_eq!(
+            default_url("https://api.game-events.io/v1/events").as_deref(),
+            Some("https://api.game-events.io/v1/health")
+        );
+    }
+}
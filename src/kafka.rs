@@ -0,0 +1,178 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rdkafka::config::ClientConfig;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::message::DeliveryResult;
+use rdkafka::producer::{BaseProducer, BaseRecord, Producer, ProducerContext, PurgeConfig};
+use rdkafka::ClientContext;
+
+use crate::{EventSink, GameEventsIOError, GameEventsIOEvent, REQUEST_TIMEOUT};
+
+/// Delivery reports for the messages of one batch
+#[derive(Default)]
+struct BatchAcks {
+    delivered: AtomicUsize,
+    failed: AtomicUsize,
+    error: Mutex<Option<KafkaError>>,
+}
+
+impl BatchAcks {
+    fn reported(&self) -> usize {
+        self.delivered.load(Ordering::SeqCst) + self.failed.load(Ordering::SeqCst)
+    }
+}
+
+struct DeliveryContext;
+
+impl ClientContext for DeliveryContext {}
+
+impl ProducerContext for DeliveryContext {
+    type DeliveryOpaque = Arc<BatchAcks>;
+
+    fn delivery(&self, result: &DeliveryResult<'_>, acks: Arc<BatchAcks>) {
+        match result {
+            Ok(_) => {
+                acks.delivered.fetch_add(1, Ordering::SeqCst);
+            }
+            Err((e, _)) => {
+                acks.failed.fetch_add(1, Ordering::SeqCst);
+                let mut error = acks.error.lock().unwrap_or_else(|e| e.into_inner());
+                error.get_or_insert_with(|| e.clone());
+            }
+        }
+    }
+}
+
+/// Produces events to a Kafka topic, keyed by `user_id`
+///
+/// Each event is one JSON message. A flush only succeeds once the brokers
+/// acknowledged every message of the batch; otherwise undelivered messages
+/// are purged and the batch is retried like a failed HTTP request.
+pub struct KafkaSink {
+    producer: BaseProducer<DeliveryContext>,
+    topic: String,
+}
+
+impl KafkaSink {
+    /// Connect to comma separated `brokers` and produce to `topic`
+    pub fn new(brokers: &str, topic: impl Into<String>) -> Result<Self, GameEventsIOError> {
+        let mut config = ClientConfig::new();
+        config.set("bootstrap.servers", brokers);
+        Self::from_config(&config, topic)
+    }
+
+    /// Produce to `topic` with a full librdkafka configuration, e.g. for SASL
+    pub fn from_config(
+        config: &ClientConfig,
+        topic: impl Into<String>,
+    ) -> Result<Self, GameEventsIOError> {
+        let producer = config
+            .create_with_context(DeliveryContext)
+            .map_err(|e| GameEventsIOError::Sink(Box::new(e)))?;
+        Ok(KafkaSink {
+            producer,
+            topic: topic.into(),
+        })
+    }
+
+    /// Drop messages of a failed batch that are still queued, so a retry does
+    /// not produce duplicates once the brokers come back
+    fn abort(&self) {
+        self.producer
+            .purge(PurgeConfig::default().queue().inflight());
+        // Purged messages are reported as failed deliveries
+        self.producer.poll(Duration::ZERO);
+    }
+}
+
+impl std::fmt::Debug for KafkaSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaSink")
+            .field("topic", &self.topic)
+            .finish()
+    }
+}
+
+impl EventSink for KafkaSink {
+    fn send(
+        &self,
+        events: &[GameEventsIOEvent],
+        timeout: Option<Duration>,
+    ) -> Result<String, GameEventsIOError> {
+        let deadline = Instant::now() + timeout.unwrap_or(REQUEST_TIMEOUT);
+        let acks = Arc::new(BatchAcks::default());
+
+        for event in events {
+            let payload = serde_json::to_vec(event)?;
+            let mut record = BaseRecord::with_opaque_to(&self.topic, acks.clone())
+                .key(&event.user_id)
+                .payload(&payload);
+            loop {
+                match self.producer.send(record) {
+                    Ok(()) => break,
+                    Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), r))
+                        if Instant::now() < deadline =>
+                    {
+                        record = r;
+                        self.producer.poll(Duration::from_millis(100));
+                    }
+                    Err((e, _)) => {
+                        self.abort();
+                        return Err(GameEventsIOError::Sink(Box::new(e)));
+                    }
+                }
+            }
+        }
+
+        while acks.reported() < events.len() && Instant::now() < deadline {
+            self.producer.poll(Duration::from_millis(100));
+        }
+        if acks.reported() < events.len() {
+            self.abort();
+            return Err(GameEventsIOError::Sink(
+                format!(
+                    "timed out waiting for Kafka to acknowledge {} of {} events",
+                    events.len() - acks.delivered.load(Ordering::SeqCst),
+                    events.len()
+                )
+                .into(),
+            ));
+        }
+
+        let error = acks.error.lock().unwrap_or_else(|e| e.into_inner()).take();
+        match error {
+            Some(e) => Err(GameEventsIOError::Sink(Box::new(e))),
+            None => Ok(format!(
+                "Delivered {} events to {}",
+                acks.delivered.load(Ordering::SeqCst),
+                self.topic
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+
+    #[test]
+    fn test_unreachable_broker_times_out() {
+        let sink = KafkaSink::new("127.0.0.1:9", "analytics").unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .user_id("user123")
+            .build()
+            .unwrap();
+
+        let started = Instant::now();
+        let error = sink
+            .send(&[event], Some(Duration::from_millis(300)))
+            .unwrap_err();
+        assert!(matches!(error, GameEventsIOError::Sink(_)));
+        assert!(error.is_retryable());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -1,51 +1,279 @@
 #![allow(non_snake_case)]
 
+#[cfg(feature = "http")]
 #[macro_use]
 extern crate derive_builder;
 
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+// Lets `#[derive(TypedEvent)]` refer to `::game_events_sdk` inside this crate
+extern crate self as game_events_sdk;
 
-/// Event structure for game-events.io
-#[derive(Serialize, Deserialize, Clone, Debug, Builder, Default)]
-#[builder(setter(into))]
-#[builder(default)]
-pub struct GameEventsIOEvent {
-    /// Event name (e.g., "level_completed", "purchase")
-    pub event: String,
+pub use game_events_sdk_core::{
+    to_payload, write_payload, EventPriority, GameEventsIOEvent, GameEventsIOEventBuilder,
+    GameEventsIOEventBuilderError, Properties, UserPropertyMutation, UserPropertyOp,
+    MAX_EVENT_NAME_LEN, MAX_PROPERTY_COUNT, MAX_PROPERTY_NAME_LEN, MAX_PROPERTY_VALUE_SIZE,
+};
 
-    /// Unique user identifier
-    pub user_id: String,
+/// Compile items only with the `http` feature, which everything beyond the
+/// event model needs
+macro_rules! cfg_http {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "http")]
+            $item
+        )*
+    };
+}
 
-    /// Session identifier
-    pub session_id: String,
+cfg_http! {
+    mod ad;
+    mod app_info;
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    mod async_worker;
+    mod batch;
+    #[cfg(feature = "bevy")]
+    mod bevy;
+    mod chunk;
+    mod circuit;
+    mod clock;
+    mod cohort;
+    mod compression;
+    #[cfg(feature = "toml")]
+    mod config;
+    mod consent;
+    #[cfg(not(target_arch = "wasm32"))]
+    mod crash;
+    mod debug_sink;
+    mod deletion;
+    mod device;
+    mod env_config;
+    mod environment;
+    mod error;
+    #[cfg(all(any(feature = "tracing", feature = "log"), not(target_arch = "wasm32")))]
+    mod error_bridge;
+    mod experiment;
+    #[cfg(feature = "persistence")]
+    mod export_format;
+    #[cfg(feature = "persistence")]
+    mod file_sink;
+    mod flags;
+    mod flush_report;
+    #[cfg(feature = "grpc")]
+    mod grpc;
+    #[cfg(all(feature = "godot", not(target_arch = "wasm32")))]
+    mod godot;
+    #[cfg(not(target_arch = "wasm32"))]
+    mod handle;
+    mod headers;
+    mod health;
+    #[cfg(feature = "kafka")]
+    mod kafka;
+    mod memory_sink;
+    mod metrics;
+    #[cfg(all(feature = "otlp", not(target_arch = "wasm32")))]
+    mod otlp;
+    mod payload_format;
+    mod performance;
+    mod plugin;
+    mod pool;
+    mod producer;
+    mod progression;
+    #[cfg(feature = "prometheus")]
+    mod prometheus_metrics;
+    mod proxy;
+    mod purchase;
+    mod rate_limit;
+    mod region;
+    mod registry;
+    mod rejection;
+    mod remote_config;
+    mod resource;
+    mod retry;
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    mod runtime;
+    #[cfg(feature = "schema")]
+    mod schema;
+    mod scrub;
+    #[cfg(not(target_arch = "wasm32"))]
+    mod segment;
+    #[cfg(feature = "signing")]
+    mod signing;
+    mod sink;
+    mod spool;
+    #[cfg(feature = "encryption")]
+    mod spool_key;
+    mod state;
+    mod storage;
+    mod time_format;
+    mod timeouts;
+    mod timezone;
+    #[cfg(not(target_arch = "wasm32"))]
+    mod tls;
+    mod transport;
+    mod typed_event;
+    mod typed_session;
+    #[cfg(target_arch = "wasm32")]
+    mod web;
+    #[cfg(feature = "winit")]
+    mod winit;
+    mod worker;
 
-    /// Unix timestamp in seconds
-    #[builder(default = "self.default_time()")]
-    pub time: u64,
+    pub use ad::{AdAction, AdEvent, AdEventBuilder, AdEventBuilderError};
+    pub use app_info::{AppInfo, AppInfoBuilder, AppInfoBuilderError};
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    pub use async_worker::GameEventsIOAsyncWorker;
+    pub use batch::{BatchPolicy, BatchPolicyBuilder, BatchPolicyBuilderError};
+    #[cfg(feature = "bevy")]
+    pub use bevy::{AnalyticsEvent, GameEventsIOBevyPlugin};
+    pub use chunk::ChunkOutcome;
+    pub use circuit::{BreakerState, CircuitBreaker, CircuitBreakerBuilder};
+    pub use clock::{Clock, FixedClock, SystemClock};
+    pub use cohort::COHORTS_PROPERTY;
+    pub use compression::Compression;
+    #[cfg(feature = "toml")]
+    pub use config::{BatchConfig, ClientConfig, PersistenceConfig};
+    pub use consent::ConsentState;
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use crash::{CrashReporter, CRASH_EVENT};
+    pub use debug_sink::{DebugSink, DEBUG_ENV_VAR};
+    pub use deletion::DeletionReceipt;
+    pub use device::DeviceMetadata;
+    pub use environment::Environment;
+    pub use error::GameEventsIOError;
+    #[cfg(all(feature = "tracing", not(target_arch = "wasm32")))]
+    pub use error_bridge::GameEventsIOLayer;
+    #[cfg(all(feature = "log", not(target_arch = "wasm32")))]
+    pub use error_bridge::GameEventsIOLogger;
+    #[cfg(all(any(feature = "tracing", feature = "log"), not(target_arch = "wasm32")))]
+    pub use error_bridge::ERROR_EVENT;
+    pub use experiment::EXPERIMENT_PROPERTY_PREFIX;
+    #[cfg(feature = "persistence")]
+    pub use export_format::ExportFormat;
+    #[cfg(feature = "persistence")]
+    pub use file_sink::FileSink;
+    pub use flags::{FlagContext, FlagRule, FLAG_EXPOSURE_EVENT};
+    pub use flush_report::FlushReport;
+    #[cfg(feature = "derive")]
+    pub use game_events_sdk_derive::TypedEvent;
+    #[cfg(all(feature = "godot", not(target_arch = "wasm32")))]
+    pub use godot::GameEventsIOGodotClient;
+    #[cfg(feature = "grpc")]
+    pub use grpc::GrpcTransport;
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use handle::GameEventsIOHandle;
+    pub use health::HealthStatus;
+    #[cfg(feature = "kafka")]
+    pub use kafka::KafkaSink;
+    pub use memory_sink::MemorySink;
+    pub use metrics::ClientMetrics;
+    #[cfg(all(feature = "otlp", not(target_arch = "wasm32")))]
+    pub use otlp::OtlpSink;
+    pub use payload_format::PayloadFormat;
+    pub use performance::{PerformanceReporter, PERFORMANCE_SAMPLE_EVENT};
+    pub use plugin::ClientPlugin;
+    pub use producer::GameEventsIOProducer;
+    pub use progression::{
+        ProgressionEvent, ProgressionEventBuilder, ProgressionEventBuilderError, ProgressionStatus,
+    };
+    #[cfg(feature = "prometheus")]
+    pub use prometheus_metrics::PrometheusCollector;
+    pub use proxy::Proxy;
+    pub use purchase::{PurchaseEvent, PurchaseEventBuilder, PurchaseEventBuilderError};
+    pub use rate_limit::RateLimit;
+    pub use region::Region;
+    pub use registry::GameEventsIORegistry;
+    pub use rejection::RejectedEvent;
+    pub use remote_config::{
+        RemoteConfigSource, RemoteConfigSourceBuilder, RemoteConfigSourceBuilderError, RemoteSettings,
+    };
+    pub use resource::{FlowType, ResourceEvent, ResourceEventBuilder, ResourceEventBuilderError};
+    pub use retry::{RetryPolicy, RetryPolicyBuilder};
+    #[cfg(all(feature = "async-std", not(target_arch = "wasm32")))]
+    pub use runtime::AsyncStdRuntime;
+    #[cfg(all(feature = "smol", not(target_arch = "wasm32")))]
+    pub use runtime::SmolRuntime;
+    #[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+    pub use runtime::TokioRuntime;
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    pub use runtime::{AsyncRuntime, RuntimeFuture};
+    #[cfg(feature = "schema")]
+    pub use schema::{SchemaError, SchemaRegistry, SchemaViolationPolicy, SCHEMA_ERRORS_PROPERTY};
+    pub use scrub::{Scrubber, REDACTED};
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use segment::{SegmentSink, SEGMENT_BATCH_URL};
+    #[cfg(feature = "signing")]
+    pub use signing::SigningKey;
+    pub use sink::EventSink;
+    #[cfg(feature = "encryption")]
+    pub use spool_key::{SpoolKey, SpoolKeyStore};
+    #[cfg(feature = "sled")]
+    pub use storage::SledStorage;
+    #[cfg(feature = "sqlite")]
+    pub use storage::SqliteStorage;
+    pub use storage::{Compaction, FileStorage, Storage};
+    pub use timeouts::{Timeouts, TimeoutsBuilder, TimeoutsBuilderError};
+    pub use timezone::LocalTimeZone;
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use tls::{TlsConfig, TlsConfigBuilder, TlsConfigBuilderError, TlsVersion};
+    #[cfg(feature = "ureq")]
+    pub use transport::UreqTransport;
+    pub use transport::{
+        AsyncHttpTransport, HttpRequest, HttpResponse, HttpTransport, ReqwestTransport,
+        TransportFuture,
+    };
+    #[cfg(feature = "hyper")]
+    pub use transport::{HyperConnector, HyperTransport};
+    pub use typed_event::TypedEvent;
+    pub use typed_session::TypedSession;
+    #[cfg(target_arch = "wasm32")]
+    pub use web::GameEventsIOWebClient;
+    #[cfg(feature = "winit")]
+    pub use winit::WinitLifecycle;
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use worker::GameEventsIOWorker;
+    pub use worker::{FlushPolicy, FlushPolicyBuilder};
 
-    /// Event-specific properties
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
-    pub event_properties: HashMap<String, serde_json::Value>,
+    // Header types taken by transports and header callbacks
+    pub use reqwest::header::{HeaderMap, HeaderValue};
 
-    /// User properties (will be merged with existing user data)
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
-    pub user_properties: HashMap<String, serde_json::Value>,
+    use headers::HeaderCallback;
+    use pool::EventPool;
+    use reqwest::header::{AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE};
+    use serde::Serialize;
+    use storage::Store;
+    use std::borrow::Cow;
+    use std::collections::{HashMap, HashSet, VecDeque};
+    use std::fmt;
+    #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+    use std::io::BufRead;
+    use std::ops::ControlFlow;
+    #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+    use std::path::Path;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use transport::Received;
+    use uuid::Uuid;
+    use web_time::{Instant, SystemTime, UNIX_EPOCH};
 }
 
-impl GameEventsIOEventBuilder {
-    fn default_time(&self) -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_secs()
+/// Timeout applied to every request sent to the backend
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Turn a typed event payload into event properties
+#[cfg(feature = "http")]
+fn to_properties<T: Serialize>(payload: &T) -> Properties {
+    match serde_json::to_value(payload) {
+        Ok(serde_json::Value::Object(map)) => map
+            .into_iter()
+            .map(|(key, value)| (key.into(), value))
+            .collect(),
+        _ => HashMap::new(),
     }
 }
 
-use uuid::Uuid;
-
 /// Session structure that holds common properties for events
+#[cfg(feature = "http")]
 #[derive(Clone, Debug, Builder)]
 #[builder(setter(into))]
 pub struct GameEventsIOSession {
@@ -63,9 +291,114 @@ pub struct GameEventsIOSession {
 
     /// User properties that will be added to all events in this session
     #[builder(default)]
-    user_properties: HashMap<String, serde_json::Value>,
+    user_properties: Properties,
+
+    /// User property operations waiting to be attached to the next event
+    #[builder(setter(skip))]
+    #[builder(default)]
+    pending_user_property_ops: Vec<UserPropertyMutation>,
+
+    /// Start a new session_id when no event was pushed for this long
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    idle_timeout: Option<Duration>,
+
+    /// Project tag for the session's events, for a [`GameEventsIORegistry`]
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    project: Option<String>,
+
+    /// When the last event was pushed
+    #[builder(setter(skip))]
+    #[builder(default = "Instant::now()")]
+    last_activity: Instant,
+
+    /// When the current session_id started
+    #[builder(setter(skip))]
+    #[builder(default = "Instant::now()")]
+    started_at: Instant,
+
+    /// Where event timestamps come from
+    #[builder(setter(custom))]
+    #[builder(default = "Arc::new(SystemClock)")]
+    clock: Arc<dyn Clock>,
+
+    /// When the current session_id started, in Unix milliseconds
+    #[builder(setter(skip))]
+    #[builder(default = "self.default_epoch()")]
+    epoch: u64,
+
+    /// Attach the device's time zone and UTC offset to events (default: on)
+    #[builder(default = "true")]
+    report_time_zone: bool,
+
+    /// Time zone detected when the current session_id started
+    #[builder(setter(skip))]
+    #[builder(default = "self.default_time_zone()")]
+    time_zone: LocalTimeZone,
+
+    /// Events logged under the current session_id
+    #[builder(setter(skip))]
+    #[builder(default)]
+    event_count: u64,
+
+    /// Whether `end` was called for the current session_id
+    #[builder(setter(skip))]
+    #[builder(default)]
+    ended: bool,
+
+    /// Where the session's remaining events go when it is dropped
+    #[builder(setter(custom))]
+    #[builder(field(
+        ty = "Option<GameEventsIOProducer>",
+        build = "producer::DropProducer(self.producer.clone())"
+    ))]
+    producer: producer::DropProducer,
+}
+
+#[cfg(feature = "http")]
+impl GameEventsIOSessionBuilder {
+    /// Stamp events with the time from `clock` instead of the system clock
+    pub fn clock(&mut self, clock: impl Clock + 'static) -> &mut Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// End the session when it is dropped, sending `session_end` and the
+    /// events not yet taken to a client through `producer`
+    pub fn producer(&mut self, producer: GameEventsIOProducer) -> &mut Self {
+        self.producer = Some(producer);
+        self
+    }
+
+    fn default_epoch(&self) -> u64 {
+        clock::unix_millis(self.now())
+    }
+
+    fn default_time_zone(&self) -> LocalTimeZone {
+        if self.report_time_zone == Some(false) {
+            return LocalTimeZone::default();
+        }
+        LocalTimeZone::detect(self.now())
+    }
+
+    fn now(&self) -> SystemTime {
+        match &self.clock {
+            Some(clock) => clock.now(),
+            None => SystemTime::now(),
+        }
+    }
 }
 
+/// Batch size set by [`GameEventsIOClientBuilder::high_throughput`]
+#[cfg(feature = "http")]
+pub const HIGH_THROUGHPUT_BATCH: usize = 1000;
+
+/// Idle timeout used by [`GameEventsIOSession::start`]
+#[cfg(feature = "http")]
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+#[cfg(feature = "http")]
 impl Default for GameEventsIOSession {
     fn default() -> Self {
         let mut session = GameEventsIOSessionBuilder::default()
@@ -73,17 +406,13 @@ impl Default for GameEventsIOSession {
             .expect("Failed to create default GameEventsIOSession");
 
         // Auto-send new_session event
-        let mut props = HashMap::new();
-        props.insert(
-            "session_id".to_string(),
-            serde_json::json!(session.session_id.clone()),
-        );
-        session.push_event("new_session", props);
+        session.push_new_session_event();
 
         session
     }
 }
 
+#[cfg(feature = "http")]
 impl GameEventsIOSession {
     /// Create a new session with user_id and session_id
     pub fn new(user_id: impl Into<String>, session_id: impl Into<String>) -> Self {
@@ -94,22 +423,130 @@ impl GameEventsIOSession {
             .expect("Failed to create GameEventsIOSession");
 
         // Auto-send new_session event
+        session.push_new_session_event();
+
+        session
+    }
+
+    /// Start a session for `user_id` with a generated session_id
+    ///
+    /// The session rotates to a fresh session_id (and logs another
+    /// `new_session` event) once it has been idle for [`DEFAULT_IDLE_TIMEOUT`].
+    pub fn start(user_id: impl Into<String>) -> Self {
+        let mut session = GameEventsIOSessionBuilder::default()
+            .user_id(user_id)
+            .idle_timeout(DEFAULT_IDLE_TIMEOUT)
+            .build()
+            .expect("Failed to create GameEventsIOSession");
+
+        session.push_new_session_event();
+
+        session
+    }
+
+    /// Change (or disable, with `None`) the idle timeout after which the session rotates
+    pub fn set_idle_timeout(&mut self, idle_timeout: Option<Duration>) {
+        self.idle_timeout = idle_timeout;
+    }
+
+    /// Count user activity that did not produce an event towards the idle timeout
+    ///
+    /// Rotates the session first if it has already been idle too long.
+    pub fn touch(&mut self) {
+        if !self.ended {
+            self.rotate_if_idle();
+        }
+    }
+
+    /// End the session by logging a `session_end` event
+    ///
+    /// The event carries `duration_seconds` and `event_count` (every event
+    /// logged under this session_id, including `new_session`). Pushing another
+    /// event afterwards starts a new session with a generated session_id.
+    ///
+    /// A session built with a
+    /// [`producer`](GameEventsIOSessionBuilder::producer) does this when it is
+    /// dropped. Otherwise its events live in the session itself, so call this
+    /// and [`take_events`](Self::take_events) before dropping it.
+    pub fn end(&mut self) {
+        if !self.ended {
+            self.record_session_end(Instant::now());
+            self.ended = true;
+        }
+    }
+
+    fn push_new_session_event(&mut self) {
         let mut props = HashMap::new();
         props.insert(
-            "session_id".to_string(),
-            serde_json::json!(session.session_id.clone()),
+            "session_id".into(),
+            serde_json::json!(self.session_id.clone()),
         );
-        session.push_event("new_session", props);
+        let _ = self.record("new_session", props);
+    }
 
-        session
+    fn record_session_end(&mut self, at: Instant) {
+        let mut props = HashMap::new();
+        props.insert(
+            "duration_seconds".into(),
+            serde_json::json!(at.duration_since(self.started_at).as_secs()),
+        );
+        props.insert("event_count".into(), serde_json::json!(self.event_count));
+        let _ = self.record("session_end", props);
+    }
+
+    /// Start a new session_id if the session ended or has been idle too long
+    ///
+    /// An idle session is closed with a `session_end` event first.
+    fn rotate_if_idle(&mut self) {
+        let now = Instant::now();
+        let idle = self
+            .idle_timeout
+            .is_some_and(|timeout| now.duration_since(self.last_activity) >= timeout);
+        if idle && !self.ended {
+            self.record_session_end(self.last_activity);
+        }
+        self.last_activity = now;
+
+        if idle || self.ended {
+            self.session_id = Uuid::new_v4().to_string();
+            self.started_at = now;
+            self.epoch = clock::unix_millis(self.clock.now());
+            if self.report_time_zone {
+                self.time_zone = LocalTimeZone::detect(self.clock.now());
+            }
+            self.event_count = 0;
+            self.ended = false;
+            self.push_new_session_event();
+        }
     }
 
     /// Add an event to the session
+    ///
+    /// An event that fails validation is dropped; use
+    /// [`try_push_event`](Self::try_push_event) to get the error instead.
     pub fn push_event(
         &mut self,
-        event: impl Into<String>,
-        event_properties: HashMap<String, serde_json::Value>,
+        event: impl Into<Cow<'static, str>>,
+        event_properties: Properties,
     ) {
+        let _ = self.try_push_event(event, event_properties);
+    }
+
+    /// Add an event to the session, returning an error if it fails validation
+    pub fn try_push_event(
+        &mut self,
+        event: impl Into<Cow<'static, str>>,
+        event_properties: Properties,
+    ) -> Result<(), GameEventsIOEventBuilderError> {
+        self.rotate_if_idle();
+        self.record(event, event_properties)
+    }
+
+    fn record(
+        &mut self,
+        event: impl Into<Cow<'static, str>>,
+        event_properties: Properties,
+    ) -> Result<(), GameEventsIOEventBuilderError> {
         // Determine user_id: check properties first, then session
         let user_id = if let Some(uid) = event_properties.get("user_id").and_then(|v| v.as_str()) {
             uid.to_string()
@@ -126,25 +563,117 @@ impl GameEventsIOSession {
             };
 
         // Create the event
-        let event = GameEventsIOEventBuilder::default()
+        let mut builder = GameEventsIOEventBuilder::default();
+        if let Some(project) = &self.project {
+            builder.project(project.clone());
+        }
+        if let Some(name) = &self.time_zone.name {
+            builder.time_zone(name.clone());
+        }
+        if let Some(offset) = self.time_zone.utc_offset_minutes {
+            builder.utc_offset_minutes(offset);
+        }
+        let mut event = builder
             .event(event)
             .user_id(user_id)
             .session_id(session_id)
             .user_properties(self.user_properties.clone())
             .event_properties(event_properties)
-            .build()
-            .expect("Failed to build event");
+            .timestamp(self.clock.now())
+            .sequence(self.event_count + 1)
+            .session_epoch(self.epoch)
+            .build()?;
+        // Pending ops ride on the next event that is actually recorded
+        event.user_property_ops = std::mem::take(&mut self.pending_user_property_ops);
 
         self.events.push(event);
+        self.event_count += 1;
+        Ok(())
+    }
+
+    /// Add a validated purchase event to the session
+    ///
+    /// The event has [`EventPriority::Immediate`], so the client it is logged
+    /// on sends it right away.
+    pub fn push_purchase(&mut self, purchase: PurchaseEvent) {
+        if self
+            .try_push_event("purchase", to_properties(&purchase))
+            .is_ok()
+        {
+            if let Some(event) = self.events.last_mut() {
+                event.priority = EventPriority::Immediate;
+            }
+        }
+    }
+
+    /// Add a [`TypedEvent`] to the session, with its fields as event properties
+    pub fn push_typed<E: TypedEvent>(&mut self, event: &E) {
+        self.push_event(E::EVENT_NAME, to_properties(event));
+    }
+
+    /// Add an ad lifecycle event (impression, click, reward) to the session
+    pub fn ad_event(&mut self, action: AdAction, ad: AdEvent) {
+        self.push_event(action.event_name(), to_properties(&ad));
+    }
+
+    /// Add a progression (start / complete / fail) event to the session
+    ///
+    /// The event carries the step's fields plus a canonical `progression_id`
+    /// such as "world_1/level_3".
+    pub fn progression(&mut self, status: ProgressionStatus, step: ProgressionEvent) {
+        let mut props = to_properties(&step);
+        props.insert(
+            "progression_id".into(),
+            serde_json::json!(step.progression_id()),
+        );
+        self.push_event(status.event_name(), props);
+    }
+
+    /// Add a virtual economy source or sink event to the session
+    pub fn resource_event(&mut self, flow: FlowType, resource: ResourceEvent) {
+        self.push_event(flow.event_name(), to_properties(&resource));
     }
 
     /// Add or update a user property for this session
-    pub fn set_user_property(&mut self, key: impl Into<String>, value: serde_json::Value) {
+    pub fn set_user_property(
+        &mut self,
+        key: impl Into<Cow<'static, str>>,
+        value: serde_json::Value,
+    ) {
         self.user_properties.insert(key.into(), value);
     }
 
+    /// Add `amount` to a numeric user property on the server
+    ///
+    /// This and the other user property operations are sent with the next
+    /// event pushed to the session.
+    pub fn increment_user_property(
+        &mut self,
+        key: impl Into<String>,
+        amount: impl Into<serde_json::Number>,
+    ) {
+        self.push_user_property_op(key, UserPropertyOp::Increment(amount.into()));
+    }
+
+    /// Append a value to a list user property on the server
+    pub fn append_user_property(&mut self, key: impl Into<String>, value: serde_json::Value) {
+        self.push_user_property_op(key, UserPropertyOp::Append(value));
+    }
+
+    /// Set a user property on the server unless the user already has it
+    pub fn set_once_user_property(&mut self, key: impl Into<String>, value: serde_json::Value) {
+        self.push_user_property_op(key, UserPropertyOp::SetOnce(value));
+    }
+
+    fn push_user_property_op(&mut self, key: impl Into<String>, op: UserPropertyOp) {
+        self.pending_user_property_ops.push(UserPropertyMutation {
+            property: key.into(),
+            op,
+        });
+    }
+
     /// Set new user properties
-    pub fn set_user_properties(&mut self, user_properties: HashMap<String, serde_json::Value>) {
+    pub fn set_user_properties(&mut self, user_properties: Properties) {
         self.user_properties = user_properties;
     }
 
@@ -158,8 +687,13 @@ impl GameEventsIOSession {
         &self.session_id
     }
 
+    /// Get the project this session's events are tagged with
+    pub fn project(&self) -> Option<&str> {
+        self.project.as_deref()
+    }
+
     /// Get all user properties for this session
-    pub fn user_properties(&self) -> &HashMap<String, serde_json::Value> {
+    pub fn user_properties(&self) -> &Properties {
         &self.user_properties
     }
 
@@ -170,9 +704,28 @@ impl GameEventsIOSession {
     }
 }
 
+#[cfg(feature = "http")]
+impl Drop for GameEventsIOSession {
+    fn drop(&mut self) {
+        let Some(producer) = self.producer.0.take() else {
+            return;
+        };
+        self.end();
+        for event in self.events.drain(..) {
+            producer.log_event(event);
+        }
+    }
+}
+
 /// game-events.io SDK client
-#[derive(Debug, Clone, Builder)]
+///
+/// Not `Clone`: the client owns its queue and spool, and a copy would flush
+/// and spool the same events again. Share one through a
+/// [`GameEventsIOHandle`] instead.
+#[cfg(feature = "http")]
+#[derive(Debug, Builder)]
 #[builder(setter(into))]
+#[builder(build_fn(private, name = "build_client"))]
 pub struct GameEventsIOClient {
     /// API key for authentication
     api_key: String,
@@ -181,171 +734,4593 @@ pub struct GameEventsIOClient {
     #[builder(default = "\"https://api.game-events.io/v1/events\".to_string()")]
     backend_url: String,
 
-    /// HTTP client for making requests
-    #[builder(setter(skip))]
-    #[builder(
-        default = "reqwest::blocking::Client::builder().timeout(std::time::Duration::from_secs(15)).build().expect(\"failed to build reqwest client\")"
-    )]
-    client: reqwest::blocking::Client,
+    /// Data residency region whose endpoints are used; conflicts with
+    /// `backend_url`
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    region: Option<Region>,
 
-    /// Buffered events waiting to be sent
-    #[builder(setter(skip))]
+    /// User data deletion endpoint (default: `deletion-requests` next to `backend_url`)
+    #[builder(setter(strip_option))]
     #[builder(default)]
-    events: Vec<GameEventsIOEvent>,
-}
+    deletion_url: Option<String>,
 
-impl GameEventsIOClient {
-    /// Create a new GameEventsIO client
-    pub fn new(api_key: impl Into<String>) -> Self {
-        GameEventsIOClientBuilder::default()
-            .api_key(api_key)
-            .build()
-            .expect("Failed to create GameEventsIOClient")
-    }
+    /// Health check endpoint (default: `health` next to `backend_url`)
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    health_url: Option<String>,
 
-    /// Log an event (adds to buffer)
-    pub fn log_event(&mut self, event: GameEventsIOEvent) {
-        self.events.push(event);
-    }
+    /// Experiment definitions endpoint (default: `experiments` next to `backend_url`)
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    experiments_url: Option<String>,
 
-    /// Send all buffered events to the backend
-    pub fn flush(&mut self) -> Result<String, reqwest::Error> {
-        if self.events.is_empty() {
-            return Ok("No events to send".to_string());
-        }
+    /// Experiment definitions by key, kept in `state_dir` for offline runs
+    #[builder(setter(skip))]
+    #[builder(default)]
+    experiments: HashMap<String, experiment::Experiment>,
 
-        let events_to_send: Vec<GameEventsIOEvent> = self.events.drain(..).collect();
+    /// Experiments whose definitions were fetched by this client
+    #[builder(setter(skip))]
+    #[builder(default)]
+    fetched_experiments: HashSet<String>,
 
-        let response = self
-            .client
-            .post(&self.backend_url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&events_to_send)
-            .send()?
-            .text()?;
+    /// Cohort membership endpoint (default: `cohorts` next to `backend_url`)
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    cohorts_url: Option<String>,
 
-        Ok(response)
-    }
+    /// How long fetched cohort memberships are reused (default: 10 minutes)
+    #[builder(default = "Duration::from_secs(10 * 60)")]
+    cohort_ttl: Duration,
 
-    /// Send events in batches (useful for large event counts)
-    pub fn flush_batch(&mut self, batch_size: usize) -> Result<String, reqwest::Error> {
-        if self.events.is_empty() {
-            return Ok("No events to send".to_string());
-        }
+    /// Cohort ids by user id, with when they were fetched
+    #[builder(setter(skip))]
+    #[builder(default)]
+    cohort_cache: HashMap<String, (Instant, Vec<String>)>,
 
-        let events_to_send: Vec<GameEventsIOEvent> = if self.events.len() > batch_size {
-            self.events.drain(..batch_size).collect()
-        } else {
-            self.events.drain(..).collect()
-        };
+    /// Proxy the default reqwest transport connects through (default: from
+    /// the environment); custom transports take theirs when created
+    #[cfg(not(target_arch = "wasm32"))]
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    proxy: Option<Proxy>,
 
-        let response = self
-            .client
-            .post(&self.backend_url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&events_to_send)
-            .send()?
-            .text()?;
+    /// Root certificates, pinned keys and minimum version for the default
+    /// reqwest transport (default: the TLS library's defaults)
+    #[cfg(not(target_arch = "wasm32"))]
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    tls: Option<TlsConfig>,
 
-        Ok(response)
-    }
+    /// How long requests may take; `connect` and `read` apply to the default
+    /// reqwest transport only (default: 15 seconds in total)
+    #[builder(default)]
+    timeouts: Timeouts,
 
-    /// Get the number of buffered events
-    pub fn pending_events_count(&self) -> usize {
-        self.events.len()
-    }
-}
+    /// Sends the requests of blocking flushes
+    #[cfg(not(target_arch = "wasm32"))]
+    #[builder(setter(custom))]
+    #[builder(default = "Arc::new(ReqwestTransport::new())")]
+    transport: Arc<dyn HttpTransport>,
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Sends the requests of async flushes
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    #[builder(setter(custom))]
+    #[builder(default = "Arc::new(ReqwestTransport::new())")]
+    async_transport: Arc<dyn AsyncHttpTransport>,
 
-    #[test]
-    fn test_event_creation() {
-        let event = GameEventsIOEventBuilder::default()
-            .event("test_event")
-            .user_id("user123")
-            .session_id("session456")
-            .build()
-            .unwrap();
+    /// Executor async flushes wait and spawn on (default: that of the
+    /// enabled runtime feature)
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    #[builder(setter(custom))]
+    #[builder(default = "runtime::default_runtime()")]
+    async_runtime: Arc<dyn AsyncRuntime>,
 
-        assert_eq!(event.event, "test_event");
-        assert_eq!(event.user_id, "user123");
-        assert_eq!(event.session_id, "session456");
-        assert!(event.time > 0);
-    }
+    /// File that mirrors the pending events so they survive restarts
+    #[cfg_attr(feature = "persistence", builder(setter(strip_option)))]
+    #[cfg_attr(not(feature = "persistence"), builder(setter(skip)))]
+    #[builder(default)]
+    spool_path: Option<PathBuf>,
 
-    #[test]
-    fn test_client_creation() {
-        let client = GameEventsIOClient::new("test_api_key");
-        assert_eq!(client.pending_events_count(), 0);
-    }
+    /// Where the key the spool is encrypted with comes from
+    #[cfg(feature = "encryption")]
+    #[builder(setter(custom))]
+    #[builder(default)]
+    spool_key: Option<Arc<dyn SpoolKeyStore>>,
 
-    #[test]
-    fn test_event_buffering() {
-        let mut client = GameEventsIOClient::new("test_api_key");
+    /// Embedded database holding the pending events and SDK state, in place
+    /// of `spool_path` and `state_dir`
+    #[cfg(feature = "sled")]
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    sled_db: Option<sled::Db>,
 
-        let event = GameEventsIOEventBuilder::default()
-            .event("test_event")
-            .user_id("user123")
-            .session_id("session456")
-            .build()
-            .unwrap();
+    /// SQLite database holding the pending events and SDK state, in place of
+    /// `spool_path` and `state_dir`
+    #[cfg(feature = "sqlite")]
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    sqlite_path: Option<PathBuf>,
 
-        client.log_event(event);
-        assert_eq!(client.pending_events_count(), 1);
-    }
+    /// How long events wait in the SQLite database before they are pruned
+    /// unsent (default: 30 days)
+    #[cfg(feature = "sqlite")]
+    #[builder(default = "storage::SQLITE_RETENTION")]
+    sqlite_retention: Duration,
 
-    #[test]
-    fn test_session_creation() {
-        let session = GameEventsIOSession::new("user123", "session456");
-        assert_eq!(session.user_id(), "user123");
-        assert_eq!(session.session_id(), "session456");
-        assert!(session.user_properties().is_empty());
-    }
+    /// Custom backend for the pending events and SDK state, in place of
+    /// `spool_path` and `state_dir`
+    #[builder(setter(custom))]
+    #[builder(default)]
+    storage: Option<Arc<dyn Storage>>,
 
-    #[test]
-    fn test_session_with_user_properties() {
-        let mut user_props = HashMap::new();
-        user_props.insert("platform".to_string(), serde_json::json!("rust"));
-        user_props.insert("version".to_string(), serde_json::json!("1.0"));
+    /// The storage in use and how events are encoded for it, settled when
+    /// the client is built
+    #[builder(setter(skip))]
+    #[builder(default)]
+    store: Store,
 
-        let session = GameEventsIOSessionBuilder::default()
-            .user_id("user123")
-            .session_id("session456")
-            .user_properties(user_props)
-            .build()
-            .unwrap();
+    /// How failed flushes are retried
+    #[builder(default)]
+    retry_policy: RetryPolicy,
 
-        assert_eq!(session.user_properties().len(), 2);
-    }
+    /// Stops flush attempts for a while after repeated failures (default: off)
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    circuit_breaker: Option<CircuitBreaker>,
 
-    #[test]
-    fn test_session_event_creation() {
-        let mut session = GameEventsIOSession::new("user123", "session456");
-        session.push_event("test_event", HashMap::new());
+    /// Failed attempts since the last successful one
+    #[builder(setter(skip))]
+    #[builder(default)]
+    consecutive_failures: u32,
 
-        // We expect 2 events now: new_session + test_event
-        let events = session.take_events(2);
-        assert_eq!(events.len(), 2);
-        
-        // Check the second event (the one we added)
-        let event = &events[1];
+    /// When the open circuit breaker allows a trial attempt
+    #[builder(setter(skip))]
+    #[builder(default)]
+    breaker_open_until: Option<Instant>,
 
-        assert_eq!(event.event, "test_event");
-        assert_eq!(event.user_id, "user123");
-        assert_eq!(event.session_id, "session456");
-    }
+    /// When the backend said it will accept requests again
+    #[builder(setter(skip))]
+    #[builder(default)]
+    throttled_until: Option<Instant>,
 
-    #[test]
-    fn test_session_set_user_property() {
-        let mut session = GameEventsIOSession::new("user123", "session456");
-        session.set_user_property("platform", serde_json::json!("rust"));
-        session.set_user_property("level", serde_json::json!(10));
+    /// Seconds the backend's clock is ahead of this device's, from the
+    /// `Date` header of the latest successful response
+    #[builder(setter(skip))]
+    #[builder(default)]
+    clock_offset: Option<i64>,
 
-        assert_eq!(session.user_properties().len(), 2);
+    /// Largest JSON body sent in one request; bigger flushes are split
+    /// (default: unlimited)
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    max_request_bytes: Option<usize>,
 
-        // Events created after setting properties should include them
+    /// Wire format of flush payloads and the spool (default: JSON)
+    #[builder(default)]
+    payload_format: PayloadFormat,
+
+    /// Encoding applied to flush payloads
+    #[builder(default)]
+    compression: Compression,
+
+    /// Extra headers sent with every request, e.g. a tenant id
+    #[builder(setter(custom))]
+    #[builder(default)]
+    headers: Vec<(String, String)>,
+
+    /// `headers`, parsed
+    #[builder(setter(skip))]
+    #[builder(default)]
+    parsed_headers: HeaderMap,
+
+    /// Adds headers to every request as it is sent
+    #[builder(setter(custom))]
+    #[builder(default)]
+    header_callback: Option<Arc<dyn HeaderCallback>>,
+
+    /// Shared secret every request to the backend is signed with
+    #[cfg(feature = "signing")]
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    signing_key: Option<SigningKey>,
+
+    /// Directory for SDK state kept across restarts (e.g. the anonymous id)
+    #[cfg_attr(feature = "persistence", builder(setter(strip_option)))]
+    #[cfg_attr(not(feature = "persistence"), builder(setter(skip)))]
+    #[builder(default)]
+    state_dir: Option<PathBuf>,
+
+    /// Directory the panic hook saves crashes to; saved crashes are queued
+    /// when the next client is built
+    #[cfg(not(target_arch = "wasm32"))]
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    crash_dir: Option<PathBuf>,
+
+    /// Ids of queued crash events and the files they were saved in, which
+    /// are deleted once the events are sent
+    #[cfg(not(target_arch = "wasm32"))]
+    #[builder(setter(skip))]
+    #[builder(default)]
+    unsent_crashes: Vec<(String, PathBuf)>,
+
+    /// Generated id for this install, attached to every event
+    #[builder(setter(skip))]
+    #[builder(default = "Uuid::new_v4().to_string()")]
+    anonymous_id: String,
+
+    /// User id set by `identify`, used for events logged without one
+    #[builder(setter(skip))]
+    #[builder(default)]
+    identified_user_id: Option<String>,
+
+    /// Properties merged into every logged event; the event's own properties win
+    #[builder(default)]
+    super_properties: Properties,
+
+    /// Tracking consent for a fresh install; a choice persisted in `state_dir`
+    /// takes precedence
+    #[builder(default)]
+    consent: ConsentState,
+
+    /// Version and build of the game, attached to every event and sent as headers
+    #[builder(default)]
+    app_info: AppInfo,
+
+    /// Redacts personal data from event and user properties before they are queued
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    scrubber: Option<Scrubber>,
+
+    /// JSON Schemas checked against event properties in debug builds
+    #[cfg(feature = "schema")]
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    schema_registry: Option<SchemaRegistry>,
+
+    /// Where the timestamps of events the client creates itself, and the
+    /// age checked by the batch policy, come from
+    #[builder(setter(custom))]
+    #[builder(default = "Arc::new(SystemClock)")]
+    clock: Arc<dyn Clock>,
+
+    /// Where flushed events go instead of `backend_url`; defaults to a
+    /// [`DebugSink`] when [`DEBUG_ENV_VAR`] is set
+    #[builder(setter(custom))]
+    #[builder(default)]
+    sink: Option<Arc<dyn EventSink>>,
+
+    /// Receives events the backend rejected as not worth retrying, with the
+    /// reason in `rejection_code` and `rejection_message` properties
+    #[builder(setter(custom))]
+    #[builder(default)]
+    dead_letter_sink: Option<Arc<dyn EventSink>>,
+
+    /// Sinks that also receive every batch the main destination accepted
+    #[builder(setter(custom))]
+    #[builder(default)]
+    mirrors: Vec<Arc<dyn EventSink>>,
+
+    /// Serialize and report flushed batches instead of sending them; mirrors
+    /// are skipped too
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    dry_run: Option<DryRun>,
+
+    /// Receives the batches a dry run would have sent, e.g. a [`DebugSink`]
+    #[builder(setter(custom))]
+    #[builder(default)]
+    dry_run_sink: Option<Arc<dyn EventSink>>,
+
+    /// Middleware run on every logged event and flushed batch, in order
+    #[builder(setter(custom))]
+    #[builder(default)]
+    plugins: Vec<Arc<dyn ClientPlugin>>,
+
+    /// Add OS, OS version, architecture, locale and device model to the
+    /// super properties when the client is built
+    #[builder(default)]
+    collect_device_metadata: bool,
+
+    /// Best-effort final flush with this deadline when the client is dropped
+    ///
+    /// Ignored in the browser, where a flush cannot block.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    flush_on_drop: Option<Duration>,
+
+    /// Maximum number of buffered events (default: unbounded)
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    max_pending_events: Option<usize>,
+
+    /// What happens to new events once `max_pending_events` is reached
+    #[builder(default)]
+    overflow_policy: OverflowPolicy,
+
+    /// Events older than this are dropped, unsent, when the queue is loaded
+    /// and before each flush (default: kept until sent)
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    event_ttl: Option<Duration>,
+
+    /// Bytes of JSON the queue may hold; the oldest events over it are
+    /// dropped when the queue is loaded and before each flush (default:
+    /// unbounded)
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    max_retained_bytes: Option<usize>,
+
+    /// When logging an event also flushes the queue (default: never)
+    #[builder(default)]
+    batch_policy: BatchPolicy,
+
+    /// Sent events kept for [`pooled_event`](GameEventsIOClient::pooled_event)
+    /// to reuse (default: none)
+    #[builder(setter(custom))]
+    #[builder(default)]
+    event_pool: EventPool,
+
+    /// Bytes of JSON the buffered events take up; only tracked when
+    /// `batch_policy` has a byte limit or `max_retained_bytes` is set
+    #[builder(setter(skip))]
+    #[builder(default)]
+    pending_bytes: usize,
+
+    /// No automatic flush is attempted before this, after one failed
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    #[builder(setter(skip))]
+    #[builder(default)]
+    auto_flush_paused_until: Option<Instant>,
+
+    /// Per event name limits on how often an event may be logged
+    #[builder(default)]
+    rate_limits: HashMap<String, RateLimit>,
+
+    /// Token buckets for the event names in `rate_limits`
+    #[builder(setter(skip))]
+    #[builder(default)]
+    rate_limiters: HashMap<String, rate_limit::Bucket>,
+
+    /// Fraction of logged events that are kept, from 0.0 to 1.0 (default: 1.0)
+    #[builder(default = "1.0")]
+    sample_rate: f64,
+
+    /// Deployment stage stamped onto every event (default: production)
+    #[builder(default)]
+    environment: Environment,
+
+    /// Backend URLs that replace `backend_url` in particular environments
+    #[builder(setter(custom))]
+    #[builder(default)]
+    environment_endpoints: HashMap<Environment, String>,
+
+    /// Event names dropped when logged, on top of those the remote
+    /// configuration disables
+    #[builder(setter(custom))]
+    #[builder(default)]
+    disabled_events: HashSet<String>,
+
+    /// Where settings that can change without an update are fetched from
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    remote_config: Option<RemoteConfigSource>,
+
+    /// Settings from the latest remote configuration document
+    #[builder(setter(skip))]
+    #[builder(default)]
+    remote_settings: RemoteSettings,
+
+    /// Flag evaluations already reported, as (flag, user id, value); forgotten
+    /// all at once when [`MAX_FLAG_EXPOSURES`] is reached
+    #[builder(setter(skip))]
+    #[builder(default)]
+    flag_exposures: HashSet<(String, String, bool)>,
+
+    /// When the remote configuration is fetched again; `None` before the
+    /// first fetch
+    #[builder(setter(skip))]
+    #[builder(default)]
+    remote_config_refresh_at: Option<SystemTime>,
+
+    /// When the remote configuration in use was fetched
+    #[builder(setter(skip))]
+    #[builder(default)]
+    remote_config_fetched_at: Option<SystemTime>,
+
+    /// Background fetch of the remote configuration in progress
+    #[builder(setter(skip))]
+    #[builder(default)]
+    remote_config_fetch: Option<remote_config::PendingFetch>,
+
+    /// Number of retries the most recent flush needed
+    #[builder(setter(skip))]
+    #[builder(default)]
+    last_flush_retries: u32,
+
+    /// Requests made by the most recent flush
+    #[builder(setter(skip))]
+    #[builder(default)]
+    last_flush_chunks: Vec<ChunkOutcome>,
+
+    /// Events the backend rejected in the most recent flush
+    #[builder(setter(skip))]
+    #[builder(default)]
+    last_flush_rejected: Vec<RejectedEvent>,
+
+    /// What compacting the storage last kept and dropped
+    #[builder(setter(skip))]
+    #[builder(default)]
+    last_compaction: Compaction,
+
+    /// Counters of enqueued, sent, retried and discarded events
+    #[builder(setter(skip))]
+    #[builder(default)]
+    metrics: Arc<ClientMetrics>,
+
+    /// Events sent through producers, not yet queued
+    #[builder(setter(skip))]
+    #[builder(default)]
+    producers: producer::ProducerChannel,
+
+    /// Number of events discarded because they are disabled, per event name
+    #[builder(setter(skip))]
+    #[builder(default)]
+    suppressed_events: HashMap<String, u64>,
+
+    /// Number of events discarded by `rate_limits`, per event name
+    #[builder(setter(skip))]
+    #[builder(default)]
+    rate_limited_events: HashMap<String, u64>,
+
+    /// Buffered events waiting to be sent
+    #[builder(setter(skip))]
+    #[builder(default)]
+    events: VecDeque<GameEventsIOEvent>,
+}
+
+/// What a full client does with newly logged events
+#[cfg(feature = "http")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Discard the oldest buffered event to make room
+    #[default]
+    DropOldest,
+
+    /// Discard the new event
+    DropNewest,
+
+    /// Make [`GameEventsIOHandle::log_event`] wait until a flush frees space.
+    /// A client used directly cannot wait on itself and drops the new event.
+    Block,
+}
+
+/// Why [`try_log_event`](GameEventsIOClient::try_log_event) did not queue an
+/// event
+#[cfg(feature = "http")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryLogError {
+    /// The queue is at `max_pending_events`; flush, or drop the event
+    QueueFull,
+
+    /// Tracking consent was denied, or the event is disabled
+    Disabled,
+}
+
+#[cfg(feature = "http")]
+impl fmt::Display for TryLogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryLogError::QueueFull => f.write_str("event queue is full"),
+            TryLogError::Disabled => f.write_str("event is disabled"),
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl std::error::Error for TryLogError {}
+
+/// What a dry-run flush does with the events it reported
+#[cfg(feature = "http")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DryRun {
+    /// Remove them from the queue (and the spool) as if they had been sent
+    #[default]
+    Clear,
+
+    /// Keep them queued, e.g. to send them once dry-run mode is turned off
+    Retain,
+}
+
+#[cfg(feature = "http")]
+impl GameEventsIOClientBuilder {
+    /// Use `clock` instead of the system clock, e.g. a [`FixedClock`] in tests
+    pub fn clock(&mut self, clock: impl Clock + 'static) -> &mut Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Send events to `url` instead of `backend_url` while the client's
+    /// environment is `environment`
+    pub fn environment_endpoint(
+        &mut self,
+        environment: impl Into<Environment>,
+        url: impl Into<String>,
+    ) -> &mut Self {
+        self.environment_endpoints
+            .get_or_insert_with(HashMap::new)
+            .insert(environment.into(), url.into());
+        self
+    }
+
+    /// Keep pending events and SDK state in `storage` instead of the spool
+    /// file and state directory
+    pub fn storage(&mut self, storage: impl Storage + 'static) -> &mut Self {
+        self.storage = Some(Some(Arc::new(storage)));
+        self
+    }
+
+    /// Keep up to `capacity` sent events for
+    /// [`pooled_event`](GameEventsIOClient::pooled_event) to reuse
+    pub fn event_pool(&mut self, capacity: usize) -> &mut Self {
+        self.event_pool = Some(EventPool::new(capacity));
+        self
+    }
+
+    /// Settings for games logging thousands of events per second, e.g.
+    /// per-frame telemetry
+    ///
+    /// Sent events are pooled for [`pooled_event`](GameEventsIOClient::pooled_event)
+    /// and the queue is flushed in batches of [`HIGH_THROUGHPUT_BATCH`]
+    /// events. Setters called after this one override it.
+    pub fn high_throughput(&mut self) -> &mut Self {
+        self.event_pool(HIGH_THROUGHPUT_BATCH * 2)
+            .batch_policy(BatchPolicy {
+                max_events: Some(HIGH_THROUGHPUT_BATCH),
+                ..BatchPolicy::default()
+            })
+    }
+
+    /// Encrypt the spool with the key from `store`, e.g. a [`SpoolKey`] or a
+    /// closure reading the platform keystore
+    #[cfg(feature = "encryption")]
+    pub fn spool_key(&mut self, store: impl SpoolKeyStore + 'static) -> &mut Self {
+        self.spool_key = Some(Some(Arc::new(store)));
+        self
+    }
+
+    /// Send `name: value` with every request to the backend
+    ///
+    /// Headers the SDK sets itself, such as `Authorization`, take precedence.
+    pub fn header(&mut self, name: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.headers
+            .get_or_insert_with(Vec::new)
+            .push((name.into(), value.into()));
+        self
+    }
+
+    /// Drop events named `name` when they are logged
+    pub fn disable_event(&mut self, name: impl Into<String>) -> &mut Self {
+        self.disabled_events
+            .get_or_insert_with(HashSet::new)
+            .insert(name.into());
+        self
+    }
+
+    /// Have `callback` add headers to each request as it is sent, e.g. the
+    /// current trace context
+    pub fn header_callback(
+        &mut self,
+        callback: impl Fn(&mut HeaderMap) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.header_callback = Some(Some(Arc::new(callback)));
+        self
+    }
+
+    /// Send blocking flushes with `transport` instead of reqwest
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn transport(&mut self, transport: impl HttpTransport + 'static) -> &mut Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Send async flushes with `transport` instead of reqwest
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    pub fn async_transport(&mut self, transport: impl AsyncHttpTransport + 'static) -> &mut Self {
+        self.async_transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Wait and spawn on `runtime`, e.g. to pick one of several enabled
+    /// runtime features or an executor of your own
+    ///
+    /// Without the `tokio` feature, async requests go through the blocking
+    /// transport on the runtime's blocking threads, as reqwest's async client
+    /// needs Tokio.
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    pub fn async_runtime(&mut self, runtime: impl AsyncRuntime + 'static) -> &mut Self {
+        self.async_runtime = Some(Arc::new(runtime));
+        self
+    }
+
+    /// Send flushed events to `sink` instead of the HTTP backend
+    pub fn sink(&mut self, sink: impl EventSink + 'static) -> &mut Self {
+        self.sink = Some(Some(Arc::new(sink)));
+        self
+    }
+
+    /// Also write every successfully sent batch to `sink`, e.g. a [`FileSink`]
+    ///
+    /// Mirrors are best effort: their errors are ignored and not retried.
+    pub fn mirror(&mut self, sink: impl EventSink + 'static) -> &mut Self {
+        self.mirrors
+            .get_or_insert_with(Vec::new)
+            .push(Arc::new(sink));
+        self
+    }
+
+    /// Hand the batches a dry run would have sent to `sink`
+    ///
+    /// Like mirrors, its errors are ignored.
+    pub fn dry_run_sink(&mut self, sink: impl EventSink + 'static) -> &mut Self {
+        self.dry_run_sink = Some(Some(Arc::new(sink)));
+        self
+    }
+
+    /// Hand events the backend rejects as not worth retrying to `sink`
+    ///
+    /// Like mirrors, its errors are ignored. Without one such events are
+    /// dropped, but still listed by
+    /// [`last_flush_rejected`](GameEventsIOClient::last_flush_rejected).
+    pub fn dead_letter_sink(&mut self, sink: impl EventSink + 'static) -> &mut Self {
+        self.dead_letter_sink = Some(Some(Arc::new(sink)));
+        self
+    }
+
+    /// Add a plugin whose hooks see every event and flush
+    ///
+    /// Plugins run in the order they are added. When one's
+    /// [`before_enqueue`](ClientPlugin::before_enqueue) returns `false` the
+    /// event is discarded without being queued or counted as dropped, and
+    /// the plugins after it never see it.
+    pub fn plugin(&mut self, plugin: impl ClientPlugin + 'static) -> &mut Self {
+        self.plugins
+            .get_or_insert_with(Vec::new)
+            .push(Arc::new(plugin));
+        self
+    }
+
+    /// Override settings with `GAME_EVENTS_IO_*` environment variables, so
+    /// they can be changed without recompiling
+    ///
+    /// - `GAME_EVENTS_IO_API_KEY` sets `api_key`
+    /// - `GAME_EVENTS_IO_ENDPOINT` sets `backend_url`
+    /// - `GAME_EVENTS_IO_REGION` sets `region` ("us", "eu", "apac" or an events URL)
+    /// - `GAME_EVENTS_IO_ENVIRONMENT` sets `environment`
+    /// - `GAME_EVENTS_IO_FLUSH_INTERVAL` (seconds) sets the `batch_policy`'s `max_age`
+    /// - `GAME_EVENTS_IO_BATCH_SIZE` sets the `batch_policy`'s `max_events`
+    ///
+    /// Variables that are set win over anything configured before this call;
+    /// setters called after it win over the variables. Unset or empty
+    /// variables change nothing, and malformed numbers are an error.
+    pub fn env_overrides(&mut self) -> Result<&mut Self, GameEventsIOClientBuilderError> {
+        env_config::apply(self, |key| std::env::var(key).ok())?;
+        Ok(self)
+    }
+
+    /// Build the client, restoring any events left in the spool by a previous run
+    pub fn build(&self) -> Result<GameEventsIOClient, GameEventsIOClientBuilderError> {
+        let mut client = self.build_client()?;
+        if let Some(region) = &client.region {
+            if self.backend_url.is_some() {
+                return Err(format!(
+                    "region {} conflicts with backend_url {:?}; set only one",
+                    region, client.backend_url
+                )
+                .into());
+            }
+            client.backend_url = region.events_url().to_string();
+        }
+        if let Some(url) = client.environment_endpoints.get(&client.environment) {
+            client.backend_url = url.clone();
+        }
+        validate_endpoint(&client.backend_url)?;
+        if HeaderValue::from_str(&client.api_key).is_err() {
+            return Err("invalid api_key: must be a valid HTTP header value"
+                .to_string()
+                .into());
+        }
+        client.parsed_headers = headers::parse(&client.headers)?;
+        #[cfg(not(target_arch = "wasm32"))]
+        if client.proxy.is_some() || client.tls.is_some() || client.timeouts != Timeouts::default()
+        {
+            let proxy = client.proxy.clone().unwrap_or_default();
+            let mut transport =
+                ReqwestTransport::with_proxy(&proxy)?.with_timeouts(client.timeouts);
+            if let Some(tls) = &client.tls {
+                transport = transport.with_tls(tls)?;
+            }
+            let transport = Arc::new(transport);
+            if self.transport.is_none() {
+                client.transport = transport.clone();
+            }
+            #[cfg(feature = "tokio")]
+            if self.async_transport.is_none() {
+                client.async_transport = transport;
+            }
+        }
+        #[cfg(all(feature = "async", not(feature = "tokio"), not(target_arch = "wasm32")))]
+        if self.async_transport.is_none() {
+            client.async_transport = Arc::new(runtime::UnblockTransport::new(
+                client.transport.clone(),
+                client.async_runtime.clone(),
+            ));
+        }
+        if client.sink.is_none() && DebugSink::enabled_by_env() {
+            client.sink = Some(Arc::new(DebugSink::new()));
+        }
+        if !(0.0..=1.0).contains(&client.sample_rate) {
+            return Err(format!(
+                "invalid sample_rate {}: must be between 0.0 and 1.0",
+                client.sample_rate
+            )
+            .into());
+        }
+        match &client.deletion_url {
+            Some(url) => validate_endpoint(url)?,
+            None => client.deletion_url = deletion::default_url(&client.backend_url),
+        }
+        match &client.health_url {
+            Some(url) => validate_endpoint(url)?,
+            None => client.health_url = health::default_url(&client.backend_url),
+        }
+        if let Some(source) = &mut client.remote_config {
+            match &source.url {
+                Some(url) => validate_endpoint(url)?,
+                None => source.url = remote_config::default_url(&client.backend_url),
+            }
+        }
+        match &client.cohorts_url {
+            Some(url) => validate_endpoint(url)?,
+            None => client.cohorts_url = cohort::default_url(&client.backend_url),
+        }
+        match &client.experiments_url {
+            Some(url) => validate_endpoint(url)?,
+            None => client.experiments_url = experiment::default_url(&client.backend_url),
+        }
+        let mut backends: Vec<Arc<dyn Storage>> = Vec::new();
+        backends.extend(client.storage.clone());
+        #[cfg(feature = "sled")]
+        if let Some(db) = client.sled_db.clone() {
+            let storage =
+                SledStorage::new(db).map_err(|e| format!("failed to open sled storage: {}", e))?;
+            backends.push(Arc::new(storage));
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some(path) = client.sqlite_path.clone() {
+            let storage = SqliteStorage::open(&path, client.sqlite_retention)
+                .map_err(|e| format!("failed to open {}: {}", path.display(), e))?;
+            backends.push(Arc::new(storage));
+        }
+        if client.spool_path.is_some() || client.state_dir.is_some() {
+            backends.push(Arc::new(FileStorage::with_paths(
+                client.spool_path.clone(),
+                client.state_dir.clone(),
+            )));
+        }
+        if backends.len() > 1 {
+            return Err(
+                "set only one of storage, sled_db, sqlite_path, or spool_path and state_dir"
+                    .to_string()
+                    .into(),
+            );
+        }
+        client.store.backend = backends.pop();
+        {
+            let store = &client.store;
+            let read = |key| {
+                store
+                    .read_state(key)
+                    .map_err(|e| format!("failed to read {}: {}", key, e))
+            };
+            match read(ANONYMOUS_ID_KEY)? {
+                Some(id) => client.anonymous_id = id,
+                None => store.write_state(ANONYMOUS_ID_KEY, &client.anonymous_id),
+            }
+            client.identified_user_id = read(USER_ID_KEY)?;
+            if let Some(consent) = read(CONSENT_KEY)?.as_deref().and_then(ConsentState::parse) {
+                client.consent = consent;
+            }
+            if let Some(experiments) = read(EXPERIMENTS_KEY)? {
+                client.experiments = serde_json::from_str(&experiments).unwrap_or_default();
+            }
+            // A cached document that no longer parses or verifies is ignored
+            if let (Some(source), Some(document), Some(fetched_at)) = (
+                client.remote_config.clone(),
+                read(REMOTE_CONFIG_KEY)?,
+                read(REMOTE_CONFIG_FETCHED_AT_KEY)?.and_then(|secs| secs.parse().ok()),
+            ) {
+                if let Ok(settings) = source.parse(&document) {
+                    let fetched_at = UNIX_EPOCH + Duration::from_secs(fetched_at);
+                    client.remote_settings = settings;
+                    client.remote_config_fetched_at = Some(fetched_at);
+                    client.remote_config_refresh_at = Some(fetched_at + source.ttl);
+                }
+            }
+        }
+        client.revalidate_remote_config();
+        // Super properties set explicitly take precedence
+        for (key, value) in to_properties(&client.app_info) {
+            client.super_properties.entry(key).or_insert(value);
+        }
+        if client.collect_device_metadata {
+            for (key, value) in DeviceMetadata::collect().to_properties() {
+                client.super_properties.entry(key).or_insert(value);
+            }
+        }
+        // `localStorage` spools stay plain JSON
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            client.store.codec.format = client.payload_format;
+        }
+        #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+        if let Some(store) = &client.spool_key {
+            let key = store
+                .spool_key()
+                .map_err(|e| format!("failed to get spool key: {}", e))?;
+            client.store.codec.key = Some(key);
+        }
+        if client.consent == ConsentState::Denied {
+            // Events spooled before consent was denied must not be sent
+            client.store.replace(&client.events);
+        } else {
+            client.last_compaction = client
+                .store
+                .compact()
+                .map_err(|e| format!("failed to compact storage: {}", e))?;
+            client
+                .metrics
+                .add_corrupted(client.last_compaction.discarded);
+            let (events, read) = client
+                .store
+                .load()
+                .map_err(|e| format!("failed to read spool: {}", e))?;
+            for event in events {
+                client.push_event(event);
+            }
+            if read {
+                client.store.replace(&client.events);
+            }
+            client.prune_queue();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(dir) = client.crash_dir.clone() {
+            for (path, event) in crash::saved(&dir) {
+                client.queue_crash(path, event);
+            }
+        }
+        Ok(client)
+    }
+}
+
+cfg_http! {
+    /// Number of reported flag evaluations remembered; past it they are
+    /// forgotten and each is reported once more
+    const MAX_FLAG_EXPOSURES: usize = 10_000;
+
+    const ANONYMOUS_ID_KEY: &str = "anonymous_id";
+    const USER_ID_KEY: &str = "user_id";
+    const CONSENT_KEY: &str = "consent";
+    const REMOTE_CONFIG_KEY: &str = "remote_config";
+    const REMOTE_CONFIG_FETCHED_AT_KEY: &str = "remote_config_fetched_at";
+    const EXPERIMENTS_KEY: &str = "experiments";
+}
+
+/// Check that a backend URL is an absolute http(s) URL with a host
+#[cfg(feature = "http")]
+fn validate_endpoint(endpoint: &str) -> Result<(), String> {
+    let url = reqwest::Url::parse(endpoint)
+        .map_err(|e| format!("invalid backend_url {:?}: {}", endpoint, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "invalid backend_url {:?}: scheme must be http or https",
+            endpoint
+        ));
+    }
+    if url.host_str().is_none() {
+        return Err(format!("invalid backend_url {:?}: missing host", endpoint));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "http")]
+impl GameEventsIOClient {
+    /// Create a new GameEventsIO client
+    ///
+    /// Panics if the API key is not a valid HTTP header value, e.g. contains
+    /// a newline; the builder reports that as an error instead.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        GameEventsIOClientBuilder::default()
+            .api_key(api_key)
+            .build()
+            .expect("Failed to create GameEventsIOClient")
+    }
+
+    /// Create a client configured by `GAME_EVENTS_IO_*` environment variables
+    ///
+    /// See [`GameEventsIOClientBuilder::env_overrides`]; fails if
+    /// `GAME_EVENTS_IO_API_KEY` is not set.
+    pub fn from_env() -> Result<Self, GameEventsIOClientBuilderError> {
+        GameEventsIOClientBuilder::default()
+            .env_overrides()?
+            .build()
+    }
+
+    /// Create a client that sends events to a custom (e.g. self-hosted) endpoint
+    pub fn with_endpoint(
+        api_key: impl Into<String>,
+        endpoint: impl Into<String>,
+    ) -> Result<Self, GameEventsIOClientBuilderError> {
+        GameEventsIOClientBuilder::default()
+            .api_key(api_key)
+            .backend_url(endpoint)
+            .build()
+    }
+
+    /// Get the overflow policy applied once the buffer is full
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+
+    /// Get the URL events are sent to
+    pub fn backend_url(&self) -> &str {
+        &self.backend_url
+    }
+
+    /// Get the data residency region, if one was set
+    pub fn region(&self) -> Option<&Region> {
+        self.region.as_ref()
+    }
+
+    /// Log an event (adds to buffer and, if configured, to the spool)
+    ///
+    /// A spool write failure does not lose the event; it stays buffered in
+    /// memory and is written out again after the next successful flush.
+    ///
+    /// Super properties are merged into the event's properties first; a
+    /// property the event already has keeps its own value.
+    ///
+    /// When the buffer is full the configured [`OverflowPolicy`] decides which
+    /// event is dropped.
+    ///
+    /// Events over their configured [`RateLimit`] are dropped, as are events
+    /// vetoed by a [`ClientPlugin`].
+    ///
+    /// With a `sample_rate` below 1.0 each event is kept with that probability
+    /// and kept events record the rate in their payload. The remote
+    /// configuration can override the rate, per event name too.
+    ///
+    /// Events disabled with [`disable_event`](Self::disable_event) or by the
+    /// remote configuration are dropped and counted by
+    /// [`suppressed_events`](Self::suppressed_events).
+    ///
+    /// Once the queue reaches a threshold of the client's [`BatchPolicy`], or
+    /// the event has [`EventPriority::Immediate`], the queue is flushed before
+    /// this returns. After a failed automatic flush the next one waits for the
+    /// retry policy's `max_backoff`.
+    pub fn log_event(&mut self, event: GameEventsIOEvent) {
+        self.drain_producers();
+        #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+        let queued = self.enqueue(event, false);
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(Some(immediate)) = queued {
+            self.auto_flush(immediate);
+        }
+    }
+
+    /// Log an event without ever flushing or evicting, for hot paths such as
+    /// the render thread
+    ///
+    /// Unlike [`log_event`](Self::log_event), a full queue is reported
+    /// instead of resolved by the overflow policy, and the batch policy is
+    /// left to a worker or an explicit flush. Events dropped by sampling, rate
+    /// limits or plugins count as logged.
+    ///
+    /// Nothing touches the disk or the network: the event is written to the
+    /// spool by the next `log_event` or flush, and a stale remote
+    /// configuration is refreshed from there too.
+    pub fn try_log_event(&mut self, event: GameEventsIOEvent) -> Result<(), TryLogError> {
+        self.enqueue(event, true).map(|_| ())
+    }
+
+    /// A new event named `event` with a fresh id and the current time, made
+    /// from a sent one where the [event pool](GameEventsIOClientBuilder::event_pool)
+    /// has any, so its strings and property maps need no new allocations
+    pub fn pooled_event(&mut self, event: impl Into<Cow<'static, str>>) -> GameEventsIOEvent {
+        let mut pooled = self.event_pool.take();
+        pooled.event = event.into();
+        pooled.time = clock::unix_secs(self.clock.now());
+        pooled
+    }
+
+    /// Get a cheap, cloneable sender game systems can log events through
+    /// without access to the client
+    pub fn producer(&self) -> GameEventsIOProducer {
+        self.producers.producer()
+    }
+
+    /// Queue the events sent through [`producer`](Self::producer)s so far
+    ///
+    /// Every `log_event` and flush does this first, so call it only to see
+    /// producers' events in the queue sooner. Returns the number received.
+    pub fn drain_producers(&mut self) -> usize {
+        let events = self.producers.drain();
+        let received = events.len();
+        for event in events {
+            let _ = self.enqueue(event, false);
+        }
+        received
+    }
+
+    /// Run an event through consent, sampling, enrichment and the plugins and
+    /// queue it
+    ///
+    /// Returns whether a queued event has [`EventPriority::Immediate`], or
+    /// `None` if it was discarded along the way.
+    ///
+    /// `try_log` reports a full queue instead of applying the overflow
+    /// policy, and leaves storing the event and refreshing the remote
+    /// configuration to a later call.
+    fn enqueue(
+        &mut self,
+        mut event: GameEventsIOEvent,
+        try_log: bool,
+    ) -> Result<Option<bool>, TryLogError> {
+        if !try_log {
+            self.store.store_deferred(&self.events);
+            self.revalidate_remote_config();
+        }
+        if self.consent == ConsentState::Denied {
+            return Err(TryLogError::Disabled);
+        }
+        if self.is_event_disabled(&event.event) {
+            *self
+                .suppressed_events
+                .entry(event.event.to_string())
+                .or_default() += 1;
+            return Err(TryLogError::Disabled);
+        }
+        if try_log && self.is_full() {
+            return Err(TryLogError::QueueFull);
+        }
+        if !self.within_rate_limit(&event.event) {
+            *self
+                .rate_limited_events
+                .entry(event.event.to_string())
+                .or_default() += 1;
+            return Ok(None);
+        }
+        let sample_rate = self
+            .remote_settings
+            .sample_rate(&event.event)
+            .unwrap_or(self.sample_rate);
+        if sample_rate < 1.0 {
+            if rand::random_range(0.0..1.0) >= sample_rate {
+                self.metrics.add_sampled_out();
+                return Ok(None);
+            }
+            event.sample_rate = Some(sample_rate);
+        }
+        if event.event_id.is_empty() {
+            event.event_id = Uuid::new_v4().to_string();
+        }
+        if event.anonymous_id.is_none() {
+            event.anonymous_id = Some(self.anonymous_id.clone());
+        }
+        if event.environment.is_none() {
+            event.environment = Some(self.environment.to_string());
+        }
+        if let (Some(offset), None) = (self.clock_offset, event.corrected_time) {
+            event.corrected_time = Some(event.time.saturating_add_signed(offset));
+        }
+        if event.user_id.is_empty() {
+            event.user_id = self
+                .identified_user_id
+                .clone()
+                .unwrap_or_else(|| self.anonymous_id.clone());
+        }
+        #[cfg(feature = "schema")]
+        if cfg!(debug_assertions) {
+            if let Some(registry) = &self.schema_registry {
+                if let Err(errors) = registry.validate(&event) {
+                    match registry.policy() {
+                        SchemaViolationPolicy::Reject => return Ok(None),
+                        SchemaViolationPolicy::Flag => {
+                            event
+                                .event_properties
+                                .insert(SCHEMA_ERRORS_PROPERTY.into(), errors.into());
+                        }
+                    }
+                }
+            }
+        }
+        for (key, value) in &self.super_properties {
+            event
+                .event_properties
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+        for plugin in &self.plugins {
+            if !plugin.before_enqueue(&mut event) {
+                return Ok(None);
+            }
+        }
+        if let Some(scrubber) = &self.scrubber {
+            scrubber.scrub(&mut event.event_properties);
+            scrubber.scrub(&mut event.user_properties);
+        }
+        let immediate = event.priority == EventPriority::Immediate;
+        if !self.push_event(event) {
+            return Ok(None);
+        }
+        match self.events.back() {
+            Some(_) if try_log => self.store.defer(),
+            Some(event) => {
+                self.store.push(event);
+            }
+            None => {}
+        }
+        Ok(Some(immediate))
+    }
+
+    /// Queue a crash saved by the panic hook
+    ///
+    /// Crashes skip sampling, rate limits, disabled events and plugins. The
+    /// file is deleted once the event is stored, or else once it is sent;
+    /// until then it is read again by the next client.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn queue_crash(&mut self, path: PathBuf, mut event: GameEventsIOEvent) {
+        if self.consent == ConsentState::Denied {
+            let _ = std::fs::remove_file(&path);
+            return;
+        }
+        if event.anonymous_id.is_none() {
+            event.anonymous_id = Some(self.anonymous_id.clone());
+        }
+        if event.environment.is_none() {
+            event.environment = Some(self.environment.to_string());
+        }
+        if let Some(scrubber) = &self.scrubber {
+            scrubber.scrub(&mut event.event_properties);
+            scrubber.scrub(&mut event.user_properties);
+        }
+        if !self.push_event(event) {
+            return;
+        }
+        let Some(event) = self.events.back() else {
+            return;
+        };
+        if self.store.push(event) {
+            let _ = std::fs::remove_file(&path);
+        } else {
+            self.unsent_crashes.push((event.event_id.clone(), path));
+        }
+    }
+
+    /// Delete the files of the saved crashes among `sent`
+    #[cfg(not(target_arch = "wasm32"))]
+    fn forget_sent_crashes(&mut self, sent: &[GameEventsIOEvent]) {
+        self.unsent_crashes.retain(|(id, path)| {
+            let sent = sent.iter().any(|event| event.event_id == *id);
+            if sent {
+                let _ = std::fs::remove_file(path);
+            }
+            !sent
+        });
+    }
+
+    /// Flush if `immediate` or the batch policy says so, unless a recent
+    /// automatic flush failed
+    #[cfg(not(target_arch = "wasm32"))]
+    fn auto_flush(&mut self, immediate: bool) {
+        let now = Instant::now();
+        if !(immediate || self.flush_due())
+            || self
+                .auto_flush_paused_until
+                .is_some_and(|until| now < until)
+        {
+            return;
+        }
+        self.auto_flush_paused_until = match self.flush() {
+            Ok(_) => None,
+            Err(_) => Some(Instant::now() + self.retry_policy.max_backoff),
+        };
+    }
+
+    /// Whether the queue has reached a threshold of the [`BatchPolicy`]
+    pub fn flush_due(&self) -> bool {
+        let now = clock::unix_secs(self.clock.now());
+        let max_age = self.remote_settings.flush_interval.map(Duration::from_secs);
+        let policy = BatchPolicy {
+            max_age: max_age.or(self.batch_policy.max_age),
+            ..self.batch_policy.clone()
+        };
+        policy.is_due(
+            self.events.len(),
+            self.pending_bytes,
+            self.events.front().map(|event| event.time),
+            now,
+        )
+    }
+
+    /// Size of an event's JSON, or 0 when the batch policy does not need it
+    fn tracked_size(&self, event: &GameEventsIOEvent) -> usize {
+        if self.batch_policy.max_bytes.is_none() && self.max_retained_bytes.is_none() {
+            return 0;
+        }
+        chunk::json_len(event)
+    }
+
+    /// Recount `pending_bytes` after the queue was changed in bulk
+    fn recount_pending_bytes(&mut self) {
+        self.pending_bytes = self
+            .events
+            .iter()
+            .map(|event| self.tracked_size(event))
+            .sum();
+    }
+
+    /// Drop events past `event_ttl`, then the oldest events over
+    /// `max_retained_bytes`, and rewrite the spool if any went
+    fn prune_queue(&mut self) {
+        let before = self.events.len();
+        if let Some(ttl) = self.event_ttl {
+            let now = clock::unix_secs(self.clock.now());
+            self.events
+                .retain(|event| now.saturating_sub(event.time) <= ttl.as_secs());
+            let expired = before - self.events.len();
+            if expired > 0 {
+                self.metrics.add_expired(expired);
+                self.recount_pending_bytes();
+            }
+        }
+        if let Some(max) = self.max_retained_bytes {
+            while self.pending_bytes > max {
+                let Some(dropped) = self.events.pop_front() else {
+                    break;
+                };
+                self.pending_bytes -= self.tracked_size(&dropped);
+                self.metrics.add_dropped();
+            }
+        }
+        if self.events.len() != before {
+            self.metrics.set_queue_depth(self.events.len());
+            self.store.replace(&self.events);
+        }
+    }
+
+    /// Take a token from the event name's rate limiter, if it has one
+    fn within_rate_limit(&mut self, name: &str) -> bool {
+        let Some(limit) = self.rate_limits.get(name) else {
+            return true;
+        };
+        let now = Instant::now();
+        self.rate_limiters
+            .entry(name.to_string())
+            .or_insert_with(|| rate_limit::Bucket::new(limit, now))
+            .try_acquire(limit, now)
+    }
+
+    /// Add an event to the buffer, applying the overflow policy
+    ///
+    /// Returns `false` if the new event itself was dropped.
+    fn push_event(&mut self, event: GameEventsIOEvent) -> bool {
+        if self.is_full() {
+            self.metrics.add_dropped();
+            match self.overflow_policy {
+                OverflowPolicy::DropOldest => {
+                    if let Some(dropped) = self.events.pop_front() {
+                        self.pending_bytes -= self.tracked_size(&dropped);
+                        self.store.desync();
+                    }
+                }
+                OverflowPolicy::DropNewest | OverflowPolicy::Block => return false,
+            }
+        }
+        self.pending_bytes += self.tracked_size(&event);
+        self.events.push_back(event);
+        self.metrics.add_enqueued();
+        self.metrics.set_queue_depth(self.events.len());
+        true
+    }
+
+    /// Save panics in `crash_dir` so the next launch can report them
+    ///
+    /// Chains to the previously installed hook. Returns `None` if the client
+    /// has no `crash_dir`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn install_panic_hook(&self, session: &GameEventsIOSession) -> Option<CrashReporter> {
+        let dir = self.crash_dir.clone()?;
+        Some(CrashReporter::install(dir, session))
+    }
+
+    /// Get the current tracking consent
+    pub fn consent(&self) -> ConsentState {
+        self.consent
+    }
+
+    /// Record the player's tracking consent (persisted in `state_dir`)
+    ///
+    /// While consent is pending events are buffered but flushes send nothing.
+    /// Denying consent purges every buffered and spooled event and drops
+    /// events logged afterwards.
+    pub fn set_consent(&mut self, consent: ConsentState) {
+        self.consent = consent;
+        self.store.write_state(CONSENT_KEY, consent.as_str());
+        if consent == ConsentState::Denied {
+            self.events.clear();
+            self.pending_bytes = 0;
+            self.metrics.set_queue_depth(0);
+            self.store.replace(&self.events);
+        }
+    }
+
+    /// Shorthand for granting (`true`) or denying (`false`) tracking consent
+    pub fn set_tracking_enabled(&mut self, enabled: bool) {
+        self.set_consent(if enabled {
+            ConsentState::Granted
+        } else {
+            ConsentState::Denied
+        });
+    }
+
+    /// Get the SDK-generated anonymous id of this install
+    ///
+    /// It is persisted in `state_dir`, if configured, so it survives restarts.
+    pub fn anonymous_id(&self) -> &str {
+        &self.anonymous_id
+    }
+
+    /// Get the user id set by [`identify`](Self::identify), if any
+    pub fn identified_user_id(&self) -> Option<&str> {
+        self.identified_user_id.as_deref()
+    }
+
+    /// Associate this install's anonymous id with an authenticated user
+    ///
+    /// Logs an `identify` event so the backend can stitch earlier anonymous
+    /// events to `user_id`, and uses `user_id` for later events logged
+    /// without one.
+    pub fn identify(&mut self, user_id: impl Into<String>) {
+        let user_id = user_id.into();
+        self.store.write_state(USER_ID_KEY, &user_id);
+        self.identified_user_id = Some(user_id.clone());
+
+        let event = GameEventsIOEventBuilder::default()
+            .event("identify")
+            .user_id(user_id)
+            .timestamp(self.clock.now())
+            .build()
+            .expect("Failed to build event");
+        self.log_event(event);
+    }
+
+    /// Tell the backend that `previous_id` and `user_id` are the same user
+    pub fn alias(&mut self, previous_id: impl Into<String>, user_id: impl Into<String>) {
+        let mut props = HashMap::new();
+        props.insert(
+            "previous_id".into(),
+            serde_json::Value::String(previous_id.into()),
+        );
+        let event = GameEventsIOEventBuilder::default()
+            .event("alias")
+            .user_id(user_id)
+            .event_properties(props)
+            .timestamp(self.clock.now())
+            .build()
+            .expect("Failed to build event");
+        self.log_event(event);
+    }
+
+    /// Ask the backend to delete all data stored for `user_id`
+    ///
+    /// Events for that user still waiting in the buffer and the spool are
+    /// purged first, so they are never sent even if the request fails.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn request_user_deletion(
+        &mut self,
+        user_id: &str,
+    ) -> Result<DeletionReceipt, GameEventsIOError> {
+        self.purge_user_events(user_id);
+        let body = serde_json::to_vec(&deletion::DeletionRequest { user_id })?;
+        let response = self.post_blocking(self.deletion_url(), self.base_headers(), body, None)?;
+        Ok(serde_json::from_str(&response)?)
+    }
+
+    /// Ask the backend to delete all data stored for `user_id` (async)
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    pub async fn request_user_deletion_async(
+        &mut self,
+        user_id: &str,
+    ) -> Result<DeletionReceipt, GameEventsIOError> {
+        self.purge_user_events(user_id);
+        let body = serde_json::to_vec(&deletion::DeletionRequest { user_id })?;
+        let response = self
+            .post_async(self.deletion_url(), self.base_headers(), body)
+            .await?;
+        Ok(serde_json::from_str(&response)?)
+    }
+
+    /// Check that the collector is reachable and accepts the API key, e.g.
+    /// before a tournament
+    ///
+    /// The probe goes through the same transport, proxy and TLS settings as
+    /// flushes, but sends no events.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn health_check(&self) -> HealthStatus {
+        let started = Instant::now();
+        let result =
+            self.post_blocking(self.health_url(), self.base_headers(), b"{}".to_vec(), None);
+        HealthStatus::new(result, started.elapsed())
+    }
+
+    /// Check that the collector is reachable and accepts the API key (async)
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    pub async fn health_check_async(&self) -> HealthStatus {
+        let started = Instant::now();
+        let result = self
+            .post_async(self.health_url(), self.base_headers(), b"{}".to_vec())
+            .await;
+        HealthStatus::new(result, started.elapsed())
+    }
+
+    /// Fetch the remote configuration now, wait for it and apply it
+    ///
+    /// Not needed normally: stale documents are refreshed in the background
+    /// as events are logged and flushed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn refresh_remote_config(&mut self) -> Result<(), GameEventsIOError> {
+        let Some(source) = self.remote_config.clone() else {
+            return Ok(());
+        };
+        self.remote_config_refresh_at = Some(self.clock.now() + source.ttl);
+        let document =
+            self.post_blocking(source.url(), self.base_headers(), b"{}".to_vec(), None)?;
+        self.apply_remote_config(&source, document)
+    }
+
+    /// Fetch the remote configuration now and apply it (async)
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    pub async fn refresh_remote_config_async(&mut self) -> Result<(), GameEventsIOError> {
+        let Some(source) = self.remote_config.clone() else {
+            return Ok(());
+        };
+        self.remote_config_refresh_at = Some(self.clock.now() + source.ttl);
+        let document = self
+            .post_async(source.url(), self.base_headers(), b"{}".to_vec())
+            .await?;
+        self.apply_remote_config(&source, document)
+    }
+
+    /// Use a fetched document and cache it for the next client
+    fn apply_remote_config(
+        &mut self,
+        source: &RemoteConfigSource,
+        document: String,
+    ) -> Result<(), GameEventsIOError> {
+        self.remote_settings = source.parse(&document)?;
+        let now = self.clock.now();
+        self.remote_config_fetched_at = Some(now);
+        let fetched_at = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+        self.store.write_state(REMOTE_CONFIG_KEY, &document);
+        self.store.write_state(
+            REMOTE_CONFIG_FETCHED_AT_KEY,
+            &fetched_at.as_secs().to_string(),
+        );
+        Ok(())
+    }
+
+    /// Apply a finished background fetch, drop settings past `max_stale`
+    /// and start a fetch if the document in use is stale
+    fn revalidate_remote_config(&mut self) {
+        let Some(ttl) = self.remote_config.as_ref().map(|source| source.ttl) else {
+            return;
+        };
+        let finished = self
+            .remote_config_fetch
+            .as_ref()
+            .and_then(|fetch| fetch.lock().unwrap().take());
+        if let Some(result) = finished {
+            self.remote_config_fetch = None;
+            // A failed refresh keeps the settings in use
+            if let (Ok(document), Some(source)) = (result, self.remote_config.clone()) {
+                let _ = self.apply_remote_config(&source, document);
+            }
+        }
+        let now = self.clock.now();
+        if let (Some(fetched_at), Some(source)) =
+            (self.remote_config_fetched_at, &self.remote_config)
+        {
+            let age = now.duration_since(fetched_at).unwrap_or_default();
+            if source.is_expired(age) {
+                self.remote_settings = RemoteSettings::default();
+                self.remote_config_fetched_at = None;
+            }
+        }
+        let due = self.remote_config_refresh_at.is_none_or(|at| now >= at);
+        if due && self.remote_config_fetch.is_none() {
+            self.remote_config_refresh_at = Some(now + ttl);
+            self.start_remote_config_fetch();
+        }
+    }
+
+    /// Fetch the remote configuration without waiting for it
+    fn start_remote_config_fetch(&mut self) {
+        let Some(source) = &self.remote_config else {
+            return;
+        };
+        let request = self.request(source.url(), self.base_headers(), b"{}".to_vec(), None);
+        let fetch = remote_config::PendingFetch::default();
+        self.remote_config_fetch = Some(fetch.clone());
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let transport = self.transport.clone();
+            std::thread::spawn(move || {
+                let result = transport.post(request).and_then(|r| r.into_result());
+                *fetch.lock().unwrap() = Some(result.map(|received| received.body));
+            });
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let transport = self.async_transport.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = transport.post(request).await.and_then(|r| r.into_result());
+                *fetch.lock().unwrap() = Some(result.map(|received| received.body));
+            });
+        }
+    }
+
+    /// Get the ids of the cohorts `user_id` belongs to, e.g. "lapsed_spenders"
+    ///
+    /// Memberships are fetched from `cohorts_url` and reused for
+    /// `cohort_ttl`. When a refresh fails the expired list is returned, if
+    /// there is one.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn cohorts(&mut self, user_id: &str) -> Result<Vec<String>, GameEventsIOError> {
+        if let Some(cohorts) = self.fresh_cohorts(user_id) {
+            return Ok(cohorts);
+        }
+        let body = serde_json::to_vec(&cohort::CohortRequest { user_id })?;
+        let fetched = self
+            .post_blocking(self.cohorts_url(), self.base_headers(), body, None)
+            .and_then(|response| Ok(serde_json::from_str(&response)?));
+        self.store_cohorts(user_id, fetched)
+    }
+
+    /// Get the ids of the cohorts `user_id` belongs to (async)
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    pub async fn cohorts_async(&mut self, user_id: &str) -> Result<Vec<String>, GameEventsIOError> {
+        if let Some(cohorts) = self.fresh_cohorts(user_id) {
+            return Ok(cohorts);
+        }
+        let body = serde_json::to_vec(&cohort::CohortRequest { user_id })?;
+        let fetched = match self
+            .post_async(self.cohorts_url(), self.base_headers(), body)
+            .await
+        {
+            Ok(response) => serde_json::from_str(&response).map_err(Into::into),
+            Err(e) => Err(e),
+        };
+        self.store_cohorts(user_id, fetched)
+    }
+
+    /// Look up the cohorts of the session's user and store them in its
+    /// [`COHORTS_PROPERTY`] user property, so live-ops can target them
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn attach_cohorts(
+        &mut self,
+        session: &mut GameEventsIOSession,
+    ) -> Result<Vec<String>, GameEventsIOError> {
+        let cohorts = self.cohorts(session.user_id())?;
+        session.set_user_property(COHORTS_PROPERTY, cohorts.clone().into());
+        Ok(cohorts)
+    }
+
+    /// Cached cohorts of `user_id` younger than `cohort_ttl`
+    fn fresh_cohorts(&self, user_id: &str) -> Option<Vec<String>> {
+        let (fetched_at, cohorts) = self.cohort_cache.get(user_id)?;
+        (fetched_at.elapsed() < self.cohort_ttl).then(|| cohorts.clone())
+    }
+
+    /// Cache fetched cohorts; when the fetch failed, fall back to expired ones
+    fn store_cohorts(
+        &mut self,
+        user_id: &str,
+        fetched: Result<cohort::CohortResponse, GameEventsIOError>,
+    ) -> Result<Vec<String>, GameEventsIOError> {
+        match fetched {
+            Ok(response) => {
+                self.cohort_cache.insert(
+                    user_id.to_string(),
+                    (Instant::now(), response.cohorts.clone()),
+                );
+                Ok(response.cohorts)
+            }
+            Err(e) => match self.cohort_cache.get(user_id) {
+                Some((_, cohorts)) => Ok(cohorts.clone()),
+                None => Err(e),
+            },
+        }
+    }
+
+    fn cohorts_url(&self) -> &str {
+        self.cohorts_url
+            .as_deref()
+            .expect("cohorts_url is set when the client is built")
+    }
+
+    /// Whether feature flag `flag` is on in `context`
+    ///
+    /// Flags come with the remote configuration, so they are fetched and
+    /// cached with it; a flag it does not define is off. The first evaluation
+    /// for a user, and any that changes the value, logs a
+    /// [`FLAG_EXPOSURE_EVENT`] so outcomes can be tied to the flag. The
+    /// event is queued like [`try_log_event`](Self::try_log_event) does, so
+    /// a check never flushes or waits on the disk.
+    pub fn is_enabled(&mut self, flag: &str, context: &FlagContext) -> bool {
+        self.revalidate_remote_config();
+        let Some(rule) = self.remote_settings.flags.get(flag) else {
+            return false;
+        };
+        let user_id = context
+            .user_id
+            .clone()
+            .or_else(|| self.identified_user_id.clone())
+            .unwrap_or_else(|| self.anonymous_id.clone());
+        let enabled = rule.evaluate(flag, &user_id, context);
+        let exposure = (flag.to_string(), user_id.clone(), enabled);
+        if !self.flag_exposures.contains(&exposure) {
+            if self.flag_exposures.len() >= MAX_FLAG_EXPOSURES {
+                self.flag_exposures.clear();
+            }
+            self.flag_exposures.insert(exposure);
+            let mut props = HashMap::new();
+            props.insert("flag".into(), flag.into());
+            props.insert("enabled".into(), enabled.into());
+            let event = GameEventsIOEventBuilder::default()
+                .event(FLAG_EXPOSURE_EVENT)
+                .user_id(user_id)
+                .event_properties(props)
+                .timestamp(self.clock.now())
+                .build()
+                .expect("Failed to build event");
+            // Checking a flag must not wait on a flush
+            let _ = self.enqueue(event, true);
+        }
+        enabled
+    }
+
+    /// Get the variant of experiment `experiment_key` that `user_id` is in,
+    /// `None` if the experiment is not running
+    ///
+    /// The experiment's definition is fetched from `experiments_url` the
+    /// first time it is asked for, then reused; with a `state_dir` it is also
+    /// kept for runs that cannot reach the backend. Assignment happens on the
+    /// client and is deterministic, so a user always gets the same variant.
+    /// The variant is attached to events from now on as the super property
+    /// `experiment_<experiment_key>`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn get_variant(
+        &mut self,
+        experiment_key: &str,
+        user_id: &str,
+    ) -> Result<Option<String>, GameEventsIOError> {
+        if !self.fetched_experiments.contains(experiment_key) {
+            let body = serde_json::to_vec(&experiment::ExperimentRequest { experiment_key })?;
+            let fetched = self
+                .post_blocking(self.experiments_url(), self.base_headers(), body, None)
+                .and_then(|response| Ok(serde_json::from_str(&response)?));
+            self.store_experiment(experiment_key, fetched)?;
+        }
+        Ok(self.assign_variant(experiment_key, user_id))
+    }
+
+    /// Get the variant of an experiment that `user_id` is in (async)
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    pub async fn get_variant_async(
+        &mut self,
+        experiment_key: &str,
+        user_id: &str,
+    ) -> Result<Option<String>, GameEventsIOError> {
+        if !self.fetched_experiments.contains(experiment_key) {
+            let body = serde_json::to_vec(&experiment::ExperimentRequest { experiment_key })?;
+            let fetched = match self
+                .post_async(self.experiments_url(), self.base_headers(), body)
+                .await
+            {
+                Ok(response) => serde_json::from_str(&response).map_err(Into::into),
+                Err(e) => Err(e),
+            };
+            self.store_experiment(experiment_key, fetched)?;
+        }
+        Ok(self.assign_variant(experiment_key, user_id))
+    }
+
+    /// Keep a fetched experiment definition; when the fetch failed, fall back
+    /// to the one stored by an earlier run
+    fn store_experiment(
+        &mut self,
+        key: &str,
+        fetched: Result<experiment::Experiment, GameEventsIOError>,
+    ) -> Result<(), GameEventsIOError> {
+        match fetched {
+            Ok(definition) => {
+                self.experiments.insert(key.to_string(), definition);
+                self.fetched_experiments.insert(key.to_string());
+                if self.store.backend.is_some() {
+                    let experiments = serde_json::to_string(&self.experiments)?;
+                    self.store.write_state(EXPERIMENTS_KEY, &experiments);
+                }
+                Ok(())
+            }
+            Err(_) if self.experiments.contains_key(key) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Assign `user_id` a variant and attach it to future events
+    fn assign_variant(&mut self, key: &str, user_id: &str) -> Option<String> {
+        let property = format!("{}{}", EXPERIMENT_PROPERTY_PREFIX, key);
+        let variant = self.experiments.get(key)?.assign(key, user_id);
+        match variant {
+            Some(variant) => {
+                self.super_properties
+                    .insert(property.into(), variant.to_string().into());
+            }
+            None => {
+                self.super_properties.remove(property.as_str());
+            }
+        }
+        variant.map(String::from)
+    }
+
+    fn experiments_url(&self) -> &str {
+        self.experiments_url
+            .as_deref()
+            .expect("experiments_url is set when the client is built")
+    }
+
+    /// Drop buffered and spooled events belonging to `user_id`
+    fn purge_user_events(&mut self, user_id: &str) {
+        let before = self.events.len();
+        self.events.retain(|event| event.user_id != user_id);
+        if self.events.len() != before {
+            self.recount_pending_bytes();
+            self.metrics.set_queue_depth(self.events.len());
+            self.store.replace(&self.events);
+        }
+    }
+
+    fn health_url(&self) -> &str {
+        self.health_url
+            .as_deref()
+            .expect("health_url is set when the client is built")
+    }
+
+    fn deletion_url(&self) -> &str {
+        self.deletion_url
+            .as_deref()
+            .expect("deletion_url is set when the client is built")
+    }
+
+    /// Add or update a super property merged into every event logged from now on
+    pub fn set_super_property(
+        &mut self,
+        key: impl Into<Cow<'static, str>>,
+        value: serde_json::Value,
+    ) {
+        self.super_properties.insert(key.into(), value);
+    }
+
+    /// Stop merging a super property into new events
+    pub fn remove_super_property(&mut self, key: &str) -> Option<serde_json::Value> {
+        self.super_properties.remove(key)
+    }
+
+    /// Get all super properties
+    pub fn super_properties(&self) -> &Properties {
+        &self.super_properties
+    }
+
+    /// Whether the buffer has reached `max_pending_events`
+    pub fn is_full(&self) -> bool {
+        self.max_pending_events
+            .is_some_and(|max| self.events.len() >= max)
+    }
+
+    /// Send all buffered events to the backend
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn flush(&mut self) -> Result<FlushReport, GameEventsIOError> {
+        self.flush_batch(usize::MAX)
+    }
+
+    /// Send events in batches (useful for large event counts)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn flush_batch(&mut self, batch_size: usize) -> Result<FlushReport, GameEventsIOError> {
+        self.flush_batch_before(batch_size, None)
+    }
+
+    /// Flush all buffered events, giving up once `deadline` has elapsed
+    ///
+    /// Call this before the game exits. Retries stop early rather than
+    /// overrun the deadline; events that could not be sent stay buffered (and
+    /// in the spool, if configured).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn shutdown(&mut self, deadline: Duration) -> Result<FlushReport, GameEventsIOError> {
+        self.flush_batch_before(usize::MAX, Some(Instant::now() + deadline))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn flush_batch_before(
+        &mut self,
+        batch_size: usize,
+        deadline: Option<Instant>,
+    ) -> Result<FlushReport, GameEventsIOError> {
+        let result = self.send_batches_before(batch_size, deadline);
+        self.notify_flushed(&result);
+        result
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn send_batches_before(
+        &mut self,
+        batch_size: usize,
+        deadline: Option<Instant>,
+    ) -> Result<FlushReport, GameEventsIOError> {
+        let mut events_to_send = match self.start_flush(batch_size) {
+            ControlFlow::Continue(events) => events,
+            ControlFlow::Break(result) => return result,
+        };
+        let mut report = FlushReport::default();
+        while !events_to_send.is_empty() {
+            let (chunk, bytes) = chunk::take_chunk(&mut events_to_send, self.max_request_bytes);
+            let mut retries = 0;
+            let result = loop {
+                let timeout = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+                let started = Instant::now();
+                let sent = self.send_batch(&chunk, timeout);
+                report.latency += started.elapsed();
+                let sent = self.record_sent(sent);
+                match sent {
+                    Err(e) => match self.next_retry(&e, retries, deadline) {
+                        Some(backoff) => {
+                            retries += 1;
+                            std::thread::sleep(backoff);
+                        }
+                        None => break Err(e),
+                    },
+                    result => break result,
+                }
+            };
+            self.finish_chunk_send(
+                &mut report,
+                chunk,
+                bytes,
+                &mut events_to_send,
+                retries,
+                result,
+            )?;
+        }
+        Ok(self.finish_report(report))
+    }
+
+    /// Send archived newline-delimited JSON events again, e.g. after an
+    /// ingestion outage
+    ///
+    /// Events keep their original timestamps. They already went through
+    /// [`log_event`](Self::log_event) when first logged, so sampling, rate
+    /// limits, plugins and scrubbing are not applied again. They are queued
+    /// (and spooled) and flushed every `batch_size` events as the input is
+    /// read, then once more at the end; lines that fail to parse are skipped.
+    ///
+    /// Returns the number of events queued. A failed flush stops the replay,
+    /// leaving the events not yet sent in the queue.
+    #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+    pub fn replay_from(
+        &mut self,
+        reader: impl BufRead,
+        batch_size: usize,
+    ) -> Result<usize, GameEventsIOError> {
+        if self.consent == ConsentState::Denied {
+            return Ok(0);
+        }
+        let batch_size = batch_size.max(1);
+        let mut replayed = 0;
+        self.store.store_deferred(&self.events);
+        for line in reader.lines() {
+            let Ok(event) = serde_json::from_str::<GameEventsIOEvent>(&line?) else {
+                continue;
+            };
+            if !self.push_event(event) {
+                continue;
+            }
+            replayed += 1;
+            if let Some(event) = self.events.back() {
+                self.store.push(event);
+            }
+            if self.events.len() >= batch_size {
+                self.flush_batch(batch_size)?;
+            }
+        }
+        self.flush()?;
+        Ok(replayed)
+    }
+
+    /// [`replay_from`](Self::replay_from) a file, such as a spool or a
+    /// [`FileSink`] export in the native format
+    #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+    pub fn replay_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        batch_size: usize,
+    ) -> Result<usize, GameEventsIOError> {
+        let file = std::fs::File::open(path)?;
+        self.replay_from(std::io::BufReader::new(file), batch_size)
+    }
+
+    /// Send all buffered events to the backend without blocking the current thread
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    pub async fn flush_async(&mut self) -> Result<FlushReport, GameEventsIOError> {
+        self.flush_batch_async(usize::MAX).await
+    }
+
+    /// Send events in batches without blocking the current thread
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    pub async fn flush_batch_async(
+        &mut self,
+        batch_size: usize,
+    ) -> Result<FlushReport, GameEventsIOError> {
+        let result = self.send_batches_async(batch_size, None).await;
+        self.notify_flushed(&result);
+        result
+    }
+
+    /// Flush all buffered events without blocking, giving up once `deadline`
+    /// has elapsed, like [`shutdown`](Self::shutdown)
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    pub async fn shutdown_async(
+        &mut self,
+        deadline: Duration,
+    ) -> Result<FlushReport, GameEventsIOError> {
+        let deadline = Instant::now() + deadline;
+        let result = self.send_batches_async(usize::MAX, Some(deadline)).await;
+        self.notify_flushed(&result);
+        result
+    }
+
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    async fn send_batches_async(
+        &mut self,
+        batch_size: usize,
+        deadline: Option<Instant>,
+    ) -> Result<FlushReport, GameEventsIOError> {
+        let mut events_to_send = match self.start_flush(batch_size) {
+            ControlFlow::Continue(events) => events,
+            ControlFlow::Break(result) => return result,
+        };
+        let mut report = FlushReport::default();
+        while !events_to_send.is_empty() {
+            let (chunk, bytes) = chunk::take_chunk(&mut events_to_send, self.max_request_bytes);
+            let mut retries = 0;
+            let result = loop {
+                let timeout = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+                let started = Instant::now();
+                let sent = self.send_batch_async(&chunk, timeout).await;
+                report.latency += started.elapsed();
+                let sent = self.record_sent(sent);
+                match sent {
+                    Err(e) => match self.next_retry(&e, retries, deadline) {
+                        Some(backoff) => {
+                            retries += 1;
+                            #[cfg(not(target_arch = "wasm32"))]
+                            self.async_runtime.sleep(backoff).await;
+                            #[cfg(target_arch = "wasm32")]
+                            web::sleep(backoff).await;
+                        }
+                        None => break Err(e),
+                    },
+                    result => break result,
+                }
+            };
+            self.finish_chunk_send(
+                &mut report,
+                chunk,
+                bytes,
+                &mut events_to_send,
+                retries,
+                result,
+            )?;
+        }
+        Ok(self.finish_report(report))
+    }
+
+    /// Move the client onto a background thread that flushes according to `policy`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn_worker(self, policy: FlushPolicy) -> GameEventsIOWorker {
+        GameEventsIOWorker::spawn(self, policy)
+    }
+
+    /// Move the client into a task on its [`AsyncRuntime`] that flushes
+    /// according to `policy`, without a thread of its own
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    pub fn spawn_async_worker(self, policy: FlushPolicy) -> GameEventsIOAsyncWorker {
+        GameEventsIOAsyncWorker::spawn(self, policy)
+    }
+
+    /// Get the number of buffered events
+    pub fn pending_events_count(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Get the deployment stage stamped onto events
+    pub fn environment(&self) -> &Environment {
+        &self.environment
+    }
+
+    /// Get the number of events dropped because the buffer was full
+    pub fn dropped_events_count(&self) -> u64 {
+        self.metrics.dropped()
+    }
+
+    /// Drop damaged records from the storage and reclaim the space of sent
+    /// ones, e.g. after a long offline session
+    ///
+    /// Also runs when the client is built. Damaged records of events still
+    /// queued are written again from the queue.
+    pub fn compact_storage(&mut self) -> Result<Compaction, GameEventsIOError> {
+        let compaction = self.store.compact()?;
+        self.metrics.add_corrupted(compaction.discarded);
+        if compaction.discarded > 0 {
+            self.store.replace(&self.events);
+        }
+        self.last_compaction = compaction;
+        Ok(compaction)
+    }
+
+    /// Get what the most recent compaction of the storage kept and dropped
+    pub fn last_compaction(&self) -> Compaction {
+        self.last_compaction
+    }
+
+    /// Get the fraction of logged events that are kept
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Get the number of events discarded by sampling
+    pub fn sampled_out_events_count(&self) -> u64 {
+        self.metrics.sampled_out()
+    }
+
+    /// Get counters of what the client did with its events, including the
+    /// queue depth and the latency of the last flush
+    pub fn metrics(&self) -> Arc<ClientMetrics> {
+        Arc::clone(&self.metrics)
+    }
+
+    /// Export the client's [`metrics`](Self::metrics) through `registry`
+    ///
+    /// To export several clients into one registry, register a
+    /// [`PrometheusCollector`] per client with a distinguishing label.
+    #[cfg(feature = "prometheus")]
+    pub fn register_prometheus(&self, registry: &prometheus::Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(PrometheusCollector::new(self.metrics())))
+    }
+
+    /// Drop events named `name` from now on, e.g. one flooding the backend
+    pub fn disable_event(&mut self, name: impl Into<String>) {
+        self.disabled_events.insert(name.into());
+    }
+
+    /// Stop dropping events named `name`; the remote configuration may
+    /// still disable them
+    pub fn enable_event(&mut self, name: &str) -> bool {
+        self.disabled_events.remove(name)
+    }
+
+    /// Whether events named `name` are dropped, set here or remotely
+    pub fn is_event_disabled(&self, name: &str) -> bool {
+        self.disabled_events.contains(name) || self.remote_settings.disabled_events.contains(name)
+    }
+
+    /// Get the number of events discarded because they are disabled
+    pub fn disabled_events_count(&self) -> u64 {
+        self.suppressed_events.values().sum()
+    }
+
+    /// Get the number of events discarded because they are disabled, per
+    /// event name
+    pub fn suppressed_events(&self) -> &HashMap<String, u64> {
+        &self.suppressed_events
+    }
+
+    /// Get the settings from the latest remote configuration document
+    pub fn remote_settings(&self) -> &RemoteSettings {
+        &self.remote_settings
+    }
+
+    /// Get the number of events discarded by rate limits, per event name
+    pub fn rate_limited_events(&self) -> &HashMap<String, u64> {
+        &self.rate_limited_events
+    }
+
+    /// Get the number of retries the most recent flush needed
+    pub fn last_flush_retries(&self) -> u32 {
+        self.last_flush_retries
+    }
+
+    /// Get the requests the most recent flush made, one per chunk
+    pub fn last_flush_chunks(&self) -> &[ChunkOutcome] {
+        &self.last_flush_chunks
+    }
+
+    /// Get the events the backend rejected in the most recent flush
+    ///
+    /// Retryable ones were queued again, the others handed to the dead-letter
+    /// sink. The rest of their batch counts as sent.
+    pub fn last_flush_rejected(&self) -> &[RejectedEvent] {
+        &self.last_flush_rejected
+    }
+
+    /// Get the state of the circuit breaker; always `Closed` without one
+    pub fn breaker_state(&self) -> BreakerState {
+        match (self.breaker_open_until, self.breaker_retry_in()) {
+            (_, Some(retry_in)) => BreakerState::Open { retry_in },
+            (Some(_), None) => BreakerState::HalfOpen,
+            (None, None) => BreakerState::Closed,
+        }
+    }
+
+    /// How long the circuit breaker keeps flushes from being attempted
+    fn breaker_retry_in(&self) -> Option<Duration> {
+        let until = self.breaker_open_until?;
+        let remaining = until.saturating_duration_since(Instant::now());
+        (!remaining.is_zero()).then_some(remaining)
+    }
+
+    /// Get how much longer the backend asked the client to wait, after a 429
+    /// or 503 response with `Retry-After`
+    ///
+    /// Until then flushes fail with [`GameEventsIOError::RateLimited`] without
+    /// sending anything.
+    pub fn throttled_for(&self) -> Option<Duration> {
+        let until = self.throttled_until?;
+        let remaining = until.saturating_duration_since(Instant::now());
+        (!remaining.is_zero()).then_some(remaining)
+    }
+
+    /// Delay before retry number `retry`, or `None` if the backend asked to
+    /// wait longer than the retry policy's `max_backoff`
+    fn retry_delay(&self, error: &GameEventsIOError, retry: u32) -> Option<Duration> {
+        match error.retry_after() {
+            Some(delay) if delay > self.retry_policy.max_backoff => None,
+            Some(delay) => Some(delay),
+            None => Some(self.retry_policy.backoff(retry)),
+        }
+    }
+
+    /// Update the clock offset from a response's `Date` header
+    ///
+    /// Passes `received` through.
+    fn record_server_date(&mut self, received: Received) -> Received {
+        if let Some(date) = received.date.and_then(|date| i64::try_from(date).ok()) {
+            let device = clock::unix_secs(self.clock.now()) as i64;
+            self.clock_offset = date.checked_sub(device);
+        }
+        received
+    }
+
+    /// Seconds to add to this device's clock to get the backend's, once a
+    /// flush has seen a response with a `Date` header
+    pub fn clock_offset(&self) -> Option<i64> {
+        self.clock_offset
+    }
+
+    /// Update the throttle and the circuit breaker with the outcome of one
+    /// send attempt
+    fn record_attempt<T>(&mut self, result: &Result<T, GameEventsIOError>) {
+        self.throttled_until = result
+            .as_ref()
+            .err()
+            .and_then(GameEventsIOError::retry_after)
+            .and_then(|delay| Instant::now().checked_add(delay.min(error::MAX_RETRY_AFTER)));
+        let Some(breaker) = &self.circuit_breaker else {
+            return;
+        };
+        match result {
+            Ok(_) => {
+                self.consecutive_failures = 0;
+                self.breaker_open_until = None;
+            }
+            Err(e) if e.is_retryable() => {
+                self.consecutive_failures += 1;
+                if self.consecutive_failures >= breaker.failure_threshold {
+                    self.breaker_open_until = Some(Instant::now() + breaker.cool_down);
+                }
+            }
+            Err(_) => {}
+        }
+    }
+
+    /// The steps of a flush before anything is sent, shared by the blocking
+    /// and async flushes
+    ///
+    /// Breaks with the flush's result if there is nothing to send, else
+    /// continues with the batch to send.
+    fn start_flush(
+        &mut self,
+        batch_size: usize,
+    ) -> ControlFlow<Result<FlushReport, GameEventsIOError>, Vec<GameEventsIOEvent>> {
+        self.store.store_deferred(&self.events);
+        self.drain_producers();
+        self.revalidate_remote_config();
+        self.prune_queue();
+        if let Some(reason) = self.nothing_to_send() {
+            return ControlFlow::Break(Ok(FlushReport::skipped(reason)));
+        }
+        if let Some(retry_after) = self.breaker_retry_in() {
+            return ControlFlow::Break(Err(GameEventsIOError::CircuitOpen { retry_after }));
+        }
+        if let Some(retry_after) = self.throttled_for() {
+            return ControlFlow::Break(Err(GameEventsIOError::RateLimited {
+                retry_after: Some(retry_after),
+            }));
+        }
+
+        let mut events_to_send = self.take_batch(batch_size);
+        self.prepare_batch(&mut events_to_send);
+        if events_to_send.is_empty() {
+            return ControlFlow::Break(self.finish_batch(
+                events_to_send,
+                Ok(FlushReport::skipped("No events to send")),
+            ));
+        }
+        if let Some(mode) = self.dry_run {
+            return ControlFlow::Break(self.finish_dry_run(events_to_send, mode));
+        }
+        self.last_flush_retries = 0;
+        self.last_flush_chunks.clear();
+        self.last_flush_rejected.clear();
+        ControlFlow::Continue(events_to_send)
+    }
+
+    /// Record the outcome of one send attempt; passes `sent` through
+    fn record_sent(
+        &mut self,
+        sent: Result<Received, GameEventsIOError>,
+    ) -> Result<Received, GameEventsIOError> {
+        let sent = sent.map(|received| self.record_server_date(received));
+        self.record_attempt(&sent);
+        sent
+    }
+
+    /// Delay before sending a chunk again after `error`, or `None` to give
+    /// up on it, with `retries` retries made so far
+    fn next_retry(
+        &self,
+        error: &GameEventsIOError,
+        retries: u32,
+        deadline: Option<Instant>,
+    ) -> Option<Duration> {
+        if !error.is_retryable()
+            || self.breaker_retry_in().is_some()
+            || retries + 1 >= self.retry_policy.max_attempts
+        {
+            return None;
+        }
+        let backoff = self.retry_delay(error, retries + 1)?;
+        if deadline.is_some_and(|d| Instant::now() + backoff >= d) {
+            return None;
+        }
+        Some(backoff)
+    }
+
+    /// Finish a chunk once its retries are over and add its response to
+    /// `report`
+    fn finish_chunk_send(
+        &mut self,
+        report: &mut FlushReport,
+        chunk: Vec<GameEventsIOEvent>,
+        bytes: usize,
+        rest: &mut Vec<GameEventsIOEvent>,
+        retries: u32,
+        result: Result<Received, GameEventsIOError>,
+    ) -> Result<(), GameEventsIOError> {
+        self.last_flush_retries += retries;
+        self.metrics.add_retried(retries);
+        let received = self.finish_request(chunk, bytes, rest, result)?;
+        report.request_ids.extend(received.request_id);
+        report.responses.push(received.body);
+        Ok(())
+    }
+
+    /// Why a flush has nothing to do right now, if it hasn't
+    fn nothing_to_send(&self) -> Option<&'static str> {
+        if self.consent == ConsentState::Pending {
+            Some("Waiting for tracking consent")
+        } else if self.events.is_empty() {
+            Some("No events to send")
+        } else {
+            None
+        }
+    }
+
+    /// Remove up to `batch_size` events from the front of the buffer
+    fn take_batch(&mut self, batch_size: usize) -> Vec<GameEventsIOEvent> {
+        let count = std::cmp::min(self.events.len(), batch_size);
+        let batch: Vec<_> = self.events.drain(..count).collect();
+        for event in &batch {
+            self.pending_bytes -= self.tracked_size(event);
+        }
+        self.metrics.set_queue_depth(self.events.len());
+        batch
+    }
+
+    /// Put a batch that was not sent back at the front of the buffer
+    fn requeue(&mut self, batch: Vec<GameEventsIOEvent>) {
+        for event in batch.into_iter().rev() {
+            self.pending_bytes += self.tracked_size(&event);
+            self.events.push_front(event);
+        }
+        self.metrics.set_queue_depth(self.events.len());
+    }
+
+    /// Run the plugins' `before_flush` hooks on a batch
+    fn prepare_batch(&mut self, batch: &mut Vec<GameEventsIOEvent>) {
+        for plugin in &self.plugins {
+            plugin.before_flush(batch);
+        }
+        if !self.plugins.is_empty() {
+            // They may have added or removed events
+            self.store.desync();
+        }
+    }
+
+    /// Tell the plugins how a flush went
+    fn notify_flushed(&self, result: &Result<FlushReport, GameEventsIOError>) {
+        if result.is_err() {
+            self.metrics.add_flush_failure();
+        }
+        for plugin in &self.plugins {
+            match result {
+                Ok(report) => plugin.on_flush_success(report),
+                Err(e) => plugin.on_flush_failure(e),
+            }
+        }
+    }
+
+    /// Put a failed batch back at the front of the buffer, or drop a sent one
+    /// from the spool and hand it to the mirrors
+    fn finish_batch<T, E>(
+        &mut self,
+        batch: Vec<GameEventsIOEvent>,
+        result: Result<T, E>,
+    ) -> Result<T, E> {
+        let settled = batch.len();
+        self.finish_chunk(batch, &mut Vec::new(), settled, result)
+    }
+
+    /// Record the outcome of one request, set the events the backend rejected
+    /// aside and finish the rest of the chunk
+    fn finish_request(
+        &mut self,
+        mut chunk: Vec<GameEventsIOEvent>,
+        bytes: usize,
+        rest: &mut Vec<GameEventsIOEvent>,
+        result: Result<Received, GameEventsIOError>,
+    ) -> Result<Received, GameEventsIOError> {
+        let settled = chunk.len();
+        let rejected = match &result {
+            Ok(received) => rejection::take_rejected(&mut chunk, &received.body),
+            Err(_) => Vec::new(),
+        };
+        self.last_flush_chunks.push(ChunkOutcome::new(
+            chunk.len() + rejected.len(),
+            rejected.len(),
+            bytes,
+            &result,
+        ));
+        if !rejected.is_empty() {
+            let (retry, dead): (Vec<_>, Vec<_>) =
+                rejected.iter().partition(|rejection| rejection.retryable);
+            if !retry.is_empty() {
+                // They go back to the front, out of their stored order
+                self.store.desync();
+            }
+            self.requeue(retry.into_iter().map(|r| r.event.clone()).collect());
+            if let (Some(sink), false) = (&self.dead_letter_sink, dead.is_empty()) {
+                let dead: Vec<_> = dead
+                    .into_iter()
+                    .map(RejectedEvent::to_dead_letter)
+                    .collect();
+                let _ = sink.send(&dead, None);
+            }
+            self.last_flush_rejected.extend(rejected);
+        }
+        if result.is_ok() {
+            self.metrics.add_sent(chunk.len());
+            #[cfg(not(target_arch = "wasm32"))]
+            self.forget_sent_crashes(&chunk);
+        }
+        self.finish_chunk(chunk, rest, settled, result)
+    }
+
+    /// Fill in a flush report's counts from the outcomes of its requests and
+    /// record its latency
+    fn finish_report(&self, mut report: FlushReport) -> FlushReport {
+        self.metrics.set_last_flush_latency(report.latency);
+        for chunk in &self.last_flush_chunks {
+            report.accepted += chunk.events - chunk.rejected;
+            report.rejected += chunk.rejected;
+        }
+        report.rejections = self.last_flush_rejected.clone();
+        report
+    }
+
+    /// [`finish_batch`](Self::finish_batch) for one chunk of a batch whose
+    /// remaining chunks are in `rest`; a failure puts `rest` back as well
+    ///
+    /// `settled` is how many stored events the chunk was taken from, which
+    /// are acknowledged once it has been sent.
+    fn finish_chunk<T, E>(
+        &mut self,
+        batch: Vec<GameEventsIOEvent>,
+        rest: &mut Vec<GameEventsIOEvent>,
+        settled: usize,
+        result: Result<T, E>,
+    ) -> Result<T, E> {
+        if result.is_err() {
+            self.requeue(std::mem::take(rest));
+            self.requeue(batch);
+            return result;
+        }
+        self.store.settle(settled, rest.iter().chain(&self.events));
+        if !batch.is_empty() {
+            for mirror in &self.mirrors {
+                let _ = mirror.send(&batch, None);
+            }
+        }
+        self.event_pool.recycle(batch);
+        result
+    }
+
+    /// Serialize a batch and report it instead of sending it
+    fn finish_dry_run(
+        &mut self,
+        batch: Vec<GameEventsIOEvent>,
+        mode: DryRun,
+    ) -> Result<FlushReport, GameEventsIOError> {
+        let result = self.request_body(&batch).map(|body| {
+            FlushReport::skipped(format!(
+                "Dry run: {} events ({} bytes) not sent",
+                batch.len(),
+                body.len()
+            ))
+        });
+        if result.is_ok() {
+            if let Some(sink) = &self.dry_run_sink {
+                let _ = sink.send(&batch, None);
+            }
+        }
+        if result.is_err() || mode == DryRun::Retain {
+            self.requeue(batch);
+            return result;
+        }
+        self.store.settle(batch.len(), &self.events);
+        result
+    }
+
+    /// Headers sent with every request to the backend
+    fn base_headers(&self) -> HeaderMap {
+        let mut headers = self.parsed_headers.clone();
+        if let Some(callback) = &self.header_callback {
+            callback.add_headers(&mut headers);
+        }
+        // `build` rejects API keys that are not valid header values
+        if let Ok(value) = HeaderValue::from_str(&format!("Bearer {}", self.api_key)) {
+            headers.insert(AUTHORIZATION, value);
+        }
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        self.app_info.insert_headers(&mut headers);
+        headers
+    }
+
+    /// Headers sent with every flush request
+    fn request_headers(&self) -> HeaderMap {
+        let mut headers = self.base_headers();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(self.payload_format.content_type()),
+        );
+        if let Some(encoding) = self.compression.content_encoding() {
+            headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding));
+        }
+        headers
+    }
+
+    /// Serialize and encode a batch of events
+    fn request_body(&self, events: &[GameEventsIOEvent]) -> Result<Vec<u8>, GameEventsIOError> {
+        Ok(self.compression.encode(self.payload_format, events)?)
+    }
+
+    /// Send a batch to the configured sink, or to the backend
+    ///
+    /// A sink's response has no `Date` or request id.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn send_batch(
+        &self,
+        events: &[GameEventsIOEvent],
+        timeout: Option<Duration>,
+    ) -> Result<Received, GameEventsIOError> {
+        match &self.sink {
+            Some(sink) => sink.send(events, timeout).map(Received::from),
+            None => self.send_blocking(events, timeout),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn send_blocking(
+        &self,
+        events: &[GameEventsIOEvent],
+        timeout: Option<Duration>,
+    ) -> Result<Received, GameEventsIOError> {
+        let body = self.request_body(events)?;
+        let request = self.request(&self.backend_url, self.request_headers(), body, timeout);
+        self.transport.post(request)?.into_result()
+    }
+
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    async fn send_batch_async(
+        &self,
+        events: &[GameEventsIOEvent],
+        timeout: Option<Duration>,
+    ) -> Result<Received, GameEventsIOError> {
+        match &self.sink {
+            Some(sink) => sink.send(events, timeout).map(Received::from),
+            None => self.send_async(events, timeout).await,
+        }
+    }
+
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    async fn send_async(
+        &self,
+        events: &[GameEventsIOEvent],
+        timeout: Option<Duration>,
+    ) -> Result<Received, GameEventsIOError> {
+        let body = self.request_body(events)?;
+        let request = self.request(&self.backend_url, self.request_headers(), body, timeout);
+        self.async_transport.post(request).await?.into_result()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn post_blocking(
+        &self,
+        url: &str,
+        headers: HeaderMap,
+        body: Vec<u8>,
+        timeout: Option<Duration>,
+    ) -> Result<String, GameEventsIOError> {
+        let request = self.request(url, headers, body, timeout);
+        Ok(self.transport.post(request)?.into_result()?.body)
+    }
+
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    async fn post_async(
+        &self,
+        url: &str,
+        headers: HeaderMap,
+        body: Vec<u8>,
+    ) -> Result<String, GameEventsIOError> {
+        let request = self.request(url, headers, body, None);
+        Ok(self
+            .async_transport
+            .post(request)
+            .await?
+            .into_result()?
+            .body)
+    }
+
+    /// A request to the backend, signed if a signing key is set, that must
+    /// finish within `timeout` as well as the configured timeouts
+    #[cfg_attr(not(feature = "signing"), allow(unused_mut))]
+    fn request(
+        &self,
+        url: &str,
+        mut headers: HeaderMap,
+        body: Vec<u8>,
+        timeout: Option<Duration>,
+    ) -> HttpRequest {
+        #[cfg(feature = "signing")]
+        if let Some(key) = &self.signing_key {
+            let now = clock::unix_secs(self.clock.now())
+                .saturating_add_signed(self.clock_offset.unwrap_or(0));
+            key.sign(&mut headers, &body, now);
+        }
+        HttpRequest {
+            url: url.to_string(),
+            headers,
+            body,
+            timeout: self.timeouts.for_request(timeout),
+        }
+    }
+}
+
+/// Blocking HTTP client shared by the reqwest transport and the HTTP based sinks
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+fn new_blocking_client() -> reqwest::blocking::Client {
+    reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("failed to build reqwest client")
+}
+
+/// POST `body` and return the response body, classifying non-2xx responses
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+fn post_blocking(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    timeout: Option<Duration>,
+) -> Result<String, GameEventsIOError> {
+    let request = HttpRequest {
+        url: url.to_string(),
+        headers,
+        body,
+        timeout,
+    };
+    Ok(transport::post_reqwest_blocking(client, request)?
+        .into_result()?
+        .body)
+}
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+impl Drop for GameEventsIOClient {
+    fn drop(&mut self) {
+        if let Some(deadline) = self.flush_on_drop {
+            let _ = self.shutdown(deadline);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_creation() {
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .user_id("user123")
+            .session_id("session456")
+            .build()
+            .unwrap();
+
+        assert_eq!(event.event, "test_event");
+        assert_eq!(event.user_id, "user123");
+        assert_eq!(event.session_id, "session456");
+        assert!(event.time > 0);
+    }
+
+    #[test]
+    fn test_event_ids_are_unique_and_serialized() {
+        let event = || {
+            GameEventsIOEventBuilder::default()
+                .event("test_event")
+                .build()
+                .unwrap()
+        };
+        let (a, b) = (event(), event());
+        assert!(Uuid::parse_str(&a.event_id).is_ok());
+        assert_ne!(a.event_id, b.event_id);
+
+        let json = serde_json::to_value(&a).unwrap();
+        assert_eq!(json["event_id"], a.event_id.as_str());
+
+        // Events spooled before ids existed get one when read back
+        let old: GameEventsIOEvent =
+            serde_json::from_str(r#"{"event":"a","user_id":"u","session_id":"s","time":1}"#)
+                .unwrap();
+        assert!(!old.event_id.is_empty());
+
+        // Defaulted events have an id too, and keep it through a reload
+        let default = GameEventsIOEvent::default();
+        assert!(Uuid::parse_str(&default.event_id).is_ok());
+        let reloaded: GameEventsIOEvent =
+            serde_json::from_str(&serde_json::to_string(&default).unwrap()).unwrap();
+        assert_eq!(reloaded.event_id, default.event_id);
+
+        // Events without an id, e.g. built without `std`, get one when logged
+        let mut client = GameEventsIOClient::new("test_api_key");
+        client.log_event(GameEventsIOEvent {
+            event_id: String::new(),
+            ..event()
+        });
+        assert!(Uuid::parse_str(&client.events[0].event_id).is_ok());
+    }
+
+    #[test]
+    fn test_client_creation() {
+        let client = GameEventsIOClient::new("test_api_key");
+        assert_eq!(client.pending_events_count(), 0);
+    }
+
+    #[test]
+    fn test_with_endpoint_validation() {
+        let client =
+            GameEventsIOClient::with_endpoint("test_api_key", "https://collector.local/v1/events")
+                .unwrap();
+        assert_eq!(client.backend_url(), "https://collector.local/v1/events");
+
+        assert!(GameEventsIOClient::with_endpoint("test_api_key", "not a url").is_err());
+        assert!(
+            GameEventsIOClient::with_endpoint("test_api_key", "ftp://collector.local").is_err()
+        );
+    }
+
+    #[test]
+    fn test_api_key_validation() {
+        let error = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key\n")
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("invalid api_key"), "{}", error);
+        assert!(!error.to_string().contains("test_api_key"));
+    }
+
+    #[test]
+    fn test_event_buffering() {
+        let mut client = GameEventsIOClient::new("test_api_key");
+
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .user_id("user123")
+            .session_id("session456")
+            .build()
+            .unwrap();
+
+        client.log_event(event);
+        assert_eq!(client.pending_events_count(), 1);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_flush_async_without_events() {
+        let mut client = GameEventsIOClient::new("test_api_key");
+        let response = client.flush_async().await.unwrap();
+        assert_eq!(response.to_string(), "No events to send");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_blocking_and_async_flushes_send_the_same_request() {
+        #[derive(Default)]
+        struct Recorder {
+            requests: std::sync::Mutex<Vec<HttpRequest>>,
+        }
+        impl Recorder {
+            fn record(&self, request: HttpRequest) -> Result<HttpResponse, GameEventsIOError> {
+                self.requests.lock().unwrap().push(request);
+                Ok(HttpResponse {
+                    status: reqwest::StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: "ok".to_string(),
+                })
+            }
+        }
+        impl HttpTransport for Arc<Recorder> {
+            fn post(&self, request: HttpRequest) -> Result<HttpResponse, GameEventsIOError> {
+                self.record(request)
+            }
+        }
+        impl AsyncHttpTransport for Arc<Recorder> {
+            fn post(&self, request: HttpRequest) -> TransportFuture<'_> {
+                Box::pin(async move { self.record(request) })
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .transport(recorder.clone())
+            .async_transport(recorder.clone())
+            .build()
+            .unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+
+        client.log_event(event.clone());
+        let blocking = client.flush().unwrap();
+        client.log_event(event);
+        let non_blocking = client.flush_async().await.unwrap();
+        assert_eq!(blocking.to_string(), non_blocking.to_string());
+        assert_eq!(blocking.accepted, non_blocking.accepted);
+
+        let requests = recorder.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].url, requests[1].url);
+        assert_eq!(requests[0].headers, requests[1].headers);
+        let sent: Vec<Vec<GameEventsIOEvent>> = requests
+            .iter()
+            .map(|request| serde_json::from_slice(&request.body).unwrap())
+            .collect();
+        assert_eq!(sent[0].len(), sent[1].len());
+        assert_eq!(sent[0][0].event, sent[1][0].event);
+    }
+
+    #[test]
+    fn test_flush_retries_until_max_attempts() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .retry_policy(
+                RetryPolicyBuilder::default()
+                    .max_attempts(3u32)
+                    .initial_backoff(Duration::from_millis(1))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+        client.log_event(event);
+
+        assert!(client.flush().is_err());
+        assert_eq!(client.last_flush_retries(), 2);
+        assert_eq!(client.pending_events_count(), 1);
+    }
+
+    #[test]
+    fn test_shutdown_respects_deadline() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .retry_policy(
+                RetryPolicyBuilder::default()
+                    .max_attempts(10u32)
+                    .initial_backoff(Duration::from_millis(200))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+        client.log_event(event);
+
+        let started = Instant::now();
+        assert!(client.shutdown(Duration::from_millis(300)).is_err());
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(client.pending_events_count(), 1);
+    }
+
+    #[test]
+    fn test_plugins_run_in_order() {
+        struct Tag(&'static str);
+        impl ClientPlugin for Tag {
+            fn before_enqueue(&self, event: &mut GameEventsIOEvent) -> bool {
+                event
+                    .event_properties
+                    .entry("tags".into())
+                    .or_insert_with(|| serde_json::json!([]))
+                    .as_array_mut()
+                    .unwrap()
+                    .push(self.0.into());
+                true
+            }
+        }
+
+        struct DropDebug;
+        impl ClientPlugin for DropDebug {
+            fn before_enqueue(&self, event: &mut GameEventsIOEvent) -> bool {
+                !event.event.starts_with("debug_")
+            }
+
+            fn before_flush(&self, batch: &mut Vec<GameEventsIOEvent>) {
+                batch.retain(|event| event.event != "noisy");
+            }
+        }
+
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .plugin(Tag("first"))
+            .plugin(DropDebug)
+            .plugin(Tag("second"))
+            .build()
+            .unwrap();
+
+        for name in ["debug_fps", "noisy", "noisy"] {
+            let event = GameEventsIOEventBuilder::default()
+                .event(name)
+                .build()
+                .unwrap();
+            client.log_event(event);
+        }
+        assert_eq!(client.pending_events_count(), 2);
+        assert_eq!(
+            client.events[0].event_properties["tags"],
+            serde_json::json!(["first", "second"])
+        );
+
+        // Every event in the batch is vetoed, so nothing is sent
+        assert_eq!(client.flush().unwrap().to_string(), "No events to send");
+        assert_eq!(client.pending_events_count(), 0);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(MemorySink::new())
+            .max_pending_events(2usize)
+            .build()
+            .unwrap();
+        let metrics = client.metrics();
+        for name in ["a", "b", "c"] {
+            client.log_event(
+                GameEventsIOEventBuilder::default()
+                    .event(name)
+                    .build()
+                    .unwrap(),
+            );
+        }
+        assert_eq!((metrics.enqueued(), metrics.dropped()), (3, 1));
+        assert_eq!(metrics.queue_depth(), 2);
+
+        client.flush().unwrap();
+        assert_eq!((metrics.sent(), metrics.queue_depth()), (2, 0));
+        assert_eq!(metrics.retried(), 0);
+    }
+
+    #[test]
+    fn test_plugins_see_flush_outcomes() {
+        #[derive(Clone, Default)]
+        struct Health(Arc<std::sync::Mutex<Vec<String>>>);
+        impl ClientPlugin for Health {
+            fn on_flush_success(&self, report: &FlushReport) {
+                let outcome = format!("sent {}", report.accepted);
+                self.0.lock().unwrap().push(outcome);
+            }
+
+            fn on_flush_failure(&self, error: &GameEventsIOError) {
+                self.0.lock().unwrap().push(error.to_string());
+            }
+        }
+
+        struct Down;
+        impl EventSink for Down {
+            fn send(
+                &self,
+                _events: &[GameEventsIOEvent],
+                _timeout: Option<Duration>,
+            ) -> Result<String, GameEventsIOError> {
+                Err(GameEventsIOError::Sink("queue unavailable".into()))
+            }
+        }
+
+        let health = Health::default();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(MemorySink::new())
+            .plugin(health.clone())
+            .build()
+            .unwrap();
+        client.flush().unwrap();
+        client.log_event(
+            GameEventsIOEventBuilder::default()
+                .event("a")
+                .build()
+                .unwrap(),
+        );
+        client.flush().unwrap();
+        assert_eq!(*health.0.lock().unwrap(), ["sent 0", "sent 1"]);
+
+        let worker = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(Down)
+            .retry_policy(RetryPolicy::disabled())
+            .plugin(health.clone())
+            .build()
+            .unwrap()
+            .spawn_worker(FlushPolicy::default());
+        worker.log_event(
+            GameEventsIOEventBuilder::default()
+                .event("b")
+                .build()
+                .unwrap(),
+        );
+        worker.flush();
+        worker.stop();
+        let outcomes = health.0.lock().unwrap();
+        assert!(outcomes[2].contains("queue unavailable"), "{:?}", outcomes);
+    }
+
+    #[test]
+    fn test_custom_sink_receives_batches() {
+        use std::sync::Mutex;
+
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+        impl EventSink for Recorder {
+            fn send(
+                &self,
+                events: &[GameEventsIOEvent],
+                _timeout: Option<Duration>,
+            ) -> Result<String, GameEventsIOError> {
+                let mut sent = self.0.lock().unwrap();
+                sent.extend(events.iter().map(|e| e.event.to_string()));
+                Ok(format!("recorded {}", events.len()))
+            }
+        }
+
+        let recorder = Recorder::default();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(recorder.clone())
+            .build()
+            .unwrap();
+        for name in ["app_start", "level_up"] {
+            let event = GameEventsIOEventBuilder::default()
+                .event(name)
+                .build()
+                .unwrap();
+            client.log_event(event);
+        }
+
+        let report = client.flush().unwrap();
+        assert_eq!(report.to_string(), "recorded 2");
+        assert_eq!(report.accepted, 2);
+        assert!(report.request_ids.is_empty());
+        assert_eq!(client.pending_events_count(), 0);
+        assert_eq!(*recorder.0.lock().unwrap(), ["app_start", "level_up"]);
+    }
+
+    #[test]
+    fn test_dry_run_skips_sending() {
+        let event = || {
+            GameEventsIOEventBuilder::default()
+                .event("test_event")
+                .build()
+                .unwrap()
+        };
+
+        let sink = MemorySink::new();
+        let mirror = MemorySink::new();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .dry_run(DryRun::Clear)
+            .dry_run_sink(sink.clone())
+            .mirror(mirror.clone())
+            .build()
+            .unwrap();
+        client.log_event(event());
+        assert!(client
+            .flush()
+            .unwrap()
+            .to_string()
+            .starts_with("Dry run: 1 events"));
+        assert_eq!(client.pending_events_count(), 0);
+        assert_eq!(sink.events().len(), 1);
+        assert!(mirror.events().is_empty());
+
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .dry_run(DryRun::Retain)
+            .build()
+            .unwrap();
+        client.log_event(event());
+        client.flush().unwrap();
+        assert_eq!(client.pending_events_count(), 1);
+    }
+
+    #[test]
+    fn test_pooled_events_are_recycled_after_flush() {
+        let sink = MemorySink::new();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .high_throughput()
+            .clock(FixedClock::at_unix_secs(1_700_000_000))
+            .sink(sink.clone())
+            .build()
+            .unwrap();
+        let mut event = client.pooled_event("frame_stats");
+        event
+            .event_properties
+            .insert("fps".into(), serde_json::json!(58));
+        let sent_id = event.event_id.clone();
+        client.log_event(event);
+        client.flush().unwrap();
+        sink.assert_event_logged("frame_stats", |props| props["fps"] == 58);
+
+        let event = client.pooled_event("frame_stats");
+        assert_ne!(event.event_id, sent_id);
+        assert_eq!(event.time, 1_700_000_000);
+        assert!(event.event_properties.is_empty());
+        assert!(event.event_properties.capacity() > 0);
+    }
+
+    #[test]
+    fn test_batch_policy_flushes_automatically() {
+        let event = |time: u64| {
+            GameEventsIOEventBuilder::default()
+                .event("test_event")
+                .time(time)
+                .build()
+                .unwrap()
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let sink = MemorySink::new();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(sink.clone())
+            .batch_policy(
+                BatchPolicyBuilder::default()
+                    .max_events(3usize)
+                    .max_age(Duration::from_secs(60))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        for _ in 0..2 {
+            client.log_event(event(now));
+        }
+        assert_eq!(client.pending_events_count(), 2);
+        client.log_event(event(now));
+        assert_eq!(client.pending_events_count(), 0);
+        client.log_event(event(now - 120));
+        assert_eq!(sink.events().len(), 4);
+
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(MemorySink::new())
+            .batch_policy(
+                BatchPolicyBuilder::default()
+                    .max_bytes(300usize)
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        client.log_event(event(now));
+        assert_eq!(client.pending_events_count(), 1);
+        assert!(client.pending_bytes > 0);
+        while client.pending_events_count() > 0 {
+            client.log_event(event(now));
+        }
+        assert_eq!(client.pending_bytes, 0);
+    }
+
+    #[test]
+    fn test_injected_clock() {
+        let clock = FixedClock::at_unix_secs(1_700_000_000);
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .clock(clock.clone())
+            .batch_policy(
+                BatchPolicyBuilder::default()
+                    .max_age(Duration::from_secs(60))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        client.identify("user123");
+        assert_eq!(client.events[0].time, 1_700_000_000);
+        assert!(!client.flush_due());
+        clock.advance(Duration::from_secs(60));
+        assert!(client.flush_due());
+
+        let mut session = GameEventsIOSessionBuilder::default()
+            .user_id("user123")
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        session.push_event("test_event", HashMap::new());
+        let event = &session.take_events(1)[0];
+        assert_eq!(event.time, 1_700_000_060);
+        assert_eq!(event.session_epoch, Some(1_700_000_060_000));
+
+        let backfilled = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .timestamp(UNIX_EPOCH + Duration::from_secs(1_600_000_000))
+            .build()
+            .unwrap();
+        assert_eq!(backfilled.time, 1_600_000_000);
+    }
+
+    #[test]
+    fn test_flush_splits_oversized_batches() {
+        struct FailThird(MemorySink);
+        impl EventSink for FailThird {
+            fn send(
+                &self,
+                events: &[GameEventsIOEvent],
+                timeout: Option<Duration>,
+            ) -> Result<String, GameEventsIOError> {
+                if self.0.events().len() == 4 {
+                    return Err(GameEventsIOError::Sink("unavailable".into()));
+                }
+                self.0.send(events, timeout)
+            }
+        }
+
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .user_id("user123")
+            .build()
+            .unwrap();
+        let recorder = MemorySink::new();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(FailThird(recorder.clone()))
+            .retry_policy(RetryPolicy::disabled())
+            .build()
+            .unwrap();
+        for _ in 0..6 {
+            client.log_event(event.clone());
+        }
+        let len = serde_json::to_vec(&client.events[0]).unwrap().len();
+        client.max_request_bytes = Some(2 * len + 3);
+
+        assert!(client.flush().is_err());
+        let chunks = client.last_flush_chunks();
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|chunk| chunk.events == 2));
+        assert!(chunks.iter().all(|chunk| chunk.bytes <= 2 * len + 3));
+        assert_eq!(chunks[0].result.as_deref(), Ok("Recorded 2 events"));
+        assert!(chunks[2].result.is_err());
+        assert_eq!(recorder.events().len(), 4);
+        assert_eq!(client.pending_events_count(), 2);
+    }
+
+    #[test]
+    fn test_immediate_events_flush_the_queue() {
+        let sink = MemorySink::new();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(sink.clone())
+            .build()
+            .unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("level_up")
+            .build()
+            .unwrap();
+        client.log_event(event);
+        assert_eq!(client.pending_events_count(), 1);
+
+        let mut session = GameEventsIOSession::new("user123", "session456");
+        let purchase = PurchaseEventBuilder::default()
+            .item_id("gems_100")
+            .price(0.99)
+            .currency("USD")
+            .quantity(1u32)
+            .transaction_id("txn_456")
+            .build()
+            .unwrap();
+        session.push_purchase(purchase);
+        for event in session.take_events(10) {
+            client.log_event(event);
+        }
+        assert_eq!(client.pending_events_count(), 0);
+        let names: Vec<_> = sink.events().into_iter().map(|e| e.event).collect();
+        assert_eq!(names, ["level_up", "new_session", "purchase"]);
+    }
+
+    #[test]
+    fn test_circuit_breaker_opens_after_failures() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .retry_policy(
+                RetryPolicyBuilder::default()
+                    .max_attempts(5u32)
+                    .initial_backoff(Duration::from_millis(1))
+                    .build()
+                    .unwrap(),
+            )
+            .circuit_breaker(
+                CircuitBreakerBuilder::default()
+                    .failure_threshold(2u32)
+                    .cool_down(Duration::from_millis(50))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+        client.log_event(event);
+
+        assert!(client.flush().is_err());
+        assert_eq!(client.last_flush_retries(), 1);
+        assert!(matches!(client.breaker_state(), BreakerState::Open { .. }));
+        assert!(matches!(
+            client.flush(),
+            Err(GameEventsIOError::CircuitOpen { .. })
+        ));
+        assert_eq!(client.pending_events_count(), 1);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(client.breaker_state(), BreakerState::HalfOpen);
+        // The trial attempt fails and reopens the breaker without retrying
+        assert!(client.flush().is_err());
+        assert_eq!(client.last_flush_retries(), 0);
+        assert!(matches!(client.breaker_state(), BreakerState::Open { .. }));
+    }
+
+    #[test]
+    fn test_retry_after_keeps_events_queued() {
+        let (url, server) = transport::serve_once(
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 120\r\n\
+             Content-Length: 0\r\nConnection: close\r\n\r\n",
+        );
+
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url(url)
+            .build()
+            .unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+        client.log_event(event);
+
+        let error = client.flush().unwrap_err();
+        assert_eq!(error.retry_after(), Some(Duration::from_secs(120)));
+        assert_eq!(client.last_flush_retries(), 0);
+        server.join().unwrap();
+
+        // The next flush waits for the backend instead of sending
+        assert!(client.throttled_for().unwrap() > Duration::from_secs(100));
+        assert!(matches!(
+            client.flush(),
+            Err(GameEventsIOError::RateLimited {
+                retry_after: Some(_)
+            })
+        ));
+        assert_eq!(client.pending_events_count(), 1);
+    }
+
+    #[test]
+    fn test_server_date_corrects_later_events() {
+        let (url, server) = transport::serve_once(
+            "HTTP/1.1 200 OK\r\nDate: Tue, 14 Nov 2023 22:13:20 GMT\r\n\
+             Content-Length: 2\r\nConnection: close\r\n\r\nok",
+        );
+
+        // The device clock runs an hour fast
+        let device_now = 1_700_003_600;
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url(url)
+            .clock(FixedClock::at_unix_secs(device_now))
+            .build()
+            .unwrap();
+        let event = || {
+            GameEventsIOEventBuilder::default()
+                .event("test_event")
+                .time(device_now)
+                .build()
+                .unwrap()
+        };
+        client.log_event(event());
+        assert_eq!(client.clock_offset(), None);
+        assert_eq!(client.events[0].corrected_time, None);
+
+        client.flush().unwrap();
+        server.join().unwrap();
+        assert_eq!(client.clock_offset(), Some(-3600));
+
+        client.log_event(event());
+        assert_eq!(client.events[0].time, device_now);
+        assert_eq!(client.events[0].corrected_time, Some(1_700_000_000));
+    }
+
+    #[test]
+    fn test_absurd_response_headers_do_not_panic() {
+        /// Answers with the headers in `responses`, in order
+        struct Absurd(std::sync::Mutex<Vec<(reqwest::StatusCode, &'static str, &'static str)>>);
+        impl HttpTransport for Absurd {
+            fn post(&self, _request: HttpRequest) -> Result<HttpResponse, GameEventsIOError> {
+                let (status, name, value) = self.0.lock().unwrap().remove(0);
+                let mut headers = HeaderMap::new();
+                headers.insert(name, value.parse().unwrap());
+                Ok(HttpResponse {
+                    status,
+                    headers,
+                    body: "ok".to_string(),
+                })
+            }
+        }
+
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .transport(Absurd(std::sync::Mutex::new(vec![
+                (
+                    reqwest::StatusCode::TOO_MANY_REQUESTS,
+                    "retry-after",
+                    "18446744073709551615",
+                ),
+                (
+                    reqwest::StatusCode::OK,
+                    "date",
+                    "Fri, 31 Dec 99999999999 23:59:59 GMT",
+                ),
+                (
+                    reqwest::StatusCode::OK,
+                    "date",
+                    "Fri, 31 Dec 9999 23:59:59 GMT",
+                ),
+            ])))
+            .retry_policy(RetryPolicy::disabled())
+            .build()
+            .unwrap();
+        let event = || {
+            GameEventsIOEventBuilder::default()
+                .event("test_event")
+                .build()
+                .unwrap()
+        };
+        client.log_event(event());
+        assert!(client.flush().is_err());
+        assert!(client.throttled_for().unwrap() <= error::MAX_RETRY_AFTER);
+        client.throttled_until = None;
+
+        // An unparseable date is ignored, a far-off one is believed
+        client.flush().unwrap();
+        assert_eq!(client.clock_offset(), None);
+        client.log_event(event());
+        client.flush().unwrap();
+        assert!(client.clock_offset().unwrap() > 0);
+        client.log_event(event());
+        assert!(client.events[0].corrected_time.is_some());
+
+        // Sinks can report any delay
+        client.record_attempt::<()>(&Err(GameEventsIOError::RateLimited {
+            retry_after: Some(Duration::MAX),
+        }));
+        assert!(client.throttled_for().is_some());
+    }
+
+    #[test]
+    fn test_custom_transport_sends_requests() {
+        #[derive(Default)]
+        struct Recorder {
+            requests: std::sync::Mutex<Vec<HttpRequest>>,
+        }
+        impl HttpTransport for Arc<Recorder> {
+            fn post(&self, request: HttpRequest) -> Result<HttpResponse, GameEventsIOError> {
+                self.requests.lock().unwrap().push(request);
+                Ok(HttpResponse {
+                    status: reqwest::StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: "ok".to_string(),
+                })
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .transport(recorder.clone())
+            .build()
+            .unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+        client.log_event(event);
+        assert_eq!(client.flush().unwrap().to_string(), "ok");
+
+        let requests = recorder.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, client.backend_url());
+        assert_eq!(requests[0].headers[AUTHORIZATION], "Bearer test_api_key");
+        let sent: Vec<GameEventsIOEvent> = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(sent[0].event, "test_event");
+    }
+
+    #[test]
+    fn test_requests_go_through_proxy() {
+        let (url, server) = transport::serve_once(
+            "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+        );
+        let proxy = Proxy::url(url.trim_end_matches("/v1/events")).basic_auth("alice", "secret");
+
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://collector.invalid/v1/events")
+            .proxy(proxy)
+            .build()
+            .unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+        client.log_event(event);
+        assert_eq!(client.flush().unwrap().to_string(), "ok");
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST http://collector.invalid/v1/events "));
+        // base64("alice:secret")
+        assert!(request.contains("proxy-authorization: Basic YWxpY2U6c2VjcmV0"));
+
+        assert!(GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .proxy(Proxy::url("ftp://proxy.corp"))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_timed_out_flush_keeps_events() {
+        // Accepts connections but never answers
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url(format!(
+                "http://{}/v1/events",
+                listener.local_addr().unwrap()
+            ))
+            .timeouts(
+                TimeoutsBuilder::default()
+                    .connect(Duration::from_millis(200))
+                    .read(Duration::from_millis(200))
+                    .build()
+                    .unwrap(),
+            )
+            .retry_policy(RetryPolicy::disabled())
+            .build()
+            .unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+        client.log_event(event);
+
+        let started = Instant::now();
+        let error = client.flush().unwrap_err();
+        assert!(matches!(error, GameEventsIOError::Timeout(_)), "{}", error);
+        assert!(error.is_retryable());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(client.pending_events_count(), 1);
+        drop(listener);
+    }
+
+    #[test]
+    fn test_rejected_events_are_requeued_or_dead_lettered() {
+        struct PartialSink;
+        impl EventSink for PartialSink {
+            fn send(
+                &self,
+                _events: &[GameEventsIOEvent],
+                _timeout: Option<Duration>,
+            ) -> Result<String, GameEventsIOError> {
+                Ok(r#"{"rejected": [
+                    {"index": 0, "code": "invalid_property", "message": "level < 0"},
+                    {"index": 2, "code": "overloaded", "retryable": true}
+                ]}"#
+                .to_string())
+            }
+        }
+
+        let dead_letters = MemorySink::new();
+        let mirror = MemorySink::new();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(PartialSink)
+            .dead_letter_sink(dead_letters.clone())
+            .mirror(mirror.clone())
+            .build()
+            .unwrap();
+        for name in ["a", "b", "c"] {
+            let event = GameEventsIOEventBuilder::default()
+                .event(name)
+                .build()
+                .unwrap();
+            client.log_event(event);
+        }
+
+        let report = client.flush().unwrap();
+        assert_eq!((report.accepted, report.rejected), (1, 2));
+        assert_eq!(report.rejections[0].code, "invalid_property");
+        assert_eq!(client.last_flush_chunks()[0].rejected, 2);
+        assert_eq!(client.last_flush_rejected().len(), 2);
+        assert_eq!(client.events[0].event, "c");
+        assert_eq!(client.pending_events_count(), 1);
+        dead_letters
+            .assert_event_logged("a", |props| props["rejection_code"] == "invalid_property");
+        assert_eq!(dead_letters.events().len(), 1);
+        assert_eq!(mirror.events()[0].event, "b");
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_file_sink_mirrors_sent_batches() {
+        let dir = std::env::temp_dir().join(format!("mirror-{}", Uuid::new_v4()));
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(FileSink::new(dir.join("primary")).rotate_daily(false))
+            .mirror(FileSink::new(dir.join("backfill")).rotate_daily(false))
+            .build()
+            .unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+        client.log_event(event);
+        client.flush().unwrap();
+
+        for sub in ["primary", "backfill"] {
+            let written = std::fs::read_to_string(dir.join(sub).join("events.ndjson")).unwrap();
+            assert_eq!(written.lines().count(), 1);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rate_limited_events_are_counted() {
+        let mut limits = HashMap::new();
+        limits.insert("footstep".into(), RateLimit::per_minute(5));
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .rate_limits(limits)
+            .build()
+            .unwrap();
+
+        for name in ["footstep"; 8].into_iter().chain(["level_up"; 8]) {
+            let event = GameEventsIOEventBuilder::default()
+                .event(name)
+                .build()
+                .unwrap();
+            client.log_event(event);
+        }
+
+        assert_eq!(client.pending_events_count(), 13);
+        assert_eq!(client.rate_limited_events()["footstep"], 3);
+        assert!(!client.rate_limited_events().contains_key("level_up"));
+    }
+
+    #[test]
+    fn test_sampling() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sample_rate(0.0)
+            .build()
+            .unwrap();
+        for _ in 0..10 {
+            let event = GameEventsIOEventBuilder::default()
+                .event("footstep")
+                .build()
+                .unwrap();
+            client.log_event(event);
+        }
+        assert_eq!(client.pending_events_count(), 0);
+        assert_eq!(client.sampled_out_events_count(), 10);
+
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sample_rate(0.5)
+            .build()
+            .unwrap();
+        for _ in 0..100 {
+            let event = GameEventsIOEventBuilder::default()
+                .event("footstep")
+                .build()
+                .unwrap();
+            client.log_event(event);
+        }
+        let kept = client.pending_events_count() as u64;
+        assert_eq!(kept + client.sampled_out_events_count(), 100);
+        assert!(client.events.iter().all(|e| e.sample_rate == Some(0.5)));
+
+        assert!(GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sample_rate(1.5)
+            .build()
+            .is_err());
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_user_deletion_purges_queued_events() {
+        let path = std::env::temp_dir().join(format!("spool-{}.spool", Uuid::new_v4()));
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .spool_path(path.clone())
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.deletion_url(),
+            "http://127.0.0.1:9/v1/deletion-requests"
+        );
+
+        for user_id in ["user_1", "user_2", "user_1"] {
+            let event = GameEventsIOEventBuilder::default()
+                .event("test_event")
+                .user_id(user_id)
+                .build()
+                .unwrap();
+            client.log_event(event);
+        }
+
+        assert!(client.request_user_deletion("user_1").is_err());
+        assert_eq!(client.pending_events_count(), 1);
+        assert_eq!(spool::load(&path).unwrap().records.len(), 1);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_health_check_unreachable() {
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .build()
+            .unwrap();
+        assert_eq!(client.health_url(), "http://127.0.0.1:9/v1/health");
+
+        let status = client.health_check();
+        assert!(!status.is_healthy());
+        assert!(!status.reachable && !status.auth_ok);
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_replay_preserves_timestamps() {
+        let sink = MemorySink::new();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(sink.clone())
+            .sample_rate(0.0)
+            .build()
+            .unwrap();
+
+        let mut archive = Vec::new();
+        for time in [1_700_000_000u64, 1_700_000_060, 1_700_000_120] {
+            let event = GameEventsIOEventBuilder::default()
+                .event("level_up")
+                .time(time)
+                .build()
+                .unwrap();
+            serde_json::to_writer(&mut archive, &event).unwrap();
+            archive.push(b'\n');
+        }
+        archive.extend_from_slice(b"not json\n");
+
+        assert_eq!(client.replay_from(archive.as_slice(), 2).unwrap(), 3);
+        assert_eq!(client.pending_events_count(), 0);
+        let times: Vec<_> = sink.events().iter().map(|e| e.time).collect();
+        assert_eq!(times, [1_700_000_000, 1_700_000_060, 1_700_000_120]);
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_spool_survives_restart() {
+        let path = std::env::temp_dir().join(format!("spool-{}.spool", Uuid::new_v4()));
+        let builder = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .spool_path(path.clone())
+            .retry_policy(RetryPolicy::disabled())
+            .clone();
+
+        let mut client = builder.build().unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+        client.log_event(event);
+        assert!(client.flush().is_err());
+        assert_eq!(client.pending_events_count(), 1);
+        drop(client);
+
+        let client = builder.build().unwrap();
+        assert_eq!(client.pending_events_count(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_custom_storage() {
+        #[derive(Default)]
+        struct MemoryStorage {
+            records: std::sync::Mutex<VecDeque<Vec<u8>>>,
+            state: std::sync::Mutex<HashMap<String, String>>,
+            acked: std::sync::Mutex<Vec<usize>>,
+        }
+        impl Storage for Arc<MemoryStorage> {
+            fn append(&self, _event: &GameEventsIOEvent, record: &[u8]) -> std::io::Result<()> {
+                self.records.lock().unwrap().push_back(record.to_vec());
+                Ok(())
+            }
+            fn read_batch(&self, offset: usize, max: usize) -> std::io::Result<Vec<Vec<u8>>> {
+                let records = self.records.lock().unwrap();
+                Ok(records.iter().skip(offset).take(max).cloned().collect())
+            }
+            fn ack(&self, count: usize) -> std::io::Result<()> {
+                self.acked.lock().unwrap().push(count);
+                let mut records = self.records.lock().unwrap();
+                let count = count.min(records.len());
+                records.drain(..count);
+                Ok(())
+            }
+            fn read_state(&self, key: &str) -> std::io::Result<Option<String>> {
+                Ok(self.state.lock().unwrap().get(key).cloned())
+            }
+            fn write_state(&self, key: &str, value: &str) -> std::io::Result<()> {
+                let mut state = self.state.lock().unwrap();
+                state.insert(key.to_string(), value.to_string());
+                Ok(())
+            }
+        }
+
+        let storage = Arc::new(MemoryStorage::default());
+        let sink = MemorySink::new();
+        let builder = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .storage(storage.clone())
+            .sink(sink.clone())
+            .clone();
+        let log = |client: &mut GameEventsIOClient, name: &'static str| {
+            let event = GameEventsIOEventBuilder::default()
+                .event(name)
+                .build()
+                .unwrap();
+            client.log_event(event);
+        };
+
+        let mut client = builder.build().unwrap();
+        let anonymous_id = client.anonymous_id().to_string();
+        log(&mut client, "level_start");
+        log(&mut client, "level_end");
+        assert_eq!(storage.records.lock().unwrap().len(), 2);
+        // Sent events are acknowledged, not rewritten
+        client.flush().unwrap();
+        assert_eq!(*storage.acked.lock().unwrap(), [2]);
+        assert!(storage.records.lock().unwrap().is_empty());
+
+        log(&mut client, "shop_open");
+        drop(client);
+        let client = builder.build().unwrap();
+        assert_eq!(client.anonymous_id(), anonymous_id);
+        assert_eq!(client.events[0].event, "shop_open");
+        assert_eq!(sink.events().len(), 2);
+
+        #[cfg(feature = "persistence")]
+        let error = builder
+            .clone()
+            .spool_path(std::env::temp_dir().join("unused.spool"))
+            .build()
+            .unwrap_err();
+        #[cfg(feature = "persistence")]
+        assert!(error.to_string().contains("set only one of"));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_spool_survives_restart() {
+        let path = std::env::temp_dir().join(format!("spool-{}.spool", Uuid::new_v4()));
+        let key = SpoolKey::generate();
+        let builder = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .spool_path(path.clone())
+            .spool_key(key)
+            .retry_policy(RetryPolicy::disabled())
+            .clone();
+
+        let mut client = builder.build().unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("purchase")
+            .user_id("user123")
+            .build()
+            .unwrap();
+        client.log_event(event);
+        drop(client);
+        let spool = std::fs::read(&path).unwrap();
+        assert!(!spool.windows(7).any(|window| window == b"user123"));
+
+        assert_eq!(builder.build().unwrap().pending_events_count(), 1);
+
+        let error = builder
+            .clone()
+            .spool_key(|| Err("keystore locked".to_string()))
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("keystore locked"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "sled")]
+    #[test]
+    fn test_sled_storage_survives_restart() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let builder = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .sled_db(db)
+            .retry_policy(RetryPolicy::disabled())
+            .clone();
+
+        let mut client = builder.build().unwrap();
+        let anonymous_id = client.anonymous_id().to_string();
+        client.set_consent(ConsentState::Granted);
+        let event = GameEventsIOEventBuilder::default()
+            .event("match_end")
+            .build()
+            .unwrap();
+        client.log_event(event);
+        assert!(client.flush().is_err());
+        drop(client);
+
+        let client = builder.build().unwrap();
+        assert_eq!(client.pending_events_count(), 1);
+        assert_eq!(client.anonymous_id(), anonymous_id);
+        assert_eq!(client.consent(), ConsentState::Granted);
+
+        let error = builder
+            .clone()
+            .spool_path(std::env::temp_dir().join("unused.spool"))
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("set only one of"));
+    }
+
+    #[cfg(all(feature = "msgpack", feature = "persistence"))]
+    #[test]
+    fn test_message_pack_flush_and_spool() {
+        #[derive(Default)]
+        struct Recorder {
+            requests: std::sync::Mutex<Vec<HttpRequest>>,
+        }
+        impl HttpTransport for Arc<Recorder> {
+            fn post(&self, request: HttpRequest) -> Result<HttpResponse, GameEventsIOError> {
+                self.requests.lock().unwrap().push(request);
+                Ok(HttpResponse {
+                    status: reqwest::StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: "ok".to_string(),
+                })
+            }
+        }
+
+        let path = std::env::temp_dir().join(format!("spool-{}.spool", Uuid::new_v4()));
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .spool_path(path.clone())
+            .build()
+            .unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+        client.log_event(event);
+        drop(client);
+
+        // JSON records are still read
+        let recorder = Arc::new(Recorder::default());
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .payload_format(PayloadFormat::MessagePack)
+            .spool_path(path.clone())
+            .transport(recorder.clone())
+            .build()
+            .unwrap();
+        assert_eq!(client.pending_events_count(), 1);
+
+        client.flush().unwrap();
+        let requests = recorder.requests.lock().unwrap();
+        assert_eq!(requests[0].headers[CONTENT_TYPE], "application/msgpack");
+        let sent: Vec<GameEventsIOEvent> = rmp_serde::from_slice(&requests[0].body).unwrap();
+        assert_eq!(sent[0].event, "test_event");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_bounded_queue_overflow() {
+        let event = |name: &'static str| {
+            GameEventsIOEventBuilder::default()
+                .event(name)
+                .build()
+                .unwrap()
+        };
+
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .max_pending_events(2usize)
+            .build()
+            .unwrap();
+        for name in ["a", "b", "c"] {
+            client.log_event(event(name));
+        }
+        assert_eq!(client.pending_events_count(), 2);
+        assert_eq!(client.dropped_events_count(), 1);
+        assert_eq!(client.events[0].event, "b");
+
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .max_pending_events(2usize)
+            .overflow_policy(OverflowPolicy::DropNewest)
+            .build()
+            .unwrap();
+        for name in ["a", "b", "c"] {
+            client.log_event(event(name));
+        }
+        assert_eq!(client.dropped_events_count(), 1);
+        assert_eq!(client.events[1].event, "b");
+    }
+
+    #[test]
+    fn test_event_ttl_and_retained_bytes() {
+        let clock = FixedClock::at_unix_secs(1_700_000_000);
+        let event = |name: &'static str, secs: u64| {
+            GameEventsIOEventBuilder::default()
+                .event(name)
+                .timestamp(UNIX_EPOCH + Duration::from_secs(secs))
+                .build()
+                .unwrap()
+        };
+        let sink = MemorySink::new();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(sink.clone())
+            .clock(clock.clone())
+            .event_ttl(Duration::from_secs(60 * 60))
+            .build()
+            .unwrap();
+        client.log_event(event("stale", 1_700_000_000 - 2 * 60 * 60));
+        client.log_event(event("fresh", 1_700_000_000 - 60));
+        client.flush().unwrap();
+        assert_eq!(sink.events().len(), 1);
+        assert_eq!(sink.events()[0].event, "fresh");
+        assert_eq!(client.metrics().expired(), 1);
+
+        // The oldest events over the budget go first
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(MemorySink::new())
+            .max_retained_bytes(usize::MAX)
+            .build()
+            .unwrap();
+        for name in ["a", "b", "c"] {
+            client.log_event(event(name, 1_700_000_000));
+        }
+        client.max_retained_bytes = Some(client.pending_bytes - 1);
+        client.prune_queue();
+        assert_eq!(client.pending_events_count(), 2);
+        assert_eq!(client.events[0].event, "b");
+        assert_eq!(client.dropped_events_count(), 1);
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_expired_events_dropped_on_load() {
+        let path = std::env::temp_dir().join(format!("spool-{}.spool", Uuid::new_v4()));
+        let clock = FixedClock::at_unix_secs(1_700_000_000);
+        let builder = || {
+            let mut builder = GameEventsIOClientBuilder::default();
+            builder
+                .api_key("test_api_key")
+                .spool_path(&path)
+                .clock(clock.clone())
+                .event_ttl(Duration::from_secs(60 * 60));
+            builder
+        };
+        let mut client = builder().build().unwrap();
+        client.log_event(
+            GameEventsIOEventBuilder::default()
+                .event("test_event")
+                .timestamp(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+                .build()
+                .unwrap(),
+        );
+        drop(client);
+
+        clock.advance(Duration::from_secs(2 * 60 * 60));
+        let client = builder().build().unwrap();
+        assert_eq!(client.pending_events_count(), 0);
+        assert_eq!(client.metrics().expired(), 1);
+        assert_eq!(spool::load(&path).unwrap().records.len(), 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_damaged_spool_is_compacted() {
+        let path = std::env::temp_dir().join(format!("spool-{}.spool", Uuid::new_v4()));
+        let builder = || {
+            let mut builder = GameEventsIOClientBuilder::default();
+            builder.api_key("test_api_key").spool_path(&path);
+            builder
+        };
+        let mut client = builder().build().unwrap();
+        for name in ["first", "second", "third"] {
+            client.log_event(
+                GameEventsIOEventBuilder::default()
+                    .event(name)
+                    .build()
+                    .unwrap(),
+            );
+        }
+        let corrupt = || {
+            let mut bytes = std::fs::read(&path).unwrap();
+            let second = bytes.windows(6).position(|w| w == b"second").unwrap();
+            bytes[second] = b'S';
+            std::fs::write(&path, &bytes).unwrap();
+        };
+
+        // A running client writes damaged records again from its queue
+        corrupt();
+        let compaction = client.compact_storage().unwrap();
+        assert_eq!((compaction.salvaged, compaction.discarded), (2, 1));
+        assert_eq!(spool::load(&path).unwrap().records.len(), 3);
+        drop(client);
+
+        corrupt();
+        let client = builder().build().unwrap();
+        assert_eq!(client.last_compaction().discarded, 1);
+        assert_eq!(client.metrics().corrupted(), 1);
+        let names: Vec<_> = client.events.iter().map(|e| e.event.as_ref()).collect();
+        assert_eq!(names, ["first", "third"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_producers_feed_the_queue() {
+        let sink = MemorySink::new();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(sink.clone())
+            .build()
+            .unwrap();
+        let producer = client.producer();
+        let threads: Vec<_> = ["physics", "audio"]
+            .into_iter()
+            .map(|system| {
+                let producer = producer.clone();
+                std::thread::spawn(move || {
+                    let event = GameEventsIOEventBuilder::default()
+                        .event(format!("{}_tick", system))
+                        .build()
+                        .unwrap();
+                    producer.log_event(event);
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(client.pending_events_count(), 0);
+        assert_eq!(client.flush().unwrap().accepted, 2);
+        sink.assert_event_logged("audio_tick", |_| true);
+        assert_eq!(client.drain_producers(), 0);
+    }
+
+    #[test]
+    fn test_try_log_event() {
+        let event = |name: &'static str| {
+            GameEventsIOEventBuilder::default()
+                .event(name)
+                .priority(EventPriority::Immediate)
+                .build()
+                .unwrap()
+        };
+
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .max_pending_events(2usize)
+            .disable_event("debug_fps")
+            .build()
+            .unwrap();
+        assert_eq!(client.try_log_event(event("a")), Ok(()));
+        assert_eq!(client.try_log_event(event("b")), Ok(()));
+        assert_eq!(
+            client.try_log_event(event("c")),
+            Err(TryLogError::QueueFull)
+        );
+        assert_eq!(
+            client.try_log_event(event("debug_fps")),
+            Err(TryLogError::Disabled)
+        );
+        // Nothing was evicted, and nothing flushed despite the priority
+        assert_eq!(client.events[0].event, "a");
+        assert_eq!(client.pending_events_count(), 2);
+        assert_eq!(client.dropped_events_count(), 0);
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_try_log_event_defers_io() {
+        let path = std::env::temp_dir().join(format!("spool-{}.spool", Uuid::new_v4()));
+        let clock = FixedClock::at_unix_secs(1_700_000_000);
+        let builder = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .spool_path(path.clone())
+            .remote_config(
+                RemoteConfigSourceBuilder::default()
+                    .ttl(Duration::from_secs(60))
+                    .build()
+                    .unwrap(),
+            )
+            .clock(clock.clone())
+            .clone();
+        let event = |name: &'static str| {
+            GameEventsIOEventBuilder::default()
+                .event(name)
+                .build()
+                .unwrap()
+        };
+        let spooled = || std::fs::metadata(&path).map_or(0, |m| m.len());
+
+        let mut client = builder.build().unwrap();
+        let fetch = client.remote_config_fetch.clone().unwrap();
+        for _ in 0..500 {
+            if fetch.lock().unwrap().is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        clock.advance(Duration::from_secs(120));
+
+        // The finished fetch is not picked up and no new one started
+        let before = spooled();
+        client.try_log_event(event("a")).unwrap();
+        assert_eq!(spooled(), before);
+        assert!(fetch.lock().unwrap().is_some());
+
+        client.log_event(event("b"));
+        assert!(spooled() > before);
+        assert!(fetch.lock().unwrap().is_none());
+        drop(client);
+
+        let client = builder.build().unwrap();
+        let names: Vec<_> = client.events.iter().map(|e| e.event.to_string()).collect();
+        assert_eq!(names, ["a", "b"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_super_properties_merge() {
+        let mut client = GameEventsIOClient::new("test_api_key");
+        client.set_super_property("platform", serde_json::json!("steam"));
+        client.set_super_property("build_number", serde_json::json!(42));
+
+        let mut props = HashMap::new();
+        props.insert("platform".into(), serde_json::json!("epic"));
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .event_properties(props)
+            .build()
+            .unwrap();
+        client.log_event(event);
+
+        let event = &client.events[0];
+        assert_eq!(event.event_properties.get("platform").unwrap(), "epic");
+        assert_eq!(event.event_properties.get("build_number").unwrap(), 42);
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_anonymous_id_and_identify() {
+        let dir = std::env::temp_dir().join(format!("state-{}", Uuid::new_v4()));
+        let builder = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .state_dir(dir.clone())
+            .clone();
+
+        let mut client = builder.build().unwrap();
+        let anonymous_id = client.anonymous_id().to_string();
+        let event = GameEventsIOEventBuilder::default()
+            .event("app_start")
+            .build()
+            .unwrap();
+        client.log_event(event);
+        assert_eq!(client.events[0].user_id, anonymous_id);
+
+        client.identify("user123");
+        let identify = &client.events[1];
+        assert_eq!(identify.event, "identify");
+        assert_eq!(identify.user_id, "user123");
+        assert_eq!(
+            identify.anonymous_id.as_deref(),
+            Some(anonymous_id.as_str())
+        );
+        drop(client);
+
+        let client = builder.build().unwrap();
+        assert_eq!(client.anonymous_id(), anonymous_id);
+        assert_eq!(client.identified_user_id(), Some("user123"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_app_info_attached_to_events_and_headers() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .app_info(
+                AppInfoBuilder::default()
+                    .app_version("1.4.2")
+                    .build_number("1234")
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+        client.log_event(event);
+        let props = &client.events[0].event_properties;
+        assert_eq!(props.get("app_version").unwrap(), "1.4.2");
+        assert_eq!(props.get("build_number").unwrap(), "1234");
+        assert!(!props.contains_key("bundle_id"));
+
+        let headers = client.request_headers();
+        assert_eq!(headers["x-app-version"], "1.4.2");
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn test_requests_are_signed_with_server_time() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .clock(FixedClock::at_unix_secs(1_700_000_000))
+            .signing_key(SigningKey::new("key-1", "secret"))
+            .build()
+            .unwrap();
+        client.clock_offset = Some(-60);
+
+        let first = client.request("http://localhost/", HeaderMap::new(), b"[]".to_vec(), None);
+        let second = client.request("http://localhost/", HeaderMap::new(), b"[]".to_vec(), None);
+        assert_eq!(first.headers["x-signature-key-id"], "key-1");
+        assert_eq!(first.headers["x-signature-timestamp"], "1699999940");
+        assert!(first.headers["x-signature"]
+            .to_str()
+            .unwrap()
+            .starts_with("v1="));
+        assert_ne!(
+            first.headers["x-signature-nonce"],
+            second.headers["x-signature-nonce"]
+        );
+    }
+
+    #[test]
+    fn test_environment_tags_events_and_picks_endpoint() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .environment("staging")
+            .environment_endpoint(
+                Environment::Staging,
+                "https://staging.example.com/v1/events",
+            )
+            .environment_endpoint("qa", "https://qa.example.com/v1/events")
+            .build()
+            .unwrap();
+        assert_eq!(client.environment(), &Environment::Staging);
+        assert_eq!(
+            client.backend_url(),
+            "https://staging.example.com/v1/events"
+        );
+
+        let event = GameEventsIOEventBuilder::default()
+            .event("test_event")
+            .build()
+            .unwrap();
+        client.log_event(event);
+        assert_eq!(client.events[0].environment.as_deref(), Some("staging"));
+
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .environment_endpoint(
+                Environment::Staging,
+                "https://staging.example.com/v1/events",
+            )
+            .build()
+            .unwrap();
+        assert_eq!(client.backend_url(), "https://api.game-events.io/v1/events");
+    }
+
+    #[test]
+    fn test_custom_headers() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .header("X-Tenant-Id", "studio-42")
+            .header("Authorization", "ignored")
+            .header_callback(move |headers: &mut HeaderMap| {
+                let call = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                headers.insert("traceparent", HeaderValue::from(call));
+            })
+            .build()
+            .unwrap();
+
+        let headers = client.request_headers();
+        assert_eq!(headers["x-tenant-id"], "studio-42");
+        assert_eq!(headers["authorization"], "Bearer test_api_key");
+        assert_eq!(headers["traceparent"], "0");
+        assert_eq!(client.base_headers()["traceparent"], "1");
+
+        assert!(GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .header("X-Tenant-Id", "line\nbreak")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_scrubber_runs_before_enqueue() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .scrubber(Scrubber::new().redact_emails())
+            .build()
+            .unwrap();
+
+        let mut props = HashMap::new();
+        props.insert("message".into(), serde_json::json!("me@example.com"));
+        let event = GameEventsIOEventBuilder::default()
+            .event("feedback")
+            .event_properties(props)
+            .build()
+            .unwrap();
+        client.log_event(event);
+
+        assert_eq!(
+            client.events[0].event_properties.get("message").unwrap(),
+            "[EMAIL]"
+        );
+    }
+
+    #[test]
+    fn test_collect_device_metadata() {
+        let mut super_properties = HashMap::new();
+        super_properties.insert("os".into(), serde_json::json!("switch"));
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .super_properties(super_properties)
+            .collect_device_metadata(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.super_properties().get("os").unwrap(), "switch");
+        assert_eq!(
+            client.super_properties().get("arch").unwrap(),
+            std::env::consts::ARCH
+        );
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_consent_states() {
+        let dir = std::env::temp_dir().join(format!("state-{}", Uuid::new_v4()));
+        let builder = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .state_dir(dir.clone())
+            .consent(ConsentState::Pending)
+            .clone();
+        let event = || {
+            GameEventsIOEventBuilder::default()
+                .event("test_event")
+                .build()
+                .unwrap()
+        };
+
+        let mut client = builder.build().unwrap();
+        client.log_event(event());
+        assert_eq!(
+            client.flush().unwrap().to_string(),
+            "Waiting for tracking consent"
+        );
+        assert_eq!(client.pending_events_count(), 1);
+
+        client.set_tracking_enabled(false);
+        assert_eq!(client.pending_events_count(), 0);
+        client.log_event(event());
+        assert_eq!(client.pending_events_count(), 0);
+        drop(client);
+
+        let client = builder.build().unwrap();
+        assert_eq!(client.consent(), ConsentState::Denied);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_session_creation() {
+        let session = GameEventsIOSession::new("user123", "session456");
+        assert_eq!(session.user_id(), "user123");
+        assert_eq!(session.session_id(), "session456");
+        assert!(session.user_properties().is_empty());
+    }
+
+    #[test]
+    fn test_session_with_user_properties() {
+        let mut user_props = HashMap::new();
+        user_props.insert("platform".into(), serde_json::json!("rust"));
+        user_props.insert("version".into(), serde_json::json!("1.0"));
+
+        let session = GameEventsIOSessionBuilder::default()
+            .user_id("user123")
+            .session_id("session456")
+            .user_properties(user_props)
+            .build()
+            .unwrap();
+
+        assert_eq!(session.user_properties().len(), 2);
+    }
+
+    #[test]
+    fn test_session_event_creation() {
+        let mut session = GameEventsIOSession::new("user123", "session456");
+        session.push_event("test_event", HashMap::new());
+
+        // We expect 2 events now: new_session + test_event
+        let events = session.take_events(2);
+        assert_eq!(events.len(), 2);
+
+        // Check the second event (the one we added)
+        let event = &events[1];
+
+        assert_eq!(event.event, "test_event");
+        assert_eq!(event.user_id, "user123");
+        assert_eq!(event.session_id, "session456");
+    }
+
+    #[test]
+    fn test_session_set_user_property() {
+        let mut session = GameEventsIOSession::new("user123", "session456");
+        session.set_user_property("platform", serde_json::json!("rust"));
+        session.set_user_property("level", serde_json::json!(10));
+
+        assert_eq!(session.user_properties().len(), 2);
+
+        // Events created after setting properties should include them
         session.push_event("test_event", HashMap::new());
 
         // We expect 2 events: new_session (no props) + test_event (with props)
@@ -356,6 +5331,28 @@ mod tests {
         assert_eq!(event.user_properties.get("platform").unwrap(), "rust");
     }
 
+    #[test]
+    fn test_session_user_property_ops() {
+        let mut session = GameEventsIOSession::new("user123", "session456");
+        session.increment_user_property("matches_played", 1);
+        session.set_once_user_property("first_seen_version", serde_json::json!("1.2.0"));
+        // An event that fails validation leaves them for the next one
+        assert!(session
+            .try_push_event("match end!", HashMap::new())
+            .is_err());
+        session.push_event("match_end", HashMap::new());
+        session.push_event("test_event", HashMap::new());
+
+        let events = session.take_events(3);
+        assert_eq!(events[1].user_property_ops.len(), 2);
+        assert_eq!(
+            events[1].user_property_ops[0].op,
+            UserPropertyOp::Increment(1.into())
+        );
+        // Operations are sent exactly once
+        assert!(events[2].user_property_ops.is_empty());
+    }
+
     #[test]
     fn test_session_defaults() {
         let session = GameEventsIOSession::default();
@@ -364,7 +5361,7 @@ mod tests {
         // Simple check to see if it looks like a UUID (36 chars)
         assert_eq!(session.user_id().len(), 36);
         assert_eq!(session.session_id().len(), 36);
-        
+
         // Should have 1 event automatically
         assert_eq!(session.events.len(), 1);
         assert_eq!(session.events[0].event, "new_session");
@@ -375,11 +5372,8 @@ mod tests {
         let mut session = GameEventsIOSession::new("default_user", "default_session");
 
         let mut props = HashMap::new();
-        props.insert("user_id".to_string(), serde_json::json!("custom_user"));
-        props.insert(
-            "session_id".to_string(),
-            serde_json::json!("custom_session"),
-        );
+        props.insert("user_id".into(), serde_json::json!("custom_user"));
+        props.insert("session_id".into(), serde_json::json!("custom_session"));
 
         session.push_event("test_event", props);
 
@@ -391,16 +5385,615 @@ mod tests {
         assert_eq!(event.session_id, "custom_session");
     }
 
+    #[test]
+    fn test_session_push_purchase() {
+        let mut session = GameEventsIOSession::new("user123", "session456");
+        let purchase = PurchaseEventBuilder::default()
+            .item_id("sword_legendary")
+            .price(9.99)
+            .currency("USD")
+            .quantity(2u32)
+            .transaction_id("txn_123")
+            .build()
+            .unwrap();
+        session.push_purchase(purchase);
+
+        let events = session.take_events(2);
+        let event = &events[1];
+        assert_eq!(event.event, "purchase");
+        assert_eq!(event.event_properties.get("currency").unwrap(), "USD");
+        assert_eq!(event.event_properties.get("quantity").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_session_ad_event() {
+        let mut session = GameEventsIOSession::new("user123", "session456");
+        let ad = AdEventBuilder::default()
+            .network("admob")
+            .placement("level_end_rewarded")
+            .estimated_revenue(0.012)
+            .build()
+            .unwrap();
+        session.ad_event(AdAction::RewardGranted, ad);
+
+        let events = session.take_events(2);
+        let event = &events[1];
+        assert_eq!(event.event, "ad_reward_granted");
+        assert_eq!(event.event_properties.get("network").unwrap(), "admob");
+        assert!(!event.event_properties.contains_key("revenue_currency"));
+    }
+
+    #[test]
+    fn test_session_progression() {
+        let mut session = GameEventsIOSession::new("user123", "session456");
+        let step = ProgressionEventBuilder::default()
+            .world("world_1")
+            .level("level_3")
+            .score(1500i64)
+            .attempts(2u32)
+            .build()
+            .unwrap();
+        session.progression(ProgressionStatus::Complete, step);
+
+        let events = session.take_events(2);
+        let event = &events[1];
+        assert_eq!(event.event, "progression_complete");
+        assert_eq!(
+            event.event_properties.get("progression_id").unwrap(),
+            "world_1/level_3"
+        );
+        assert_eq!(event.event_properties.get("score").unwrap(), 1500);
+    }
+
+    #[test]
+    fn test_session_resource_event() {
+        let mut session = GameEventsIOSession::new("user123", "session456");
+        let resource = ResourceEventBuilder::default()
+            .currency("gems")
+            .amount(50.0)
+            .item_type("shop")
+            .item_id("extra_life")
+            .build()
+            .unwrap();
+        session.resource_event(FlowType::Sink, resource);
+
+        let events = session.take_events(2);
+        let event = &events[1];
+        assert_eq!(event.event, "resource_sink");
+        assert_eq!(event.event_properties.get("currency").unwrap(), "gems");
+        assert_eq!(event.event_properties.get("amount").unwrap(), 50.0);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_session_typed_event() {
+        #[derive(Serialize, TypedEvent)]
+        struct LevelCompleted {
+            level: u32,
+            duration_seconds: f64,
+        }
+
+        #[derive(Serialize, TypedEvent)]
+        #[event(name = "boss.defeated")]
+        struct BossDefeated {
+            boss: String,
+        }
+
+        let mut session = GameEventsIOSession::new("user123", "session456");
+        session.push_typed(&LevelCompleted {
+            level: 3,
+            duration_seconds: 42.5,
+        });
+        session.push_typed(&BossDefeated {
+            boss: "dragon".to_string(),
+        });
+
+        let events = session.take_events(3);
+        assert_eq!(events[1].event, "level_completed");
+        assert_eq!(events[1].event_properties.get("level").unwrap(), 3);
+        assert_eq!(events[2].event, "boss.defeated");
+        assert_eq!(events[2].event_properties.get("boss").unwrap(), "dragon");
+    }
+
+    #[test]
+    fn test_session_start_rotates_after_idle() {
+        let mut session = GameEventsIOSession::start("user123");
+        let first_session_id = session.session_id().to_string();
+        assert_eq!(first_session_id.len(), 36);
+
+        session.push_event("test_event", HashMap::new());
+        assert_eq!(session.session_id(), first_session_id);
+
+        session.last_activity = Instant::now() - DEFAULT_IDLE_TIMEOUT;
+        session.push_event("test_event", HashMap::new());
+        assert_ne!(session.session_id(), first_session_id);
+
+        let events = session.take_events(10);
+        let names: Vec<_> = events.iter().map(|e| e.event.as_ref()).collect();
+        assert_eq!(
+            names,
+            [
+                "new_session",
+                "test_event",
+                "session_end",
+                "new_session",
+                "test_event"
+            ]
+        );
+        assert_eq!(events[2].session_id, first_session_id);
+        assert_eq!(events[4].session_id, session.session_id());
+
+        // Sequence numbers restart with each session_id
+        let sequences: Vec<_> = events.iter().map(|e| e.sequence.unwrap()).collect();
+        assert_eq!(sequences, [1, 2, 3, 1, 2]);
+        assert_eq!(events[0].session_epoch, events[2].session_epoch);
+        assert!(events[3].session_epoch >= events[2].session_epoch);
+    }
+
+    #[test]
+    fn test_session_time_zone() {
+        let mut session = GameEventsIOSession::new("user123", "session456");
+        let detected = LocalTimeZone::detect(SystemTime::now());
+        let event = &session.take_events(1)[0];
+        assert_eq!(event.time_zone, detected.name);
+        assert_eq!(event.utc_offset_minutes, detected.utc_offset_minutes);
+
+        let mut session = GameEventsIOSessionBuilder::default()
+            .report_time_zone(false)
+            .build()
+            .unwrap();
+        session.push_event("test_event", HashMap::new());
+        let event = &session.take_events(1)[0];
+        assert_eq!(event.time_zone, None);
+        assert_eq!(event.utc_offset_minutes, None);
+    }
+
+    #[test]
+    fn test_session_end() {
+        let mut session = GameEventsIOSession::new("user123", "session456");
+        session.push_event("test_event", HashMap::new());
+        session.end();
+        session.end();
+
+        let events = session.take_events(10);
+        assert_eq!(events.len(), 3);
+        let end = &events[2];
+        assert_eq!(end.event, "session_end");
+        assert_eq!(end.session_id, "session456");
+        assert_eq!(end.event_properties.get("event_count").unwrap(), 2);
+        assert!(end.event_properties.contains_key("duration_seconds"));
+
+        // Logging after end starts a new session
+        session.push_event("test_event", HashMap::new());
+        assert_ne!(session.session_id(), "session456");
+        assert_eq!(session.take_events(10)[0].event, "new_session");
+    }
+
+    #[test]
+    fn test_session_ends_on_drop() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .build()
+            .unwrap();
+        let mut session = GameEventsIOSessionBuilder::default()
+            .user_id("user123")
+            .session_id("session456")
+            .producer(client.producer())
+            .build()
+            .unwrap();
+        session.push_event("test_event", HashMap::new());
+
+        // A copy is not bound to the producer
+        drop(session.clone());
+        assert_eq!(client.drain_producers(), 0);
+
+        drop(session);
+        assert_eq!(client.drain_producers(), 2);
+        let end = &client.events[1];
+        assert_eq!(end.event, "session_end");
+        assert_eq!(end.session_id, "session456");
+        assert_eq!(end.event_properties.get("event_count").unwrap(), 1);
+
+        // An ended session is not ended again
+        let mut session = GameEventsIOSessionBuilder::default()
+            .producer(client.producer())
+            .build()
+            .unwrap();
+        session.end();
+        drop(session);
+        assert_eq!(client.drain_producers(), 1);
+    }
+
     #[test]
     fn test_new_session_event_auto_added() {
         let session = GameEventsIOSession::new("user123", "session456");
-        
+
         // Should have 1 event automatically
         assert_eq!(session.events.len(), 1);
-        
+
         let event = &session.events[0];
         assert_eq!(event.event, "new_session");
         assert_eq!(event.session_id, "session456");
-        assert_eq!(event.event_properties.get("session_id").unwrap(), "session456");
+        assert_eq!(
+            event.event_properties.get("session_id").unwrap(),
+            "session456"
+        );
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_remote_config_is_applied_and_cached() {
+        struct ConfigServer;
+        impl HttpTransport for ConfigServer {
+            fn post(&self, request: HttpRequest) -> Result<HttpResponse, GameEventsIOError> {
+                let body = match request.url.ends_with("/config") {
+                    true => {
+                        r#"{"config":{"disabled_events":["debug_ping"],"event_sample_rates":{"footstep":0.0},"flush_interval":60}}"#
+                    }
+                    false => "ok",
+                };
+                Ok(HttpResponse {
+                    status: reqwest::StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: body.to_string(),
+                })
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("remote-config-{}", Uuid::new_v4()));
+        let source = RemoteConfigSourceBuilder::default()
+            .url("https://api.game-events.io/v1/config")
+            .build()
+            .unwrap();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .transport(ConfigServer)
+            .remote_config(source.clone())
+            .state_dir(&dir)
+            .build()
+            .unwrap();
+        // Building the client started a fetch in the background
+        assert!(client.remote_config_fetch.is_some());
+        for _ in 0..500 {
+            client.revalidate_remote_config();
+            if client.remote_config_fetch.is_none() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(client.remote_settings().flush_interval, Some(60));
+
+        // A fresh client uses the cached document without fetching it again
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .transport(ConfigServer)
+            .remote_config(source.clone())
+            .state_dir(&dir)
+            .build()
+            .unwrap();
+        assert!(client.remote_config_fetch.is_none());
+        for name in ["debug_ping", "footstep", "level_up"] {
+            let event = GameEventsIOEventBuilder::default()
+                .event(name)
+                .build()
+                .unwrap();
+            client.log_event(event);
+        }
+        assert_eq!(client.disabled_events_count(), 1);
+        assert_eq!(client.sampled_out_events_count(), 1);
+        assert_eq!(client.pending_events_count(), 1);
+
+        // A stale document is used while it is refreshed, an expired one is not
+        let stale = RemoteConfigSource {
+            url: Some("http://127.0.0.1:9/v1/config".to_string()),
+            ttl: Duration::ZERO,
+            ..source
+        };
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .remote_config(stale.clone())
+            .state_dir(&dir)
+            .build()
+            .unwrap();
+        assert!(client.remote_config_fetch.is_some());
+        assert_eq!(client.remote_settings().flush_interval, Some(60));
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .remote_config(RemoteConfigSource {
+                max_stale: Some(Duration::ZERO),
+                ..stale
+            })
+            .state_dir(&dir)
+            .build()
+            .unwrap();
+        assert_eq!(client.remote_settings(), &RemoteSettings::default());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_remote_config_stale_while_revalidate() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::Mutex;
+
+        /// Serves `flush_interval` set to `version`, or an error for 0;
+        /// requests wait while `gate` is held
+        #[derive(Clone, Default)]
+        struct ConfigServer {
+            version: Arc<AtomicU64>,
+            gate: Arc<Mutex<()>>,
+        }
+        impl HttpTransport for ConfigServer {
+            fn post(&self, _request: HttpRequest) -> Result<HttpResponse, GameEventsIOError> {
+                let _open = self.gate.lock().unwrap();
+                let (status, body) = match self.version.load(Ordering::SeqCst) {
+                    0 => (reqwest::StatusCode::SERVICE_UNAVAILABLE, String::new()),
+                    version => (
+                        reqwest::StatusCode::OK,
+                        format!(r#"{{"config":{{"flush_interval":{}}}}}"#, version),
+                    ),
+                };
+                Ok(HttpResponse {
+                    status,
+                    headers: HeaderMap::new(),
+                    body,
+                })
+            }
+        }
+        let settle = |client: &mut GameEventsIOClient| {
+            for _ in 0..500 {
+                client.revalidate_remote_config();
+                if client.remote_config_fetch.is_none() {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            panic!("remote config fetch did not finish");
+        };
+
+        let server = ConfigServer::default();
+        server.version.store(60, Ordering::SeqCst);
+        let clock = FixedClock::at_unix_secs(1_700_000_000);
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .transport(server.clone())
+            .clock(clock.clone())
+            .remote_config(
+                RemoteConfigSourceBuilder::default()
+                    .url("https://api.game-events.io/v1/config")
+                    .ttl(Duration::from_secs(60 * 60))
+                    .max_stale(Duration::from_secs(60 * 60))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        settle(&mut client);
+        assert_eq!(client.remote_settings().flush_interval, Some(60));
+
+        // A fresh document is not fetched again
+        clock.advance(Duration::from_secs(30 * 60));
+        client.revalidate_remote_config();
+        assert!(client.remote_config_fetch.is_none());
+
+        // A stale one stays in use while the refresh is in flight
+        server.version.store(120, Ordering::SeqCst);
+        let held = server.gate.lock().unwrap();
+        clock.advance(Duration::from_secs(31 * 60));
+        client.revalidate_remote_config();
+        assert!(client.remote_config_fetch.is_some());
+        std::thread::sleep(Duration::from_millis(20));
+        client.revalidate_remote_config();
+        assert_eq!(client.remote_settings().flush_interval, Some(60));
+
+        // and is swapped out once the refresh completes
+        drop(held);
+        settle(&mut client);
+        assert_eq!(client.remote_settings().flush_interval, Some(120));
+
+        // Failed refreshes keep a stale document until `max_stale` runs out
+        server.version.store(0, Ordering::SeqCst);
+        clock.advance(Duration::from_secs(90 * 60));
+        settle(&mut client);
+        assert_eq!(client.remote_settings().flush_interval, Some(120));
+        clock.advance(Duration::from_secs(31 * 60));
+        client.revalidate_remote_config();
+        assert_eq!(client.remote_settings(), &RemoteSettings::default());
+    }
+
+    #[test]
+    fn test_disabled_events_are_suppressed() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .disable_event("debug_ping")
+            .build()
+            .unwrap();
+        let log = |client: &mut GameEventsIOClient, name: &'static str| {
+            let event = GameEventsIOEventBuilder::default()
+                .event(name)
+                .build()
+                .unwrap();
+            client.log_event(event);
+        };
+        log(&mut client, "debug_ping");
+        client.disable_event("frame_time");
+        log(&mut client, "frame_time");
+        log(&mut client, "frame_time");
+        log(&mut client, "level_up");
+        assert_eq!(client.pending_events_count(), 1);
+        assert_eq!(client.suppressed_events()["frame_time"], 2);
+        assert_eq!(client.disabled_events_count(), 3);
+
+        assert!(client.enable_event("frame_time"));
+        assert!(!client.is_event_disabled("frame_time"));
+        log(&mut client, "frame_time");
+        assert_eq!(client.pending_events_count(), 2);
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_experiment_variants() {
+        struct ExperimentServer;
+        impl HttpTransport for ExperimentServer {
+            fn post(&self, request: HttpRequest) -> Result<HttpResponse, GameEventsIOError> {
+                assert!(request.url.ends_with("/v1/experiments"));
+                let body = match serde_json::from_slice(&request.body).unwrap() {
+                    serde_json::Value::Object(o) if o["experiment_key"] == "new_shop" => {
+                        r#"{"variants":[{"name":"control"},{"name":"treatment"}]}"#
+                    }
+                    _ => r#"{"variants":[]}"#,
+                };
+                Ok(HttpResponse {
+                    status: reqwest::StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: body.to_string(),
+                })
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("experiments-{}", Uuid::new_v4()));
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .transport(ExperimentServer)
+            .state_dir(&dir)
+            .build()
+            .unwrap();
+        let variant = client.get_variant("new_shop", "user-1").unwrap().unwrap();
+        assert_eq!(client.super_properties()["experiment_new_shop"], variant);
+        assert_eq!(client.get_variant("old_test", "user-1").unwrap(), None);
+        assert!(!client
+            .super_properties()
+            .contains_key("experiment_old_test"));
+
+        // Offline, the stored definition gives the same assignment
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .state_dir(&dir)
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.get_variant("new_shop", "user-1").unwrap(),
+            Some(variant)
+        );
+        assert!(client.get_variant("unknown", "user-1").is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_flag_evaluation_logs_exposures() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .build()
+            .unwrap();
+        client.remote_settings = RemoteConfigSourceBuilder::default()
+            .url("https://api.game-events.io/v1/config")
+            .build()
+            .unwrap()
+            .parse(r#"{"config":{"flags":{"new_shop":{"enabled":true,"rollout":0,"users":["tester"]}}}}"#)
+            .unwrap();
+
+        let tester = FlagContext::user("tester");
+        assert!(client.is_enabled("new_shop", &tester));
+        assert!(client.is_enabled("new_shop", &tester));
+        assert!(!client.is_enabled("new_shop", &FlagContext::new()));
+        assert!(!client.is_enabled("unknown", &tester));
+
+        let exposures: Vec<_> = client.events.iter().collect();
+        assert_eq!(exposures.len(), 2);
+        assert_eq!(exposures[0].event, FLAG_EXPOSURE_EVENT);
+        assert_eq!(exposures[0].user_id, "tester");
+        assert_eq!(exposures[0].event_properties["enabled"], true);
+        assert_eq!(exposures[1].user_id, client.anonymous_id());
+        assert_eq!(exposures[1].event_properties["flag"], "new_shop");
+
+        // The reported evaluations are bounded
+        client.events.clear();
+        for user in 0..MAX_FLAG_EXPOSURES + 10 {
+            client.is_enabled("new_shop", &FlagContext::user(user.to_string()));
+        }
+        assert!(client.flag_exposures.len() <= MAX_FLAG_EXPOSURES);
+        assert_eq!(client.pending_events_count(), MAX_FLAG_EXPOSURES + 10);
+    }
+
+    #[test]
+    fn test_cohorts_are_cached() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Default)]
+        struct CohortServer {
+            requests: AtomicUsize,
+        }
+        impl HttpTransport for Arc<CohortServer> {
+            fn post(&self, request: HttpRequest) -> Result<HttpResponse, GameEventsIOError> {
+                assert!(request.url.ends_with("/v1/cohorts"));
+                // Only the first request succeeds
+                if self.requests.fetch_add(1, Ordering::SeqCst) > 0 {
+                    return Err(GameEventsIOError::Transport("offline".into()));
+                }
+                Ok(HttpResponse {
+                    status: reqwest::StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: r#"{"cohorts":["lapsed_spenders"]}"#.to_string(),
+                })
+            }
+        }
+
+        let server = Arc::new(CohortServer::default());
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .transport(server.clone())
+            .build()
+            .unwrap();
+        let mut session = GameEventsIOSession::new("user123", "session456");
+        assert_eq!(
+            client.attach_cohorts(&mut session).unwrap(),
+            ["lapsed_spenders"]
+        );
+        assert_eq!(
+            session.user_properties()[COHORTS_PROPERTY],
+            serde_json::json!(["lapsed_spenders"])
+        );
+        client.cohorts("user123").unwrap();
+        assert_eq!(server.requests.load(Ordering::SeqCst), 1);
+
+        // Once expired, a failed refresh falls back to the cached list
+        client.cohort_ttl = Duration::ZERO;
+        assert_eq!(client.cohorts("user123").unwrap(), ["lapsed_spenders"]);
+        assert_eq!(server.requests.load(Ordering::SeqCst), 2);
+        assert!(client.cohorts("someone_else").is_err());
+    }
+
+    #[test]
+    fn test_region_picks_every_endpoint() {
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .region(Region::Eu)
+            .remote_config(RemoteConfigSourceBuilder::default().build().unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.backend_url(),
+            "https://eu.api.game-events.io/v1/events"
+        );
+        assert_eq!(
+            client.deletion_url(),
+            "https://eu.api.game-events.io/v1/deletion-requests"
+        );
+        assert_eq!(
+            client.remote_config.as_ref().unwrap().url(),
+            "https://eu.api.game-events.io/v1/config"
+        );
+        assert!(client.cohorts_url().starts_with("https://eu."));
+        assert!(client.experiments_url().starts_with("https://eu."));
+
+        let error = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .region(Region::Eu)
+            .backend_url("https://api.game-events.io/v1/events")
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("conflicts"), "{}", error);
     }
 }
@@ -0,0 +1,153 @@
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::{EventSink, GameEventsIOError, GameEventsIOEvent, Properties};
+
+/// Keeps flushed events in memory so tests can assert on them
+///
+/// Clones share the same storage: give one clone to
+/// [`GameEventsIOClientBuilder::sink`] and inspect the other after flushing.
+/// Nothing is sent over the network.
+///
+/// [`GameEventsIOClientBuilder::sink`]: crate::GameEventsIOClientBuilder::sink
+#[derive(Clone, Default)]
+pub struct MemorySink {
+    events: Arc<Mutex<Vec<GameEventsIOEvent>>>,
+}
+
+impl MemorySink {
+    /// Create an empty sink
+    pub fn new() -> Self {
+        MemorySink::default()
+    }
+
+    /// Get every event received so far, in the order they were flushed
+    pub fn events(&self) -> Vec<GameEventsIOEvent> {
+        self.lock().clone()
+    }
+
+    /// Get the events received for `user_id`
+    pub fn events_for_user(&self, user_id: &str) -> Vec<GameEventsIOEvent> {
+        self.lock()
+            .iter()
+            .filter(|event| event.user_id == user_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Get the events received with the name `event`
+    pub fn events_named(&self, event: &str) -> Vec<GameEventsIOEvent> {
+        self.lock()
+            .iter()
+            .filter(|e| e.event == event)
+            .cloned()
+            .collect()
+    }
+
+    /// Forget every event received so far
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Panic unless an event named `event` whose properties satisfy
+    /// `matcher` was received
+    ///
+    /// Pass `|_| true` to only check the name.
+    #[track_caller]
+    pub fn assert_event_logged(
+        &self,
+        event: &str,
+        matcher: impl Fn(&Properties) -> bool,
+    ) {
+        let events = self.lock();
+        if events
+            .iter()
+            .any(|e| e.event == event && matcher(&e.event_properties))
+        {
+            return;
+        }
+        let names: Vec<_> = events.iter().map(|e| e.event.as_ref()).collect();
+        panic!(
+            "no matching {:?} event was logged; received: {:?}",
+            event, names
+        );
+    }
+
+    /// Panic if an event named `event` was received
+    #[track_caller]
+    pub fn assert_event_not_logged(&self, event: &str) {
+        let count = self.lock().iter().filter(|e| e.event == event).count();
+        assert!(
+            count == 0,
+            "expected no {:?} event, but {} were logged",
+            event,
+            count
+        );
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<GameEventsIOEvent>> {
+        self.events.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for MemorySink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemorySink")
+            .field("events", &self.lock().len())
+            .finish()
+    }
+}
+
+impl EventSink for MemorySink {
+    fn send(
+        &self,
+        events: &[GameEventsIOEvent],
+        _timeout: Option<Duration>,
+    ) -> Result<String, GameEventsIOError> {
+        self.lock().extend_from_slice(events);
+        Ok(format!("Recorded {} events", events.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameEventsIOClientBuilder, GameEventsIOEventBuilder};
+
+    #[test]
+    fn test_records_flushed_events() {
+        let sink = MemorySink::new();
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .sink(sink.clone())
+            .build()
+            .unwrap();
+        for (user_id, level) in [("user_1", 3), ("user_2", 4)] {
+            let mut props = Properties::new();
+            props.insert("level".into(), serde_json::json!(level));
+            let event = GameEventsIOEventBuilder::default()
+                .event("level_completed")
+                .user_id(user_id)
+                .event_properties(props)
+                .build()
+                .unwrap();
+            client.log_event(event);
+        }
+        client.flush().unwrap();
+
+        sink.assert_event_logged("level_completed", |props| props["level"] == 4);
+        sink.assert_event_not_logged("purchase");
+        assert_eq!(sink.events_for_user("user_1").len(), 1);
+        assert_eq!(sink.events_named("level_completed").len(), 2);
+
+        sink.clear();
+        assert!(sink.events().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "no matching \"level_completed\" event")]
+    fn test_assert_event_logged_panics() {
+        MemorySink::new().assert_event_logged("level_completed", |_| true);
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Running counters of what a client did with its events
+///
+/// The counters are atomics, so the `Arc` returned by
+/// [`metrics`](crate::GameEventsIOClient::metrics) can be read from another
+/// thread, e.g. a debug overlay, while the client runs on a worker.
+#[derive(Debug, Default)]
+pub struct ClientMetrics {
+    enqueued: AtomicU64,
+    sent: AtomicU64,
+    retried: AtomicU64,
+    sampled_out: AtomicU64,
+    dropped: AtomicU64,
+    expired: AtomicU64,
+    corrupted: AtomicU64,
+    flush_failures: AtomicU64,
+    queue_depth: AtomicU64,
+    last_flush_latency_micros: AtomicU64,
+}
+
+impl ClientMetrics {
+    /// Events added to the queue, including ones restored from the spool
+    pub fn enqueued(&self) -> u64 {
+        self.enqueued.load(Ordering::Relaxed)
+    }
+
+    /// Events the backend, or the sink, accepted
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// Requests sent again after a retryable failure
+    pub fn retried(&self) -> u64 {
+        self.retried.load(Ordering::Relaxed)
+    }
+
+    /// Events discarded by sampling
+    pub fn sampled_out(&self) -> u64 {
+        self.sampled_out.load(Ordering::Relaxed)
+    }
+
+    /// Events discarded because the queue was full, or held more than
+    /// `max_retained_bytes`
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Events discarded unsent because they outlived `event_ttl`
+    pub fn expired(&self) -> u64 {
+        self.expired.load(Ordering::Relaxed)
+    }
+
+    /// Stored records discarded because they were damaged, e.g. by a crash
+    /// mid-write
+    pub fn corrupted(&self) -> u64 {
+        self.corrupted.load(Ordering::Relaxed)
+    }
+
+    /// Flushes that returned an error
+    pub fn flush_failures(&self) -> u64 {
+        self.flush_failures.load(Ordering::Relaxed)
+    }
+
+    /// Events waiting to be sent
+    pub fn queue_depth(&self) -> u64 {
+        self.queue_depth.load(Ordering::Relaxed)
+    }
+
+    /// Time the most recent flush that sent something waited for responses;
+    /// zero before the first
+    pub fn last_flush_latency(&self) -> Duration {
+        Duration::from_micros(self.last_flush_latency_micros.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn add_enqueued(&self) {
+        self.enqueued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_sent(&self, events: usize) {
+        self.sent.fetch_add(events as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_retried(&self, retries: u32) {
+        self.retried
+            .fetch_add(u64::from(retries), Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_sampled_out(&self) {
+        self.sampled_out.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_expired(&self, events: usize) {
+        self.expired.fetch_add(events as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_corrupted(&self, records: usize) {
+        self.corrupted.fetch_add(records as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_flush_failure(&self) {
+        self.flush_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_queue_depth(&self, events: usize) {
+        self.queue_depth.store(events as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_last_flush_latency(&self, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.last_flush_latency_micros
+            .store(micros, Ordering::Relaxed);
+    }
+}
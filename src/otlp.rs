@@ -0,0 +1,199 @@
+//! OpenTelemetry exporter speaking OTLP/HTTP with JSON encoding
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use serde_json::{json, Value};
+
+use crate::{EventSink, GameEventsIOError, GameEventsIOEvent};
+
+/// OTLP severity number for INFO
+const SEVERITY_INFO: u8 = 9;
+
+/// Ships events to an OpenTelemetry collector as OTLP log records
+///
+/// Each event becomes a log record whose body is the event name, with the
+/// ids as `user.id`, `session.id` and `anonymous.id` attributes and the
+/// properties as `event_properties.*` and `user_properties.*` attributes.
+///
+/// The exporter sends blocking requests, so use it with `flush` rather than
+/// `flush_async`.
+#[derive(Debug)]
+pub struct OtlpSink {
+    url: String,
+    service_name: String,
+    headers: HeaderMap,
+    client: OnceLock<reqwest::blocking::Client>,
+}
+
+impl OtlpSink {
+    /// Export to the collector at `endpoint`, e.g. "http://localhost:4318"
+    ///
+    /// Records are posted to `<endpoint>/v1/logs`.
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        let endpoint = endpoint.into();
+        OtlpSink {
+            url: format!("{}/v1/logs", endpoint.trim_end_matches('/')),
+            service_name: "game".to_string(),
+            headers: HeaderMap::new(),
+            client: OnceLock::new(),
+        }
+    }
+
+    /// Set the `service.name` resource attribute (default: "game")
+    pub fn service_name(self, service_name: impl Into<String>) -> Self {
+        OtlpSink {
+            service_name: service_name.into(),
+            ..self
+        }
+    }
+
+    /// Add a header sent with every export, e.g. a collector API key
+    ///
+    /// Headers that are not valid HTTP headers are ignored.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            self.headers.insert(name, value);
+        }
+        self
+    }
+
+    /// Build the `ExportLogsServiceRequest` body for a batch
+    fn export_request(&self, events: &[GameEventsIOEvent]) -> Value {
+        json!({
+            "resourceLogs": [{
+                "resource": {
+                    "attributes": [attribute("service.name", &Value::from(self.service_name.as_str()))]
+                },
+                "scopeLogs": [{
+                    "scope": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION")
+                    },
+                    "logRecords": events.iter().map(log_record).collect::<Vec<_>>()
+                }]
+            }]
+        })
+    }
+}
+
+impl EventSink for OtlpSink {
+    fn send(
+        &self,
+        events: &[GameEventsIOEvent],
+        timeout: Option<Duration>,
+    ) -> Result<String, GameEventsIOError> {
+        let body = serde_json::to_vec(&self.export_request(events))?;
+        let mut headers = self.headers.clone();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        crate::post_blocking(
+            self.client.get_or_init(crate::new_blocking_client),
+            &self.url,
+            headers,
+            body,
+            timeout,
+        )
+    }
+}
+
+fn log_record(event: &GameEventsIOEvent) -> Value {
+    let mut attributes = vec![
+        attribute("event.name", &Value::from(event.event.as_ref())),
+        attribute("user.id", &Value::from(event.user_id.as_str())),
+        attribute("session.id", &Value::from(event.session_id.as_str())),
+    ];
+    if let Some(id) = &event.anonymous_id {
+        attributes.push(attribute("anonymous.id", &Value::from(id.as_str())));
+    }
+    for (prefix, properties) in [
+        ("event_properties", &event.event_properties),
+        ("user_properties", &event.user_properties),
+    ] {
+        let mut keys: Vec<_> = properties.keys().collect();
+        keys.sort();
+        for key in keys {
+            attributes.push(attribute(&format!("{}.{}", prefix, key), &properties[key]));
+        }
+    }
+
+    let time_nanos = u128::from(event.time) * 1_000_000_000;
+    json!({
+        "timeUnixNano": time_nanos.to_string(),
+        "severityNumber": SEVERITY_INFO,
+        "severityText": "INFO",
+        "body": {"stringValue": event.event},
+        "attributes": attributes
+    })
+}
+
+fn attribute(key: &str, value: &Value) -> Value {
+    json!({"key": key, "value": any_value(value)})
+}
+
+/// Map a JSON value to an OTLP `AnyValue`
+fn any_value(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({}),
+        Value::Bool(b) => json!({"boolValue": b}),
+        // 64-bit integers are strings in the OTLP JSON encoding
+        Value::Number(n) if n.is_i64() => json!({"intValue": n.to_string()}),
+        Value::Number(n) => json!({"doubleValue": n.as_f64()}),
+        Value::String(s) => json!({"stringValue": s}),
+        Value::Array(items) => {
+            json!({"arrayValue": {"values": items.iter().map(any_value).collect::<Vec<_>>()}})
+        }
+        Value::Object(map) => json!({
+            "kvlistValue": {
+                "values": map.iter().map(|(k, v)| attribute(k, v)).collect::<Vec<_>>()
+            }
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_export_request() {
+        let mut props = HashMap::new();
+        props.insert("level".into(), json!(5));
+        props.insert("tags".into(), json!(["boss"]));
+        let event = GameEventsIOEventBuilder::default()
+            .event("level_completed")
+            .user_id("user123")
+            .session_id("session456")
+            .time(1_700_000_000u64)
+            .event_properties(props)
+            .build()
+            .unwrap();
+
+        let sink = OtlpSink::new("http://localhost:4318/").service_name("my-game");
+        assert_eq!(sink.url, "http://localhost:4318/v1/logs");
+
+        let request = sink.export_request(&[event]);
+        let resource = &request["resourceLogs"][0];
+        assert_eq!(
+            resource["resource"]["attributes"][0]["value"]["stringValue"],
+            "my-game"
+        );
+        let record = &resource["scopeLogs"][0]["logRecords"][0];
+        assert_eq!(record["timeUnixNano"], "1700000000000000000");
+        assert_eq!(record["body"]["stringValue"], "level_completed");
+        let attributes = record["attributes"].as_array().unwrap();
+        assert!(attributes.contains(&json!({
+            "key": "event_properties.level",
+            "value": {"intValue": "5"}
+        })));
+        assert!(attributes.contains(&json!({
+            "key": "event_properties.tags",
+            "value": {"arrayValue": {"values": [{"stringValue": "boss"}]}}
+        })));
+    }
+}
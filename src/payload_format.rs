@@ -0,0 +1,68 @@
+use std::io::Write;
+
+use serde::Serialize;
+
+/// Wire format of flush payloads and of the spool file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PayloadFormat {
+    /// JSON, sent as `application/json`
+    #[default]
+    Json,
+
+    /// MessagePack with named fields, sent as `application/msgpack`; usually
+    /// well under half the size of the JSON for property-heavy events
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+}
+
+impl PayloadFormat {
+    /// Value for the `Content-Type` header of flush requests
+    pub(crate) fn content_type(self) -> &'static str {
+        match self {
+            PayloadFormat::Json => "application/json",
+            #[cfg(feature = "msgpack")]
+            PayloadFormat::MessagePack => "application/msgpack",
+        }
+    }
+
+    /// Serialize `value` into `writer`
+    pub(crate) fn write<T: Serialize + ?Sized>(
+        self,
+        writer: impl Write,
+        value: &T,
+    ) -> Result<(), serde_json::Error> {
+        match self {
+            PayloadFormat::Json => serde_json::to_writer(writer, value),
+            #[cfg(feature = "msgpack")]
+            PayloadFormat::MessagePack => {
+                let mut writer = writer;
+                rmp_serde::encode::write_named(&mut writer, value).map_err(serde::ser::Error::custom)
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "msgpack"))]
+mod tests {
+    use super::*;
+    use crate::{GameEventsIOEvent, GameEventsIOEventBuilder};
+
+    #[test]
+    fn test_message_pack_round_trip() {
+        let event = GameEventsIOEventBuilder::default()
+            .event("inventory_changed")
+            .user_id("user123")
+            .property("slots", serde_json::json!([{"item": "sword", "count": 1}]))
+            .build()
+            .unwrap();
+        let events = vec![event; 10];
+
+        let mut body = Vec::new();
+        PayloadFormat::MessagePack.write(&mut body, &events).unwrap();
+        assert!(body.len() < crate::chunk::json_len(&events));
+
+        let decoded: Vec<GameEventsIOEvent> = rmp_serde::from_slice(&body).unwrap();
+        assert_eq!(decoded[9].event, "inventory_changed");
+        assert_eq!(decoded[9].event_properties, events[9].event_properties);
+    }
+}
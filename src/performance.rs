@@ -0,0 +1,137 @@
+//! Periodic client performance samples fed by the game loop
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::GameEventsIOSession;
+
+/// Name of the events logged by [`PerformanceReporter`]
+pub const PERFORMANCE_SAMPLE_EVENT: &str = "performance_sample";
+
+/// Aggregates frame times and logs a `performance_sample` event per interval
+///
+/// The interval is measured in accumulated frame time, so a sample always
+/// covers the frames it describes. Each event carries `fps`, `frame_count`,
+/// `frame_time_avg_ms`, `frame_time_p50_ms`, `frame_time_p95_ms`,
+/// `frame_time_p99_ms`, `frame_time_max_ms` and, on Linux, `rss_bytes`.
+#[derive(Clone, Debug)]
+pub struct PerformanceReporter {
+    interval: Duration,
+    frame_times: Vec<Duration>,
+    elapsed: Duration,
+}
+
+impl PerformanceReporter {
+    /// Log a sample every `interval` of frame time
+    pub fn new(interval: Duration) -> Self {
+        PerformanceReporter {
+            interval,
+            frame_times: Vec::new(),
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Record how long the last frame took, logging a sample once the
+    /// interval is full
+    pub fn record_frame(&mut self, session: &mut GameEventsIOSession, frame_time: Duration) {
+        self.frame_times.push(frame_time);
+        self.elapsed += frame_time;
+        if self.elapsed >= self.interval {
+            self.report(session);
+        }
+    }
+
+    /// Log a sample for the frames recorded so far, e.g. before shutting down
+    pub fn report(&mut self, session: &mut GameEventsIOSession) {
+        if self.frame_times.is_empty() {
+            return;
+        }
+        let mut frame_times = std::mem::take(&mut self.frame_times);
+        let elapsed = std::mem::take(&mut self.elapsed);
+        frame_times.sort_unstable();
+
+        let count = frame_times.len();
+        let millis = |d: Duration| d.as_secs_f64() * 1000.0;
+        let percentile =
+            |p: f64| millis(frame_times[((count as f64 * p).ceil() as usize).max(1) - 1]);
+
+        let mut props = HashMap::new();
+        let fps = if elapsed.is_zero() {
+            0.0
+        } else {
+            count as f64 / elapsed.as_secs_f64()
+        };
+        props.insert("fps".into(), serde_json::json!(fps));
+        props.insert("frame_count".into(), serde_json::json!(count));
+        props.insert(
+            "frame_time_avg_ms".into(),
+            serde_json::json!(millis(elapsed) / count as f64),
+        );
+        props.insert(
+            "frame_time_p50_ms".into(),
+            serde_json::json!(percentile(0.50)),
+        );
+        props.insert(
+            "frame_time_p95_ms".into(),
+            serde_json::json!(percentile(0.95)),
+        );
+        props.insert(
+            "frame_time_p99_ms".into(),
+            serde_json::json!(percentile(0.99)),
+        );
+        props.insert(
+            "frame_time_max_ms".into(),
+            serde_json::json!(millis(frame_times[count - 1])),
+        );
+        if let Some(rss) = resident_set_size() {
+            props.insert("rss_bytes".into(), serde_json::json!(rss));
+        }
+        session.push_event(PERFORMANCE_SAMPLE_EVENT, props);
+    }
+}
+
+/// Resident memory of this process, where the platform makes it cheap to read
+#[cfg(target_os = "linux")]
+fn resident_set_size() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resident_set_size() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOSessionBuilder;
+
+    #[test]
+    fn test_reports_percentiles_per_interval() {
+        let mut session = GameEventsIOSessionBuilder::default().build().unwrap();
+        let mut reporter = PerformanceReporter::new(Duration::from_secs(1));
+
+        // 90 frames at 10ms and 2 at 50ms add up to exactly one second
+        for _ in 0..90 {
+            reporter.record_frame(&mut session, Duration::from_millis(10));
+        }
+        assert!(session.take_events(usize::MAX).is_empty());
+        for _ in 0..2 {
+            reporter.record_frame(&mut session, Duration::from_millis(50));
+        }
+
+        let events = session.take_events(usize::MAX);
+        assert_eq!(events.len(), 1);
+        let props = &events[0].event_properties;
+        assert_eq!(events[0].event, PERFORMANCE_SAMPLE_EVENT);
+        assert_eq!(props["frame_count"], 92);
+        assert_eq!(props["fps"], 92.0);
+        assert_eq!(props["frame_time_p50_ms"], 10.0);
+        assert_eq!(props["frame_time_p95_ms"], 10.0);
+        assert_eq!(props["frame_time_p99_ms"], 50.0);
+        assert_eq!(props["frame_time_max_ms"], 50.0);
+    }
+}
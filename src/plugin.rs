@@ -0,0 +1,50 @@
+use std::fmt;
+
+use crate::{FlushReport, GameEventsIOError, GameEventsIOEvent};
+
+/// Hooks the client runs on every event, in the order plugins were added
+///
+/// Plugins are shared behind an `Arc`, so hooks take `&self`; use interior
+/// mutability for plugins that keep state.
+pub trait ClientPlugin: Send + Sync {
+    /// Name shown in the client's `Debug` output
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Called when an event is logged, after super properties are merged and
+    /// before it is scrubbed and queued
+    ///
+    /// Return `false` to drop the event; later plugins do not see it.
+    fn before_enqueue(&self, event: &mut GameEventsIOEvent) -> bool {
+        let _ = event;
+        true
+    }
+
+    /// Called with each batch right before it is sent
+    ///
+    /// Events removed from the batch are dropped. A batch whose send fails is
+    /// returned to the queue as modified here and passed to this hook again on
+    /// the next flush.
+    fn before_flush(&self, batch: &mut Vec<GameEventsIOEvent>) {
+        let _ = batch;
+    }
+
+    /// Called after every flush that did not fail, including ones with
+    /// nothing to send and ones run by a background worker
+    fn on_flush_success(&self, report: &FlushReport) {
+        let _ = report;
+    }
+
+    /// Called after every flush that failed, including ones the circuit
+    /// breaker or throttle refused to attempt
+    fn on_flush_failure(&self, error: &GameEventsIOError) {
+        let _ = error;
+    }
+}
+
+impl fmt::Debug for dyn ClientPlugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
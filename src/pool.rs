@@ -0,0 +1,112 @@
+use std::fmt::Write;
+
+use uuid::Uuid;
+
+use crate::{EventPriority, GameEventsIOEvent};
+
+/// Sent events kept for reuse, so logging at a high rate does not allocate a
+/// fresh event, id and property maps for every event
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EventPool {
+    events: Vec<GameEventsIOEvent>,
+    capacity: usize,
+}
+
+impl EventPool {
+    pub(crate) fn new(capacity: usize) -> Self {
+        EventPool {
+            events: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// An empty event with a fresh `event_id`, reusing a recycled one's
+    /// allocations if there is one
+    pub(crate) fn take(&mut self) -> GameEventsIOEvent {
+        let mut event = self.events.pop().unwrap_or_default();
+        event.event_id.clear();
+        let _ = write!(event.event_id, "{}", Uuid::new_v4());
+        event
+    }
+
+    /// Keep sent events for [`take`](Self::take), up to the pool's capacity
+    pub(crate) fn recycle(&mut self, events: impl IntoIterator<Item = GameEventsIOEvent>) {
+        let room = self.capacity.saturating_sub(self.events.len());
+        self.events.extend(events.into_iter().take(room).map(reset));
+    }
+}
+
+/// Clear an event, keeping the capacity of its strings and collections
+fn reset(mut event: GameEventsIOEvent) -> GameEventsIOEvent {
+    let GameEventsIOEvent {
+        event_id,
+        event: name,
+        user_id,
+        session_id,
+        time,
+        utc_offset_minutes,
+        time_zone,
+        event_properties,
+        user_properties,
+        anonymous_id,
+        user_property_ops,
+        sample_rate,
+        sequence,
+        session_epoch,
+        corrected_time,
+        priority,
+        environment,
+        project,
+    } = &mut event;
+    event_id.clear();
+    *name = "".into();
+    user_id.clear();
+    session_id.clear();
+    *time = 0;
+    *utc_offset_minutes = None;
+    *time_zone = None;
+    event_properties.clear();
+    user_properties.clear();
+    *anonymous_id = None;
+    user_property_ops.clear();
+    *sample_rate = None;
+    *sequence = None;
+    *session_epoch = None;
+    *corrected_time = None;
+    *priority = EventPriority::Normal;
+    *environment = None;
+    *project = None;
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+
+    #[test]
+    fn test_recycled_events_keep_their_allocations() {
+        let mut pool = EventPool::new(1);
+        let event = GameEventsIOEventBuilder::default()
+            .event("frame_stats")
+            .user_id("user123")
+            .property("fps", 58)
+            .build()
+            .unwrap();
+        let first_id = event.event_id.clone();
+        pool.recycle([event.clone(), event]);
+        assert_eq!(pool.events.len(), 1);
+
+        let event = pool.take();
+        assert_eq!(pool.events.len(), 0);
+        assert!(event.event.is_empty() && event.user_id.is_empty());
+        assert!(event.user_id.capacity() > 0);
+        assert!(event.event_properties.is_empty());
+        assert!(event.event_properties.capacity() > 0);
+        assert_eq!(event.event_id.len(), first_id.len());
+        assert_ne!(event.event_id, first_id);
+
+        // An empty pool hands out new events
+        assert_eq!(pool.take().user_id.capacity(), 0);
+    }
+}
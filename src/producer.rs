@@ -0,0 +1,74 @@
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::GameEventsIOEvent;
+
+/// Cheap-to-clone sender of events into a client's queue
+///
+/// Game systems can log through a producer without access to the client or
+/// a lock on it. Events wait in a channel until the client next logs or
+/// flushes, or [`drain_producers`](crate::GameEventsIOClient::drain_producers)
+/// is called, and then go through sampling, plugins and the queue limits like
+/// any other event.
+#[derive(Clone, Debug)]
+pub struct GameEventsIOProducer {
+    sender: Sender<GameEventsIOEvent>,
+}
+
+impl GameEventsIOProducer {
+    /// Send an event to the client; never blocks
+    pub fn log_event(&self, event: GameEventsIOEvent) {
+        let _ = self.sender.send(event);
+    }
+}
+
+/// Producer a session sends `session_end` and its untaken events to when it
+/// is dropped
+///
+/// A clone is unbound, so dropping a copy of a session does not end it.
+#[derive(Debug)]
+pub(crate) struct DropProducer(pub(crate) Option<GameEventsIOProducer>);
+
+impl Clone for DropProducer {
+    fn clone(&self) -> Self {
+        DropProducer(None)
+    }
+}
+
+/// The channel behind a client's producers
+#[derive(Clone)]
+pub(crate) struct ProducerChannel {
+    sender: Sender<GameEventsIOEvent>,
+    receiver: Arc<Mutex<Receiver<GameEventsIOEvent>>>,
+}
+
+impl Default for ProducerChannel {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        ProducerChannel {
+            sender,
+            receiver: Arc::new(Mutex::new(receiver)),
+        }
+    }
+}
+
+impl fmt::Debug for ProducerChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProducerChannel")
+    }
+}
+
+impl ProducerChannel {
+    pub(crate) fn producer(&self) -> GameEventsIOProducer {
+        GameEventsIOProducer {
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// Take every event sent so far
+    pub(crate) fn drain(&self) -> Vec<GameEventsIOEvent> {
+        let receiver = self.receiver.lock().unwrap_or_else(PoisonError::into_inner);
+        receiver.try_iter().collect()
+    }
+}
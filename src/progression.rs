@@ -0,0 +1,112 @@
+use serde::Serialize;
+
+/// Outcome of a progression step, logged with [`GameEventsIOSession::progression`]
+///
+/// [`GameEventsIOSession::progression`]: crate::GameEventsIOSession::progression
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressionStatus {
+    Start,
+    Complete,
+    Fail,
+}
+
+impl ProgressionStatus {
+    /// Event name used for this status
+    pub fn event_name(self) -> &'static str {
+        match self {
+            ProgressionStatus::Start => "progression_start",
+            ProgressionStatus::Complete => "progression_complete",
+            ProgressionStatus::Fail => "progression_fail",
+        }
+    }
+}
+
+/// A step in a world / level / phase hierarchy
+#[derive(Clone, Debug, Builder, Serialize)]
+#[builder(setter(into))]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct ProgressionEvent {
+    /// Top level of the hierarchy, e.g. "world_1"
+    pub world: String,
+
+    /// Level within the world
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+
+    /// Phase within the level
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+
+    /// Score reached, usually sent with Complete or Fail
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<i64>,
+
+    /// How many times the player has attempted this step
+    #[builder(setter(strip_option))]
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u32>,
+}
+
+impl ProgressionEvent {
+    /// Canonical id of this step, e.g. "world_1/level_3/boss"
+    pub fn progression_id(&self) -> String {
+        [Some(&self.world), self.level.as_ref(), self.phase.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+impl ProgressionEventBuilder {
+    fn validate(&self) -> Result<(), String> {
+        let level = self.level.as_ref().and_then(|l| l.as_deref());
+        let phase = self.phase.as_ref().and_then(|p| p.as_deref());
+        if phase.is_some() && level.is_none() {
+            return Err("phase requires a level".to_string());
+        }
+        let names = [self.world.as_deref(), level, phase];
+        if names
+            .into_iter()
+            .flatten()
+            .any(|name| name.is_empty() || name.contains('/'))
+        {
+            return Err("progression names must be non-empty and must not contain '/'".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progression_id_and_validation() {
+        let step = ProgressionEventBuilder::default()
+            .world("world_1")
+            .level("level_3")
+            .phase("boss")
+            .build()
+            .unwrap();
+        assert_eq!(step.progression_id(), "world_1/level_3/boss");
+
+        assert!(ProgressionEventBuilder::default()
+            .world("world_1")
+            .phase("boss")
+            .build()
+            .is_err());
+        assert!(ProgressionEventBuilder::default()
+            .world("world/1")
+            .build()
+            .is_err());
+    }
+}
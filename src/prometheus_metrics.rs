@@ -0,0 +1,189 @@
+//! Prometheus export of a client's metrics
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use prometheus::core::{Collector, Desc, Describer};
+use prometheus::proto::MetricFamily;
+use prometheus::{Gauge, IntCounter, IntGauge, Opts};
+
+use crate::ClientMetrics;
+
+type Read = fn(&ClientMetrics) -> u64;
+
+const COUNTERS: [(&str, &str, Read); 8] = [
+    (
+        "game_events_enqueued_total",
+        "Events added to the queue",
+        ClientMetrics::enqueued,
+    ),
+    (
+        "game_events_sent_total",
+        "Events the backend accepted",
+        ClientMetrics::sent,
+    ),
+    (
+        "game_events_retried_total",
+        "Requests sent again after a retryable failure",
+        ClientMetrics::retried,
+    ),
+    (
+        "game_events_sampled_out_total",
+        "Events discarded by sampling",
+        ClientMetrics::sampled_out,
+    ),
+    (
+        "game_events_dropped_total",
+        "Events discarded because the queue was full",
+        ClientMetrics::dropped,
+    ),
+    (
+        "game_events_expired_total",
+        "Events discarded because they outlived their TTL",
+        ClientMetrics::expired,
+    ),
+    (
+        "game_events_corrupted_total",
+        "Stored records discarded because they were damaged",
+        ClientMetrics::corrupted,
+    ),
+    (
+        "game_events_flush_failures_total",
+        "Flushes that returned an error",
+        ClientMetrics::flush_failures,
+    ),
+];
+
+const QUEUE_DEPTH: (&str, &str) = ("game_events_queue_depth", "Events waiting to be sent");
+
+const LAST_FLUSH_LATENCY: (&str, &str) = (
+    "game_events_last_flush_latency_seconds",
+    "Time the most recent flush waited for responses",
+);
+
+/// Exposes a client's [`ClientMetrics`] to a `prometheus::Registry`
+///
+/// Values are read when the registry is gathered, so nothing has to be
+/// updated as the client runs.
+///
+/// ```ignore
+/// let registry = prometheus::Registry::new();
+/// registry.register(Box::new(
+///     PrometheusCollector::new(client.metrics()).label("project", "farm-game"),
+/// ))?;
+/// ```
+#[derive(Debug)]
+pub struct PrometheusCollector {
+    metrics: Arc<ClientMetrics>,
+    labels: HashMap<String, String>,
+    descs: Vec<Desc>,
+}
+
+impl PrometheusCollector {
+    pub fn new(metrics: Arc<ClientMetrics>) -> Self {
+        let mut collector = PrometheusCollector {
+            metrics,
+            labels: HashMap::new(),
+            descs: Vec::new(),
+        };
+        collector.descs = collector.build_descs();
+        collector
+    }
+
+    /// Add a constant label to every metric, e.g. to tell the clients of
+    /// several projects apart in one registry
+    pub fn label(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(name.into(), value.into());
+        self.descs = self.build_descs();
+        self
+    }
+
+    fn opts(&self, (name, help): (&str, &str)) -> Opts {
+        Opts::new(name, help).const_labels(self.labels.clone())
+    }
+
+    fn build_descs(&self) -> Vec<Desc> {
+        let names = COUNTERS
+            .iter()
+            .map(|(name, help, _)| (*name, *help))
+            .chain([QUEUE_DEPTH, LAST_FLUSH_LATENCY]);
+        names
+            .filter_map(|metric| self.opts(metric).describe().ok())
+            .collect()
+    }
+}
+
+impl Collector for PrometheusCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.descs.iter().collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let mut families = Vec::new();
+        for (name, help, read) in COUNTERS {
+            if let Ok(counter) = IntCounter::with_opts(self.opts((name, help))) {
+                counter.inc_by(read(&self.metrics));
+                families.extend(counter.collect());
+            }
+        }
+        if let Ok(gauge) = IntGauge::with_opts(self.opts(QUEUE_DEPTH)) {
+            gauge.set(i64::try_from(self.metrics.queue_depth()).unwrap_or(i64::MAX));
+            families.extend(gauge.collect());
+        }
+        if let Ok(gauge) = Gauge::with_opts(self.opts(LAST_FLUSH_LATENCY)) {
+            gauge.set(self.metrics.last_flush_latency().as_secs_f64());
+            families.extend(gauge.collect());
+        }
+        families
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gather() {
+        let metrics = Arc::new(ClientMetrics::default());
+        metrics.add_enqueued();
+        metrics.add_dropped();
+        metrics.set_queue_depth(1);
+
+        let registry = prometheus::Registry::new();
+        registry
+            .register(Box::new(
+                PrometheusCollector::new(Arc::clone(&metrics)).label("project", "farm-game"),
+            ))
+            .unwrap();
+        let families = registry.gather();
+        let value = |name: &str| {
+            let family = families.iter().find(|f| f.name() == name).unwrap();
+            let metric = &family.get_metric()[0];
+            assert_eq!(metric.get_label()[0].value(), "farm-game");
+            metric
+        };
+        assert_eq!(
+            value("game_events_enqueued_total")
+                .get_counter()
+                .get_value(),
+            1.0
+        );
+        assert_eq!(
+            value("game_events_dropped_total").get_counter().get_value(),
+            1.0
+        );
+        assert_eq!(
+            value("game_events_queue_depth").get_gauge().get_value(),
+            1.0
+        );
+
+        // Values are read at gather time
+        metrics.add_enqueued();
+        let families = registry.gather();
+        let enqueued = families
+            .iter()
+            .find(|f| f.name() == "game_events_enqueued_total")
+            .unwrap();
+        assert_eq!(enqueued.get_metric()[0].get_counter().get_value(), 2.0);
+    }
+}
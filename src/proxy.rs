@@ -0,0 +1,147 @@
+/// Username and password for a proxy
+pub(crate) type Credentials = (String, String);
+
+/// Proxy the client's HTTP transport connects through
+///
+/// Set it with [`GameEventsIOClientBuilder::proxy`] for the default reqwest
+/// transport, or pass it to `UreqTransport::with_proxy` and
+/// `HyperTransport::with_proxy`. Proxies do not apply in the browser.
+///
+/// [`GameEventsIOClientBuilder::proxy`]: crate::GameEventsIOClientBuilder::proxy
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Proxy {
+    /// Use the proxy named by `HTTPS_PROXY`, `HTTP_PROXY` or `ALL_PROXY`
+    /// (or their lowercase forms), if any
+    #[default]
+    FromEnvironment,
+
+    /// Connect directly, even if proxy variables are set
+    Direct,
+
+    /// Send every request through the proxy at `url`, e.g.
+    /// "http://proxy.corp:3128" or, with the `socks` feature,
+    /// "socks5://proxy.corp:1080"
+    Url {
+        url: String,
+
+        /// Username and password for the proxy
+        credentials: Option<Credentials>,
+    },
+}
+
+impl Proxy {
+    /// Send every request through the proxy at `url`
+    pub fn url(url: impl Into<String>) -> Self {
+        Proxy::Url {
+            url: url.into(),
+            credentials: None,
+        }
+    }
+
+    /// Authenticate to the proxy with a username and password
+    ///
+    /// Has no effect on [`Proxy::FromEnvironment`] and [`Proxy::Direct`];
+    /// put credentials for a proxy from the environment in its URL.
+    pub fn basic_auth(self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        match self {
+            Proxy::Url { url, .. } => Proxy::Url {
+                url,
+                credentials: Some((username.into(), password.into())),
+            },
+            other => other,
+        }
+    }
+
+    /// The proxy URL and credentials to connect through, if any
+    ///
+    /// Credentials in the URL are split out and take precedence. Invalid
+    /// proxy variables are ignored, as other HTTP clients do.
+    pub(crate) fn resolve(
+        &self,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<Option<(reqwest::Url, Option<Credentials>)>, String> {
+        let (raw, credentials) = match self {
+            Proxy::Direct => return Ok(None),
+            Proxy::Url { url, credentials } => (url.clone(), credentials.clone()),
+            Proxy::FromEnvironment => {
+                let from_env = [
+                    "HTTPS_PROXY",
+                    "https_proxy",
+                    "ALL_PROXY",
+                    "all_proxy",
+                    "HTTP_PROXY",
+                    "http_proxy",
+                ]
+                .into_iter()
+                .filter_map(&var)
+                .find(|value| !value.is_empty());
+                match from_env.and_then(|value| parse_url(&value).ok()) {
+                    Some(url) => return Ok(Some(split_credentials(url))),
+                    None => return Ok(None),
+                }
+            }
+        };
+        let (url, from_url) = split_credentials(parse_url(&raw)?);
+        Ok(Some((url, from_url.or(credentials))))
+    }
+}
+
+/// Parse a proxy URL, assuming `http://` when no scheme is given
+pub(crate) fn parse_url(raw: &str) -> Result<reqwest::Url, String> {
+    let with_scheme = if raw.contains("://") {
+        raw.to_string()
+    } else {
+        format!("http://{}", raw)
+    };
+    let url = reqwest::Url::parse(&with_scheme)
+        .map_err(|e| format!("invalid proxy URL {:?}: {}", raw, e))?;
+    if url.host_str().is_none() {
+        return Err(format!("invalid proxy URL {:?}: missing host", raw));
+    }
+    Ok(url)
+}
+
+/// Remove the username and password from a URL
+fn split_credentials(mut url: reqwest::Url) -> (reqwest::Url, Option<Credentials>) {
+    if url.username().is_empty() {
+        return (url, None);
+    }
+    let credentials = (
+        url.username().to_string(),
+        url.password().unwrap_or_default().to_string(),
+    );
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    (url, Some(credentials))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let no_env = |_: &str| None;
+        assert_eq!(Proxy::Direct.resolve(no_env).unwrap(), None);
+        assert_eq!(Proxy::FromEnvironment.resolve(no_env).unwrap(), None);
+
+        let (url, credentials) = Proxy::url("proxy.corp:3128")
+            .basic_auth("alice", "secret")
+            .resolve(no_env)
+            .unwrap()
+            .unwrap();
+        assert_eq!(url.as_str(), "http://proxy.corp:3128/");
+        assert_eq!(credentials, Some(("alice".into(), "secret".into())));
+
+        let env = |key: &str| match key {
+            "https_proxy" => Some("http://bob:pw@10.0.0.1:8080".to_string()),
+            "HTTP_PROXY" => Some("http://ignored:8080".to_string()),
+            _ => None,
+        };
+        let (url, credentials) = Proxy::FromEnvironment.resolve(env).unwrap().unwrap();
+        assert_eq!(url.as_str(), "http://10.0.0.1:8080/");
+        assert_eq!(credentials, Some(("bob".into(), "pw".into())));
+
+        assert!(Proxy::url("http://").resolve(no_env).is_err());
+    }
+}
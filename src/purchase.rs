@@ -0,0 +1,87 @@
+use serde::Serialize;
+
+/// A validated in-app purchase, logged with [`GameEventsIOSession::push_purchase`]
+///
+/// [`GameEventsIOSession::push_purchase`]: crate::GameEventsIOSession::push_purchase
+#[derive(Clone, Debug, Builder, Serialize)]
+#[builder(setter(into))]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct PurchaseEvent {
+    /// Store identifier of the purchased item
+    pub item_id: String,
+
+    /// Price of a single item
+    pub price: f64,
+
+    /// ISO 4217 currency code, e.g. "USD"
+    pub currency: String,
+
+    /// Number of items bought
+    pub quantity: u32,
+
+    /// Store transaction identifier, used to de-duplicate receipts
+    pub transaction_id: String,
+}
+
+impl PurchaseEventBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(item_id) = &self.item_id {
+            if item_id.is_empty() {
+                return Err("item_id must not be empty".to_string());
+            }
+        }
+        if let Some(price) = self.price {
+            if !price.is_finite() || price < 0.0 {
+                return Err(format!(
+                    "price must be a non-negative number, got {}",
+                    price
+                ));
+            }
+        }
+        if let Some(currency) = &self.currency {
+            if currency.len() != 3 || !currency.bytes().all(|b| b.is_ascii_uppercase()) {
+                return Err(format!(
+                    "currency must be a three-letter ISO 4217 code, got {:?}",
+                    currency
+                ));
+            }
+        }
+        if self.quantity == Some(0) {
+            return Err("quantity must be at least 1".to_string());
+        }
+        if let Some(transaction_id) = &self.transaction_id {
+            if transaction_id.is_empty() {
+                return Err("transaction_id must not be empty".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> PurchaseEventBuilder {
+        PurchaseEventBuilder::default()
+            .item_id("sword_legendary")
+            .price(9.99)
+            .currency("USD")
+            .quantity(1u32)
+            .transaction_id("txn_123")
+            .clone()
+    }
+
+    #[test]
+    fn test_purchase_validation() {
+        assert!(builder().build().is_ok());
+        assert!(builder().currency("usd").build().is_err());
+        assert!(builder().currency("EURO").build().is_err());
+        assert!(builder().price(-1.0).build().is_err());
+        assert!(builder().quantity(0u32).build().is_err());
+        assert!(PurchaseEventBuilder::default()
+            .item_id("sword_legendary")
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use web_time::Instant;
+
+/// Client-side limit on how often an event name may be logged
+///
+/// Implemented as a token bucket: up to `burst` events can be logged at once,
+/// after which tokens refill at `max_per_minute`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Sustained number of events allowed per minute
+    pub max_per_minute: u32,
+
+    /// Number of events allowed in a single burst
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// Allow `max_per_minute` events per minute, all of which may arrive at once
+    pub fn per_minute(max_per_minute: u32) -> Self {
+        RateLimit {
+            max_per_minute,
+            burst: max_per_minute,
+        }
+    }
+
+    /// Change the burst size
+    pub fn with_burst(self, burst: u32) -> Self {
+        RateLimit { burst, ..self }
+    }
+}
+
+/// Token bucket tracking one event name
+#[derive(Clone, Debug)]
+pub(crate) struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    pub fn new(limit: &RateLimit, now: Instant) -> Self {
+        Bucket {
+            tokens: f64::from(limit.burst),
+            refilled_at: now,
+        }
+    }
+
+    /// Take a token if one is available
+    pub fn try_acquire(&mut self, limit: &RateLimit, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        let refill = elapsed.as_secs_f64() * f64::from(limit.max_per_minute) / 60.0;
+        self.tokens = (self.tokens + refill).min(f64::from(limit.burst));
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bucket_allows_burst_then_refills() {
+        let limit = RateLimit::per_minute(60).with_burst(3);
+        let start = Instant::now();
+        let mut bucket = Bucket::new(&limit, start);
+
+        assert!((0..3).all(|_| bucket.try_acquire(&limit, start)));
+        assert!(!bucket.try_acquire(&limit, start));
+
+        // One token per second at 60 per minute
+        let later = start + Duration::from_millis(1500);
+        assert!(bucket.try_acquire(&limit, later));
+        assert!(!bucket.try_acquire(&limit, later));
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+
+/// Where a client's data is ingested and stored, for data residency
+///
+/// The region picks the events endpoint, and the deletion, health,
+/// experiments, cohorts and remote configuration endpoints are derived from
+/// it, so an EU build needs nothing but [`Region::Eu`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Region {
+    #[default]
+    Us,
+    Eu,
+    Apac,
+
+    /// Events endpoint of a self-hosted or dedicated deployment
+    Custom(String),
+}
+
+impl Region {
+    /// Events endpoint of the region
+    pub fn events_url(&self) -> &str {
+        match self {
+            Region::Us => "https://api.game-events.io/v1/events",
+            Region::Eu => "https://eu.api.game-events.io/v1/events",
+            Region::Apac => "https://apac.api.game-events.io/v1/events",
+            Region::Custom(url) => url,
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Region::Us => f.write_str("us"),
+            Region::Eu => f.write_str("eu"),
+            Region::Apac => f.write_str("apac"),
+            Region::Custom(url) => f.write_str(url),
+        }
+    }
+}
+
+/// "us", "eu" and "apac" in any case; anything else is taken as a custom
+/// events endpoint
+impl From<&str> for Region {
+    fn from(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "us" => Region::Us,
+            "eu" => Region::Eu,
+            "apac" => Region::Apac,
+            _ => Region::Custom(name.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(Region::from("EU"), Region::Eu);
+        assert_eq!(Region::from("apac").to_string(), "apac");
+        assert_eq!(
+            Region::from("https://collector.local/v1/events").events_url(),
+            "https://collector.local/v1/events"
+        );
+        assert_eq!(
+            Region::default().events_url(),
+            "https://api.game-events.io/v1/events"
+        );
+    }
+}
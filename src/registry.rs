@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use web_time::Instant;
+
+use crate::{FlushReport, GameEventsIOClient, GameEventsIOError, GameEventsIOEvent};
+
+/// Clients for several projects (API keys) in one process
+///
+/// Each project has its own client, and so its own queue, retry state and
+/// settings. Events go to the client of their `project` tag, set per event
+/// or by a [`GameEventsIOSession`](crate::GameEventsIOSession) with a
+/// project; untagged events go to the default project.
+#[derive(Debug, Default)]
+pub struct GameEventsIORegistry {
+    clients: BTreeMap<String, GameEventsIOClient>,
+    default_project: Option<String>,
+    unrouted_events: u64,
+}
+
+impl GameEventsIORegistry {
+    pub fn new() -> Self {
+        GameEventsIORegistry::default()
+    }
+
+    /// Send events tagged with `project` through `client`, returning the
+    /// client previously registered for it
+    pub fn register(
+        &mut self,
+        project: impl Into<String>,
+        client: GameEventsIOClient,
+    ) -> Option<GameEventsIOClient> {
+        self.clients.insert(project.into(), client)
+    }
+
+    /// Stop routing events to `project` and return its client, e.g. to flush
+    /// it one last time
+    pub fn unregister(&mut self, project: &str) -> Option<GameEventsIOClient> {
+        self.clients.remove(project)
+    }
+
+    /// Send events without a project tag to `project`
+    pub fn set_default_project(&mut self, project: impl Into<String>) {
+        self.default_project = Some(project.into());
+    }
+
+    /// The registered projects, in order
+    pub fn projects(&self) -> impl Iterator<Item = &str> {
+        self.clients.keys().map(String::as_str)
+    }
+
+    pub fn client(&self, project: &str) -> Option<&GameEventsIOClient> {
+        self.clients.get(project)
+    }
+
+    pub fn client_mut(&mut self, project: &str) -> Option<&mut GameEventsIOClient> {
+        self.clients.get_mut(project)
+    }
+
+    /// Log an event on the client of its project
+    ///
+    /// Events for a project that is not registered, and untagged events
+    /// without a default project, are dropped and counted by
+    /// [`unrouted_events_count`](Self::unrouted_events_count).
+    pub fn log_event(&mut self, event: GameEventsIOEvent) {
+        let project = event.project.as_ref().or(self.default_project.as_ref());
+        match project.and_then(|project| self.clients.get_mut(project)) {
+            Some(client) => client.log_event(event),
+            None => self.unrouted_events += 1,
+        }
+    }
+
+    /// Get the number of events dropped because no client was registered
+    /// for their project
+    pub fn unrouted_events_count(&self) -> u64 {
+        self.unrouted_events
+    }
+
+    /// Get the number of events buffered across all projects
+    pub fn pending_events_count(&self) -> usize {
+        self.clients
+            .values()
+            .map(GameEventsIOClient::pending_events_count)
+            .sum()
+    }
+
+    /// Flush every project's client; one failing does not stop the others
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn flush(&mut self) -> BTreeMap<String, Result<FlushReport, GameEventsIOError>> {
+        self.clients
+            .iter_mut()
+            .map(|(project, client)| (project.clone(), client.flush()))
+            .collect()
+    }
+
+    /// Flush every project's client before the game exits, all within
+    /// `deadline`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn shutdown(
+        &mut self,
+        deadline: Duration,
+    ) -> BTreeMap<String, Result<FlushReport, GameEventsIOError>> {
+        let deadline = Instant::now() + deadline;
+        self.clients
+            .iter_mut()
+            .map(|(project, client)| {
+                let left = deadline.saturating_duration_since(Instant::now());
+                (project.clone(), client.shutdown(left))
+            })
+            .collect()
+    }
+
+    /// Flush every project's client without blocking the current thread
+    #[cfg(any(feature = "async", target_arch = "wasm32"))]
+    pub async fn flush_async(
+        &mut self,
+    ) -> BTreeMap<String, Result<FlushReport, GameEventsIOError>> {
+        let mut results = BTreeMap::new();
+        for (project, client) in &mut self.clients {
+            results.insert(project.clone(), client.flush_async().await);
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameEventsIOClientBuilder, GameEventsIOEventBuilder, GameEventsIOSessionBuilder};
+
+    fn client(api_key: &str) -> GameEventsIOClient {
+        GameEventsIOClientBuilder::default()
+            .api_key(api_key)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_events_are_routed_by_project() {
+        let mut registry = GameEventsIORegistry::new();
+        registry.register("space-game", client("key-1"));
+        registry.register("farm-game", client("key-2"));
+
+        let event = |project: Option<&str>| {
+            let mut builder = GameEventsIOEventBuilder::default();
+            if let Some(project) = project {
+                builder.project(project);
+            }
+            builder.event("level_up").build().unwrap()
+        };
+        registry.log_event(event(Some("space-game")));
+        registry.log_event(event(Some("unknown-game")));
+        registry.log_event(event(None));
+        assert_eq!(registry.unrouted_events_count(), 2);
+
+        registry.set_default_project("farm-game");
+        registry.log_event(event(None));
+        let mut session = GameEventsIOSessionBuilder::default()
+            .project("space-game")
+            .build()
+            .unwrap();
+        session.push_event("session_start", Default::default());
+        for event in session.take_events(usize::MAX) {
+            registry.log_event(event);
+        }
+
+        assert_eq!(
+            registry
+                .client("space-game")
+                .unwrap()
+                .pending_events_count(),
+            2
+        );
+        assert_eq!(
+            registry.client("farm-game").unwrap().pending_events_count(),
+            1
+        );
+        assert_eq!(registry.pending_events_count(), 3);
+        assert_eq!(
+            registry.projects().collect::<Vec<_>>(),
+            ["farm-game", "space-game"]
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Events the backend rejected from an otherwise accepted batch
+//!
+//! A batch response may list rejected events by their position in the batch:
+//!
+//! ```json
+//! {"rejected": [{"index": 3, "code": "invalid_property", "message": "...", "retryable": false}]}
+//! ```
+//!
+//! Any other response body means every event was accepted.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::GameEventsIOEvent;
+
+/// An event the backend rejected, and why
+#[derive(Clone, Debug)]
+pub struct RejectedEvent {
+    /// The event as it was sent
+    pub event: GameEventsIOEvent,
+
+    /// Machine-readable reason, e.g. "invalid_property"
+    pub code: String,
+
+    /// Human-readable reason, if the backend gave one
+    pub message: String,
+
+    /// Whether the event was queued again; otherwise it was dead-lettered
+    pub retryable: bool,
+}
+
+impl RejectedEvent {
+    /// The event with the reason added as `rejection_code` and
+    /// `rejection_message` properties, as handed to the dead-letter sink
+    pub(crate) fn to_dead_letter(&self) -> GameEventsIOEvent {
+        let mut event = self.event.clone();
+        event
+            .event_properties
+            .insert("rejection_code".into(), self.code.clone().into());
+        event
+            .event_properties
+            .insert("rejection_message".into(), self.message.clone().into());
+        event
+    }
+}
+
+#[derive(Deserialize)]
+struct BatchResponse {
+    rejected: Vec<Rejection>,
+}
+
+#[derive(Deserialize)]
+struct Rejection {
+    index: usize,
+    code: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    retryable: bool,
+}
+
+/// Remove the events `body` reports as rejected from `batch`
+///
+/// Indexes outside the batch are ignored.
+pub(crate) fn take_rejected(batch: &mut Vec<GameEventsIOEvent>, body: &str) -> Vec<RejectedEvent> {
+    let Ok(response) = serde_json::from_str::<BatchResponse>(body) else {
+        return Vec::new();
+    };
+    let mut reasons: HashMap<_, _> = response
+        .rejected
+        .into_iter()
+        .filter(|rejection| rejection.index < batch.len())
+        .map(|rejection| (rejection.index, rejection))
+        .collect();
+    if reasons.is_empty() {
+        return Vec::new();
+    }
+
+    let mut rejected = Vec::new();
+    let mut accepted = Vec::new();
+    for (index, event) in batch.drain(..).enumerate() {
+        match reasons.remove(&index) {
+            Some(reason) => rejected.push(RejectedEvent {
+                event,
+                code: reason.code,
+                message: reason.message,
+                retryable: reason.retryable,
+            }),
+            None => accepted.push(event),
+        }
+    }
+    *batch = accepted;
+    rejected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+
+    #[test]
+    fn test_take_rejected() {
+        let mut batch: Vec<_> = ["a", "b", "c"]
+            .into_iter()
+            .map(|name| {
+                GameEventsIOEventBuilder::default()
+                    .event(name)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+
+        assert!(take_rejected(&mut batch, "ok").is_empty());
+        assert_eq!(batch.len(), 3);
+
+        let body = r#"{"accepted": 1, "rejected": [
+            {"index": 2, "code": "quota", "retryable": true},
+            {"index": 0, "code": "invalid_property", "message": "bad level"},
+            {"index": 7, "code": "unknown"}
+        ]}"#;
+        let rejected = take_rejected(&mut batch, body);
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].event, "b");
+        assert_eq!(rejected.len(), 2);
+        assert_eq!(rejected[0].event.event, "a");
+        assert_eq!(rejected[0].message, "bad level");
+        assert!(!rejected[0].retryable);
+        assert!(rejected[1].retryable);
+        assert_eq!(
+            rejected[0].to_dead_letter().event_properties["rejection_code"],
+            "invalid_property"
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::de::Error as _;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+
+#[cfg(feature = "signing")]
+use crate::SigningKey;
+use crate::{FlagRule, GameEventsIOError};
+
+/// Where a client fetches settings the backend can change without an update
+///
+/// The document is requested with a POST carrying the client's usual
+/// headers. With a `state_dir` the latest document is kept there and applied
+/// as soon as the next client is built.
+///
+/// Documents are stale-while-revalidate: once one is older than `ttl`, the
+/// next event or flush starts a refresh in the background and the stale
+/// settings stay in use until it completes. Nothing waits on the network,
+/// not even building the client.
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(into, strip_option))]
+pub struct RemoteConfigSource {
+    /// Endpoint that returns the configuration document (default: `config`
+    /// next to the client's `backend_url`)
+    #[builder(default)]
+    pub url: Option<String>,
+
+    /// How long a document is fresh; older ones are refreshed, and a failed
+    /// refresh is retried after another `ttl` (default: 1 hour)
+    #[builder(default = "Duration::from_secs(60 * 60)")]
+    pub ttl: Duration,
+
+    /// How long past its `ttl` a document may still be used while refreshes
+    /// fail; after that the client's own settings apply (default: forever)
+    #[builder(default)]
+    pub max_stale: Option<Duration>,
+
+    /// Key the document's `signature` must verify with; unsigned documents
+    /// and documents with a bad signature are rejected
+    #[cfg(feature = "signing")]
+    #[builder(default)]
+    pub verification_key: Option<SigningKey>,
+}
+
+/// Settings from a remote configuration document
+///
+/// The document is `{"config": {...}, "signature": "v1=<hex>"}`, where the
+/// signature is the HMAC-SHA256 of the `config` JSON exactly as sent.
+/// Every setting is optional; a missing one leaves the client's own.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RemoteSettings {
+    /// Replaces the client's `sample_rate`
+    pub sample_rate: Option<f64>,
+
+    /// Sampling rates for particular event names, over `sample_rate`
+    pub event_sample_rates: HashMap<String, f64>,
+
+    /// Seconds; replaces the `batch_policy`'s `max_age`
+    pub flush_interval: Option<u64>,
+
+    /// Event names dropped when logged
+    pub disabled_events: HashSet<String>,
+
+    /// Feature flags by name
+    pub flags: HashMap<String, FlagRule>,
+}
+
+impl RemoteSettings {
+    /// Sampling rate set remotely for `event`, if any
+    pub(crate) fn sample_rate(&self, event: &str) -> Option<f64> {
+        self.event_sample_rates
+            .get(event)
+            .copied()
+            .or(self.sample_rate)
+    }
+}
+
+#[derive(Deserialize)]
+struct Document<'a> {
+    #[serde(borrow)]
+    config: &'a RawValue,
+
+    #[cfg_attr(not(feature = "signing"), allow(dead_code))]
+    #[serde(default)]
+    signature: Option<String>,
+}
+
+/// Result of a background fetch, filled in when it completes
+pub(crate) type PendingFetch = Arc<Mutex<Option<Result<String, GameEventsIOError>>>>;
+
+impl RemoteConfigSource {
+    pub(crate) fn url(&self) -> &str {
+        self.url
+            .as_deref()
+            .expect("remote config url is set when the client is built")
+    }
+
+    /// Whether a document of this age must no longer be used
+    pub(crate) fn is_expired(&self, age: Duration) -> bool {
+        self.max_stale
+            .is_some_and(|max_stale| age > self.ttl.saturating_add(max_stale))
+    }
+
+    /// Parse a document, checking its signature if a verification key is set
+    pub(crate) fn parse(&self, body: &str) -> Result<RemoteSettings, GameEventsIOError> {
+        let document: Document = serde_json::from_str(body)?;
+        #[cfg(feature = "signing")]
+        if let Some(key) = &self.verification_key {
+            let config = document.config.get().as_bytes();
+            if !document
+                .signature
+                .as_deref()
+                .is_some_and(|signature| key.verify(config, signature))
+            {
+                return Err(GameEventsIOError::InvalidSignature);
+            }
+        }
+        let settings: RemoteSettings = serde_json::from_str(document.config.get())?;
+        let rates = settings
+            .sample_rate
+            .iter()
+            .chain(settings.event_sample_rates.values())
+            .map(|rate| ("sample rate", rate));
+        let rollouts = settings
+            .flags
+            .values()
+            .filter_map(|flag| flag.rollout.as_ref())
+            .map(|rollout| ("flag rollout", rollout));
+        for (name, fraction) in rates.chain(rollouts) {
+            if !(0.0..=1.0).contains(fraction) {
+                return Err(serde_json::Error::custom(format!(
+                    "invalid {} {}: must be between 0.0 and 1.0",
+                    name, fraction
+                ))
+                .into());
+            }
+        }
+        Ok(settings)
+    }
+}
+
+/// Derive the remote configuration endpoint from the events endpoint
+///
+/// "https://host/v1/events" -> "https://host/v1/config"
+pub(crate) fn default_url(backend_url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(backend_url).ok()?;
+    url.join("config").ok().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let source = RemoteConfigSourceBuilder::default()
+            .url("https://api.game-events.io/v1/config")
+            .build()
+            .unwrap();
+        let settings = source
+            .parse(
+                r#"{"config": {
+                    "sample_rate": 0.5,
+                    "event_sample_rates": {"frame_time": 0.01},
+                    "disabled_events": ["debug_ping"]
+                }}"#,
+            )
+            .unwrap();
+        assert_eq!(settings.sample_rate("frame_time"), Some(0.01));
+        assert_eq!(settings.sample_rate("level_up"), Some(0.5));
+        assert!(settings.disabled_events.contains("debug_ping"));
+        assert_eq!(settings.flush_interval, None);
+
+        assert!(source.parse(r#"{"config": {"sample_rate": 2.0}}"#).is_err());
+        assert!(source
+            .parse(r#"{"config": {"flags": {"new_shop": {"rollout": -1}}}}"#)
+            .is_err());
+        assert!(source.parse(r#"{"sample_rate": 0.5}"#).is_err());
+
+        let hour = Duration::from_secs(60 * 60);
+        assert!(!source.is_expired(hour * 24));
+        let source = RemoteConfigSourceBuilder::default()
+            .url("https://api.game-events.io/v1/config")
+            .max_stale(hour)
+            .build()
+            .unwrap();
+        assert!(!source.is_expired(hour * 2));
+        assert!(source.is_expired(hour * 2 + Duration::from_secs(1)));
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn test_signature() {
+        let source = RemoteConfigSourceBuilder::default()
+            .url("https://api.game-events.io/v1/config")
+            .verification_key(SigningKey::new("key-1", "secret"))
+            .build()
+            .unwrap();
+        // printf '{"flush_interval":30}' | openssl dgst -sha256 -hmac secret
+        let signature = "v1=6cff675c65ca32bd4f2a8005c8de0b8609bc6831a5fc73b8cc4a7f86c16581a0";
+        let document = |config: &str, signature: &str| {
+            format!(r#"{{"config":{},"signature":"{}"}}"#, config, signature)
+        };
+
+        let settings = source
+            .parse(&document(r#"{"flush_interval":30}"#, signature))
+            .unwrap();
+        assert_eq!(settings.flush_interval, Some(30));
+        assert!(matches!(
+            source.parse(&document(r#"{"flush_interval":5}"#, signature)),
+            Err(GameEventsIOError::InvalidSignature)
+        ));
+        assert!(matches!(
+            source.parse(r#"{"config":{"flush_interval":30}}"#),
+            Err(GameEventsIOError::InvalidSignature)
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+use serde::Serialize;
+
+/// Direction of a virtual currency flow, logged with [`GameEventsIOSession::resource_event`]
+///
+/// [`GameEventsIOSession::resource_event`]: crate::GameEventsIOSession::resource_event
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlowType {
+    /// The player gained currency (rewards, purchases, drops)
+    Source,
+
+    /// The player spent currency
+    Sink,
+}
+
+impl FlowType {
+    /// Event name used for this flow
+    pub fn event_name(self) -> &'static str {
+        match self {
+            FlowType::Source => "resource_source",
+            FlowType::Sink => "resource_sink",
+        }
+    }
+}
+
+/// A change in a virtual currency balance
+#[derive(Clone, Debug, Builder, Serialize)]
+#[builder(setter(into))]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct ResourceEvent {
+    /// Virtual currency name, e.g. "gems"
+    pub currency: String,
+
+    /// Amount gained or spent; always positive, the flow gives the direction
+    pub amount: f64,
+
+    /// Category of what the currency was gained from or spent on, e.g. "shop"
+    pub item_type: String,
+
+    /// The specific item, e.g. "extra_life"
+    pub item_id: String,
+}
+
+impl ResourceEventBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(amount) = self.amount {
+            if !amount.is_finite() || amount <= 0.0 {
+                return Err(format!("amount must be a positive number, got {}", amount));
+            }
+        }
+        for (field, value) in [
+            ("currency", &self.currency),
+            ("item_type", &self.item_type),
+            ("item_id", &self.item_id),
+        ] {
+            if value.as_ref().is_some_and(|v| v.is_empty()) {
+                return Err(format!("{} must not be empty", field));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_validation() {
+        let builder = ResourceEventBuilder::default()
+            .currency("gems")
+            .amount(50.0)
+            .item_type("shop")
+            .item_id("extra_life")
+            .clone();
+        assert!(builder.build().is_ok());
+        assert!(builder.clone().amount(-5.0).build().is_err());
+        assert!(builder.clone().currency("").build().is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+/// Controls how a failed flush is retried before the error is returned
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(into))]
+#[builder(default)]
+pub struct RetryPolicy {
+    /// Total number of attempts per flush, including the first one
+    pub max_attempts: u32,
+
+    /// Delay before the first retry; doubled for every retry after that
+    pub initial_backoff: Duration,
+
+    /// Upper bound for the delay between two attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn disabled() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Delay to wait before retry number `retry` (starting at 1)
+    ///
+    /// The exponential delay is jittered down by up to half so that many
+    /// clients failing at once do not retry in lockstep.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(31);
+        let delay = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+        delay.mul_f64(rand::random_range(0.5..=1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicyBuilder::default()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(350))
+            .build()
+            .unwrap();
+
+        let first = policy.backoff(1);
+        assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+
+        let second = policy.backoff(2);
+        assert!(second >= Duration::from_millis(100) && second <= Duration::from_millis(200));
+
+        let capped = policy.backoff(20);
+        assert!(capped >= Duration::from_millis(175) && capped <= Duration::from_millis(350));
+    }
+}
@@ -0,0 +1,199 @@
+//! Timers and tasks for async flushes, independent of the executor
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(not(feature = "tokio"))]
+use crate::transport::{AsyncHttpTransport, HttpRequest, HttpTransport, TransportFuture};
+
+/// Future returned by [`AsyncRuntime`] methods
+pub type RuntimeFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// The executor async flushes wait and spawn on
+///
+/// Implemented for Tokio, async-std and smol behind the features of the same
+/// names; implement it to run on another executor.
+pub trait AsyncRuntime: Send + Sync {
+    /// Name shown in the client's `Debug` output
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Complete after `duration`
+    fn sleep(&self, duration: Duration) -> RuntimeFuture;
+
+    /// Run `task` in the background
+    fn spawn(&self, task: RuntimeFuture);
+
+    /// Run blocking `task` where it cannot stall the executor, completing
+    /// once it has finished
+    fn spawn_blocking(&self, task: Box<dyn FnOnce() + Send>) -> RuntimeFuture;
+}
+
+impl fmt::Debug for dyn AsyncRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Tokio; spawning needs to happen inside a Tokio runtime
+#[cfg(feature = "tokio")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokioRuntime;
+
+#[cfg(feature = "tokio")]
+impl AsyncRuntime for TokioRuntime {
+    fn name(&self) -> &str {
+        "tokio"
+    }
+
+    fn sleep(&self, duration: Duration) -> RuntimeFuture {
+        Box::pin(tokio::time::sleep(duration))
+    }
+
+    fn spawn(&self, task: RuntimeFuture) {
+        tokio::spawn(task);
+    }
+
+    fn spawn_blocking(&self, task: Box<dyn FnOnce() + Send>) -> RuntimeFuture {
+        let handle = tokio::task::spawn_blocking(task);
+        Box::pin(async move {
+            let _ = handle.await;
+        })
+    }
+}
+
+/// async-std's global executor
+#[cfg(feature = "async-std")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsyncStdRuntime;
+
+#[cfg(feature = "async-std")]
+impl AsyncRuntime for AsyncStdRuntime {
+    fn name(&self) -> &str {
+        "async-std"
+    }
+
+    fn sleep(&self, duration: Duration) -> RuntimeFuture {
+        Box::pin(async_std::task::sleep(duration))
+    }
+
+    fn spawn(&self, task: RuntimeFuture) {
+        async_std::task::spawn(task);
+    }
+
+    fn spawn_blocking(&self, task: Box<dyn FnOnce() + Send>) -> RuntimeFuture {
+        Box::pin(async_std::task::spawn_blocking(task))
+    }
+}
+
+/// smol's global executor
+#[cfg(feature = "smol")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SmolRuntime;
+
+#[cfg(feature = "smol")]
+impl AsyncRuntime for SmolRuntime {
+    fn name(&self) -> &str {
+        "smol"
+    }
+
+    fn sleep(&self, duration: Duration) -> RuntimeFuture {
+        Box::pin(async move {
+            smol::Timer::after(duration).await;
+        })
+    }
+
+    fn spawn(&self, task: RuntimeFuture) {
+        smol::spawn(task).detach();
+    }
+
+    fn spawn_blocking(&self, task: Box<dyn FnOnce() + Send>) -> RuntimeFuture {
+        Box::pin(smol::unblock(task))
+    }
+}
+
+/// The runtime of the first enabled feature out of tokio, async-std and smol
+pub(crate) fn default_runtime() -> Arc<dyn AsyncRuntime> {
+    #[cfg(feature = "tokio")]
+    return Arc::new(TokioRuntime);
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    return Arc::new(AsyncStdRuntime);
+    #[cfg(all(feature = "smol", not(any(feature = "tokio", feature = "async-std"))))]
+    return Arc::new(SmolRuntime);
+}
+
+/// Sends async requests through a blocking transport on the runtime's
+/// blocking threads, for executors reqwest's async client cannot run on
+#[cfg(not(feature = "tokio"))]
+#[derive(Debug)]
+pub(crate) struct UnblockTransport {
+    transport: Arc<dyn HttpTransport>,
+    runtime: Arc<dyn AsyncRuntime>,
+}
+
+#[cfg(not(feature = "tokio"))]
+impl UnblockTransport {
+    pub(crate) fn new(transport: Arc<dyn HttpTransport>, runtime: Arc<dyn AsyncRuntime>) -> Self {
+        UnblockTransport { transport, runtime }
+    }
+}
+
+#[cfg(not(feature = "tokio"))]
+impl AsyncHttpTransport for UnblockTransport {
+    fn name(&self) -> &str {
+        self.transport.name()
+    }
+
+    fn post(&self, request: HttpRequest) -> TransportFuture<'_> {
+        use std::sync::{Mutex, PoisonError};
+
+        let response = Arc::new(Mutex::new(None));
+        let slot = Arc::clone(&response);
+        let transport = Arc::clone(&self.transport);
+        let done = self.runtime.spawn_blocking(Box::new(move || {
+            let result = transport.post(request);
+            *slot.lock().unwrap_or_else(PoisonError::into_inner) = Some(result);
+        }));
+        Box::pin(async move {
+            done.await;
+            let result = response
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+            result.unwrap_or_else(|| {
+                Err(crate::GameEventsIOError::Transport(
+                    "blocking request panicked".into(),
+                ))
+            })
+        })
+    }
+}
+
+#[cfg(all(test, feature = "smol", not(feature = "tokio")))]
+mod tests {
+    use crate::{GameEventsIOClientBuilder, GameEventsIOError, GameEventsIOEventBuilder};
+
+    #[test]
+    fn test_smol_flush_async() {
+        let mut client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .backend_url("http://127.0.0.1:9/v1/events")
+            .retry_policy(crate::RetryPolicy::disabled())
+            .build()
+            .unwrap();
+        let event = GameEventsIOEventBuilder::default()
+            .event("level_up")
+            .build()
+            .unwrap();
+        client.log_event(event);
+
+        // Sent by the blocking transport, off the executor
+        let error = smol::block_on(client.flush_async()).unwrap_err();
+        assert!(matches!(error, GameEventsIOError::Network(_)), "{}", error);
+        assert_eq!(client.pending_events_count(), 1);
+    }
+}
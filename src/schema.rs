@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::GameEventsIOEvent;
+
+/// Property name holding schema violations of a flagged event
+pub const SCHEMA_ERRORS_PROPERTY: &str = "schema_errors";
+
+/// What the client does with an event that does not match its schema
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchemaViolationPolicy {
+    /// Keep the event and list the violations in its `schema_errors` property
+    #[default]
+    Flag,
+
+    /// Drop the event
+    Reject,
+}
+
+/// A schema that could not be compiled
+#[derive(Debug)]
+pub struct SchemaError {
+    /// Event name the schema was registered for
+    pub event: String,
+
+    /// Why the schema is invalid
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid schema for {:?}: {}", self.event, self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// JSON Schemas for event properties, keyed by event name
+///
+/// The client checks `event_properties` against the schema of the event's
+/// name before super properties are merged in. Checks only run in debug
+/// builds; events without a registered schema are not checked.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaRegistry {
+    schemas: HashMap<String, jsonschema::Validator>,
+    policy: SchemaViolationPolicy,
+}
+
+impl SchemaRegistry {
+    /// Create an empty registry that flags violations
+    pub fn new() -> Self {
+        SchemaRegistry::default()
+    }
+
+    /// Register the schema for an event name, replacing any previous one
+    pub fn register(
+        mut self,
+        event: impl Into<String>,
+        schema: &serde_json::Value,
+    ) -> Result<Self, SchemaError> {
+        let event = event.into();
+        match jsonschema::validator_for(schema) {
+            Ok(validator) => {
+                self.schemas.insert(event, validator);
+                Ok(self)
+            }
+            Err(e) => Err(SchemaError {
+                event,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    /// Change what happens to nonconforming events
+    pub fn on_violation(self, policy: SchemaViolationPolicy) -> Self {
+        SchemaRegistry { policy, ..self }
+    }
+
+    /// Get the policy for nonconforming events
+    pub fn policy(&self) -> SchemaViolationPolicy {
+        self.policy
+    }
+
+    /// Check an event against its schema, returning one message per violation
+    pub fn validate(&self, event: &GameEventsIOEvent) -> Result<(), Vec<String>> {
+        let Some(validator) = self.schemas.get(event.event.as_ref()) else {
+            return Ok(());
+        };
+        let instance = serde_json::Value::Object(
+            event
+                .event_properties
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+        );
+        let errors: Vec<String> = validator
+            .iter_errors(&instance)
+            .map(|e| match e.instance_path().to_string() {
+                path if path.is_empty() => e.to_string(),
+                path => format!("{}: {}", path, e),
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_against_registered_schema() {
+        let registry = SchemaRegistry::new()
+            .register(
+                "level_completed",
+                &json!({
+                    "type": "object",
+                    "properties": {"level": {"type": "integer"}},
+                    "required": ["level"]
+                }),
+            )
+            .unwrap();
+
+        let mut props = HashMap::new();
+        props.insert("level".into(), json!("five"));
+        let event = GameEventsIOEventBuilder::default()
+            .event("level_completed")
+            .event_properties(props)
+            .build()
+            .unwrap();
+        let errors = registry.validate(&event).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("/level: "), "{}", errors[0]);
+
+        let other = GameEventsIOEventBuilder::default()
+            .event("app_start")
+            .build()
+            .unwrap();
+        assert!(registry.validate(&other).is_ok());
+
+        assert!(SchemaRegistry::new()
+            .register("bad", &json!({"type": 5}))
+            .is_err());
+    }
+}
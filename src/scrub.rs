@@ -0,0 +1,148 @@
+
+use regex::Regex;
+
+use crate::Properties;
+
+/// Token used when a rule does not specify its own replacement
+pub const REDACTED: &str = "[REDACTED]";
+
+#[derive(Clone, Debug)]
+enum Rule {
+    /// Replace the whole value of a property with this key (case-insensitive)
+    Key { key: String, replacement: String },
+
+    /// Replace matches inside every string value
+    Pattern { regex: Regex, replacement: String },
+}
+
+/// Redacts personal data from event and user properties before they are queued
+///
+/// Rules are applied in the order they were added, to nested arrays and
+/// objects as well.
+#[derive(Clone, Debug, Default)]
+pub struct Scrubber {
+    rules: Vec<Rule>,
+}
+
+impl Scrubber {
+    /// Create a scrubber without any rules
+    pub fn new() -> Self {
+        Scrubber::default()
+    }
+
+    /// Replace the value of every property named `key` with [`REDACTED`]
+    pub fn redact_key(self, key: impl Into<String>) -> Self {
+        self.redact_key_with(key, REDACTED)
+    }
+
+    /// Replace the value of every property named `key` with `replacement`
+    pub fn redact_key_with(
+        mut self,
+        key: impl Into<String>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        self.rules.push(Rule::Key {
+            key: key.into(),
+            replacement: replacement.into(),
+        });
+        self
+    }
+
+    /// Replace every match of `regex` in string values with `replacement`
+    pub fn redact_pattern(mut self, regex: Regex, replacement: impl Into<String>) -> Self {
+        self.rules.push(Rule::Pattern {
+            regex,
+            replacement: replacement.into(),
+        });
+        self
+    }
+
+    /// Replace anything that looks like an email address with "[EMAIL]"
+    pub fn redact_emails(self) -> Self {
+        let regex = Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}")
+            .expect("email pattern is valid");
+        self.redact_pattern(regex, "[EMAIL]")
+    }
+
+    /// Apply every rule to a property map in place
+    pub fn scrub(&self, properties: &mut Properties) {
+        for (key, value) in properties.iter_mut() {
+            self.scrub_entry(key, value);
+        }
+    }
+
+    fn scrub_entry(&self, key: &str, value: &mut serde_json::Value) {
+        for rule in &self.rules {
+            if let Rule::Key {
+                key: redacted,
+                replacement,
+            } = rule
+            {
+                if redacted.eq_ignore_ascii_case(key) {
+                    *value = serde_json::Value::String(replacement.clone());
+                    return;
+                }
+            }
+        }
+        self.scrub_value(value);
+    }
+
+    fn scrub_value(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(text) => {
+                for rule in &self.rules {
+                    if let Rule::Pattern { regex, replacement } = rule {
+                        if let std::borrow::Cow::Owned(scrubbed) =
+                            regex.replace_all(text, replacement.as_str())
+                        {
+                            *text = scrubbed;
+                        }
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.scrub_value(item);
+                }
+            }
+            serde_json::Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    self.scrub_entry(key, value);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_scrub_keys_and_patterns() {
+        let scrubber = Scrubber::new().redact_key("Email").redact_emails();
+
+        let mut props = Properties::new();
+        props.insert("email".into(), json!("player@example.com"));
+        props.insert(
+            "feedback".into(),
+            json!("contact me at player@example.com please"),
+        );
+        props.insert(
+            "nested".into(),
+            json!({"email": "x", "tags": ["a@b.io"]}),
+        );
+        props.insert("level".into(), json!(5));
+        scrubber.scrub(&mut props);
+
+        assert_eq!(props["email"], REDACTED);
+        assert_eq!(props["feedback"], "contact me at [EMAIL] please");
+        assert_eq!(
+            props["nested"],
+            json!({"email": REDACTED, "tags": ["[EMAIL]"]})
+        );
+        assert_eq!(props["level"], 5);
+    }
+}
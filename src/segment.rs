@@ -0,0 +1,196 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use serde_json::{json, Map, Value};
+
+use crate::{time_format, EventSink, GameEventsIOError, GameEventsIOEvent, Properties};
+
+/// Segment's HTTP tracking API batch endpoint
+pub const SEGMENT_BATCH_URL: &str = "https://api.segment.io/v1/batch";
+
+/// Forwards events to Segment's HTTP tracking API
+///
+/// `identify` and `alias` events (see [`GameEventsIOClient::identify`]) become
+/// Segment identify and alias calls; everything else is sent as a track call
+/// with the event properties as `properties` and the user properties as
+/// `context.traits`. Add it with `mirror` to dual-write during a migration.
+///
+/// [`GameEventsIOClient::identify`]: crate::GameEventsIOClient::identify
+#[derive(Debug)]
+pub struct SegmentSink {
+    write_key: String,
+    url: String,
+    client: OnceLock<reqwest::blocking::Client>,
+}
+
+impl SegmentSink {
+    /// Send to the default Segment endpoint with a source's write key
+    pub fn new(write_key: impl Into<String>) -> Self {
+        SegmentSink {
+            write_key: write_key.into(),
+            url: SEGMENT_BATCH_URL.to_string(),
+            client: OnceLock::new(),
+        }
+    }
+
+    /// Change the batch endpoint, e.g. for Segment's EU region
+    pub fn endpoint(self, url: impl Into<String>) -> Self {
+        SegmentSink {
+            url: url.into(),
+            ..self
+        }
+    }
+}
+
+impl EventSink for SegmentSink {
+    fn send(
+        &self,
+        events: &[GameEventsIOEvent],
+        timeout: Option<Duration>,
+    ) -> Result<String, GameEventsIOError> {
+        let batch: Vec<Value> = events.iter().map(segment_message).collect();
+        let body = serde_json::to_vec(&json!({ "batch": batch }))?;
+
+        let mut headers = HeaderMap::new();
+        let credentials = base64(format!("{}:", self.write_key).as_bytes());
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Basic {}", credentials))
+                .expect("write key is not a valid header value"),
+        );
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        crate::post_blocking(
+            self.client.get_or_init(crate::new_blocking_client),
+            &self.url,
+            headers,
+            body,
+            timeout,
+        )
+    }
+}
+
+/// Translate an event into a Segment track, identify or alias message
+fn segment_message(event: &GameEventsIOEvent) -> Value {
+    let mut message = Map::new();
+    let anonymous_id = event.anonymous_id.as_deref();
+    // The client falls back to the anonymous id for users that never identified
+    if !event.user_id.is_empty() && Some(event.user_id.as_str()) != anonymous_id {
+        message.insert("userId".into(), event.user_id.clone().into());
+    }
+    if let Some(id) = anonymous_id {
+        message.insert("anonymousId".into(), id.into());
+    }
+    message.insert("timestamp".into(), time_format::rfc3339(event.time).into());
+    if !event.event_id.is_empty() {
+        message.insert("messageId".into(), event.event_id.clone().into());
+    }
+
+    let to_object = |properties: &Properties| {
+        Value::Object(
+            properties
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+        )
+    };
+    let properties = to_object(&event.event_properties);
+    let traits = to_object(&event.user_properties);
+    match event.event.as_ref() {
+        "identify" => {
+            message.insert("type".into(), "identify".into());
+            message.insert("traits".into(), traits);
+        }
+        "alias" => {
+            message.insert("type".into(), "alias".into());
+            if let Some(previous_id) = event.event_properties.get("previous_id") {
+                message.insert("previousId".into(), previous_id.clone());
+            }
+        }
+        name => {
+            message.insert("type".into(), "track".into());
+            message.insert("event".into(), name.into());
+            message.insert("properties".into(), properties);
+            if !event.user_properties.is_empty() {
+                message.insert("context".into(), json!({ "traits": traits }));
+            }
+        }
+    }
+    Value::Object(message)
+}
+
+/// Standard base64 with padding
+pub(crate) fn base64(input: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_segment_messages() {
+        let mut props = HashMap::new();
+        props.insert("level".into(), json!(5));
+        let track = GameEventsIOEventBuilder::default()
+            .event("level_completed")
+            .user_id("anon-1")
+            .anonymous_id("anon-1")
+            .event_id("evt-1")
+            .time(1_709_164_800u64)
+            .event_properties(props)
+            .build()
+            .unwrap();
+        assert_eq!(
+            segment_message(&track),
+            json!({
+                "type": "track",
+                "event": "level_completed",
+                "anonymousId": "anon-1",
+                "timestamp": "2024-02-29T00:00:00Z",
+                "messageId": "evt-1",
+                "properties": {"level": 5}
+            })
+        );
+
+        let mut traits = HashMap::new();
+        traits.insert("vip".into(), json!(true));
+        let identify = GameEventsIOEventBuilder::default()
+            .event("identify")
+            .user_id("user123")
+            .anonymous_id("anon-1")
+            .user_properties(traits)
+            .build()
+            .unwrap();
+        let message = segment_message(&identify);
+        assert_eq!(message["type"], "identify");
+        assert_eq!(message["userId"], "user123");
+        assert_eq!(message["traits"], json!({"vip": true}));
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b"key:"), "a2V5Og==");
+        assert_eq!(base64(b"abc"), "YWJj");
+        assert_eq!(base64(b"ab"), "YWI=");
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::{GameEventsIOError, GameEventsIOEvent};
+
+/// Destination for flushed events, replacing the HTTP backend
+///
+/// Set one with [`GameEventsIOClientBuilder::sink`] to route events to a
+/// message queue, a local file or a test double. Retries, batching and the
+/// spool work the same as with the HTTP backend; errors for which
+/// [`GameEventsIOError::is_retryable`] is true are retried.
+///
+/// [`GameEventsIOClientBuilder::sink`]: crate::GameEventsIOClientBuilder::sink
+pub trait EventSink: Send + Sync {
+    /// Name shown in the client's `Debug` output
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Deliver a batch of events, giving up after `timeout` if one is set
+    ///
+    /// The returned string is passed through as the result of the flush.
+    /// `flush_async` calls this directly, so a sink used from async code
+    /// should not block for long.
+    fn send(
+        &self,
+        events: &[GameEventsIOEvent],
+        timeout: Option<Duration>,
+    ) -> Result<String, GameEventsIOError>;
+}
+
+impl fmt::Debug for dyn EventSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
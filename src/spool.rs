@@ -0,0 +1,622 @@
+//! On-disk copy of the client's pending events
+//!
+//! The spool holds exactly the events that are still waiting to be sent. New
+//! events are appended as they are logged, and sent ones are removed after
+//! every successful flush.
+//!
+//! Events are stored as records: an encoding byte (0 for JSON, 1 for
+//! MessagePack) followed by the encoded event. With a
+//! [`SpoolKey`](crate::SpoolKey), the event is sealed with AES-256-GCM and the
+//! encoding has its high bit set. Every [`Storage`](crate::Storage) holds
+//! these records; the spool file frames them as follows, with integers
+//! little-endian:
+//!
+//! - Header: the magic bytes `GEIOSPL\0`, a `u16` format version and a `u16`
+//!   length of the header fields that follow. Version 1 has none; fields added
+//!   by later minor revisions are skipped by readers that do not know them.
+//! - Records: a `u32` payload length, a `u32` checksum, the encoding byte and
+//!   the payload. The checksum is the CRC-32 of the length, encoding byte and
+//!   payload as stored.
+//!
+//! A damaged record, one that fails its checksum, has an empty payload or was
+//! cut short by a crash, is discarded and reading resumes at the next offset
+//! where an intact record starts, so a bad length does not take the rest of
+//! the spool with it. Records in an encoding this build cannot decode are skipped. A spool
+//! from a newer, incompatible version is moved aside rather than read, and
+//! the newline-delimited JSON and bare MessagePack spools of earlier releases
+//! are still read.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::{self, File, OpenOptions};
+use std::io;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+#[cfg(feature = "encryption")]
+use crate::SpoolKey;
+use crate::{GameEventsIOEvent, PayloadFormat};
+
+#[cfg(not(target_arch = "wasm32"))]
+const MAGIC: &[u8; 8] = b"GEIOSPL\0";
+
+/// Version written to new spools; files with a higher version are not read
+#[cfg(not(target_arch = "wasm32"))]
+const VERSION: u16 = 1;
+
+/// Length of the header this version writes
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) const HEADER_LEN: u64 = 12;
+
+/// Records claiming to be larger than this are treated as corrupt
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_RECORD_LEN: u32 = 64 * 1024 * 1024;
+
+/// Encoding bit of records sealed with the spool key
+#[cfg(feature = "encryption")]
+const ENCRYPTED: u8 = 0x80;
+
+/// How events are encoded into records
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Codec {
+    pub(crate) format: PayloadFormat,
+
+    /// Seals records written and opens those read
+    #[cfg(feature = "encryption")]
+    pub(crate) key: Option<SpoolKey>,
+}
+
+impl Codec {
+    /// The record for `event`
+    pub(crate) fn encode(&self, event: &GameEventsIOEvent) -> io::Result<Vec<u8>> {
+        let mut record = vec![encoding(self.format)];
+        self.format.write(&mut record, event)?;
+        #[cfg(feature = "encryption")]
+        if let Some(key) = &self.key {
+            let sealed = key.seal(&record[1..]);
+            record.truncate(1);
+            record[0] |= ENCRYPTED;
+            record.extend_from_slice(&sealed);
+        }
+        Ok(record)
+    }
+
+    /// Decode a record, if this build knows its encoding and holds the key
+    /// it was sealed with
+    pub(crate) fn decode(&self, record: &[u8]) -> Option<GameEventsIOEvent> {
+        let (&encoding, payload) = record.split_first()?;
+        #[cfg(feature = "encryption")]
+        if encoding & ENCRYPTED != 0 {
+            let payload = self.key.as_ref()?.open(payload)?;
+            return Codec::default().decode(&[&[encoding & !ENCRYPTED], &payload[..]].concat());
+        }
+        match encoding {
+            0 => serde_json::from_slice(payload).ok(),
+            #[cfg(feature = "msgpack")]
+            1 => rmp_serde::from_slice(payload).ok(),
+            _ => None,
+        }
+    }
+}
+
+fn encoding(format: PayloadFormat) -> u8 {
+    match format {
+        PayloadFormat::Json => 0,
+        #[cfg(feature = "msgpack")]
+        PayloadFormat::MessagePack => 1,
+    }
+}
+
+/// Records read from the spool
+#[derive(Debug, Default)]
+pub(crate) struct Loaded {
+    pub(crate) records: Vec<Vec<u8>>,
+
+    /// Whether the file should be rewritten before anything is appended:
+    /// it is in a legacy format, or has damaged records
+    pub(crate) needs_rewrite: bool,
+
+    /// Damaged stretches skipped, each counted as one record
+    pub(crate) discarded: usize,
+
+    /// Where each record's frame starts in the file; only meaningful when
+    /// it does not need a rewrite
+    pub(crate) offsets: Vec<u64>,
+}
+
+/// Append a single record to the spool, creating the file if needed
+///
+/// Returns where the record's frame starts.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn append(path: &Path, record: &[u8]) -> io::Result<u64> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut buffer = Vec::new();
+    let len = file.metadata()?.len();
+    if len == 0 {
+        write_header(&mut buffer)?;
+    }
+    frame(&mut buffer, record)?;
+    file.write_all(&buffer)?;
+    Ok(len.max(HEADER_LEN))
+}
+
+/// The records framed from `start` up to `end`, or the end of the file
+///
+/// Reading stops at a frame that does not check out, which only happens if
+/// the file changed since the offsets were taken.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn read_range(path: &Path, start: u64, end: Option<u64>) -> io::Result<Vec<Vec<u8>>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    match end {
+        Some(end) => file.take(end.saturating_sub(start)).read_to_end(&mut bytes)?,
+        None => file.read_to_end(&mut bytes)?,
+    };
+    let mut rest = &bytes[..];
+    let mut records = Vec::new();
+    while let Some((record, len)) = read_record(rest) {
+        records.push(record);
+        rest = &rest[len..];
+    }
+    Ok(records)
+}
+
+/// Drop every record before the frame starting at `at`
+///
+/// The remaining frames are copied as they are, without being parsed, into
+/// a temporary file that then replaces the spool.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn drop_front(path: &Path, at: u64) -> io::Result<()> {
+    let mut source = File::open(path)?;
+    source.seek(SeekFrom::Start(at))?;
+    let tmp_path = path.with_extension("tmp");
+    {
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        write_header(&mut writer)?;
+        io::copy(&mut source, &mut writer)?;
+        writer.flush()?;
+    }
+    fs::rename(tmp_path, path)
+}
+
+/// Where records of these lengths start once [`rewrite`] has written them
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn offsets(record_lens: impl IntoIterator<Item = usize>) -> Vec<u64> {
+    let mut at = HEADER_LEN;
+    record_lens
+        .into_iter()
+        .map(|len| {
+            let start = at;
+            // Length and checksum, then the record itself
+            at += 8 + len as u64;
+            start
+        })
+        .collect()
+}
+
+/// Read every record from the spool
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn load(path: &Path) -> io::Result<Loaded> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Loaded::default()),
+        Err(e) => return Err(e),
+    };
+    let Some(rest) = bytes.strip_prefix(MAGIC) else {
+        return Ok(Loaded {
+            records: load_legacy(&bytes),
+            needs_rewrite: true,
+            ..Loaded::default()
+        });
+    };
+
+    let (Some(version), Some(fields_len)) = (read_u16(rest, 0), read_u16(rest, 2)) else {
+        return Ok(Loaded {
+            needs_rewrite: true,
+            discarded: 1,
+            ..Loaded::default()
+        });
+    };
+    if version > VERSION {
+        // Leave it for the SDK that wrote it
+        fs::rename(path, path.with_extension(format!("v{}", version)))?;
+        return Ok(Loaded::default());
+    }
+    let mut rest = rest.get(4 + usize::from(fields_len)..).unwrap_or_default();
+
+    let mut loaded = Loaded::default();
+    let mut damaged = false;
+    while !rest.is_empty() {
+        match read_record(rest) {
+            Some((record, len)) => {
+                loaded.offsets.push((bytes.len() - rest.len()) as u64);
+                loaded.records.push(record);
+                rest = &rest[len..];
+                damaged = false;
+            }
+            None => {
+                // Look for the next intact record one byte further on
+                if !damaged {
+                    loaded.discarded += 1;
+                    loaded.needs_rewrite = true;
+                    damaged = true;
+                }
+                rest = &rest[1..];
+            }
+        }
+    }
+    Ok(loaded)
+}
+
+/// The record framed at the start of `bytes` and the length of its frame,
+/// if the frame is complete and its checksum matches
+///
+/// Frames with an empty payload are never written, so one is damage, e.g.
+/// the zeroed tail of a file cut short by a crash, which would otherwise
+/// pass the checksum of its empty payload.
+#[cfg(not(target_arch = "wasm32"))]
+fn read_record(bytes: &[u8]) -> Option<(Vec<u8>, usize)> {
+    let len = read_u32(bytes, 0).filter(|&len| len > 0 && len <= MAX_RECORD_LEN)?;
+    let checksum = read_u32(bytes, 4)?;
+    let encoding = *bytes.get(8)?;
+    let payload = bytes.get(9..9 + len as usize)?;
+    if frame_checksum(len, encoding, payload) != checksum {
+        return None;
+    }
+    Some(([&[encoding], payload].concat(), 9 + payload.len()))
+}
+
+/// Checksum of everything in a frame but the checksum itself, so a damaged
+/// length or encoding byte is caught as well as a damaged payload
+#[cfg(not(target_arch = "wasm32"))]
+fn frame_checksum(len: u32, encoding: u8, payload: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&len.to_le_bytes());
+    hasher.update(&[encoding]);
+    hasher.update(payload);
+    hasher.finalize()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Read a spool from before the versioned format: lines of JSON, or bare
+/// MessagePack values, skipping what fails to parse
+#[cfg(not(target_arch = "wasm32"))]
+fn load_legacy(bytes: &[u8]) -> Vec<Vec<u8>> {
+    #[cfg(feature = "msgpack")]
+    if bytes.first().is_some_and(|&byte| byte != b'{') {
+        let mut rest = bytes;
+        let mut records = Vec::new();
+        while !rest.is_empty() {
+            let value = rest;
+            match rmp_serde::from_read::<_, GameEventsIOEvent>(&mut rest) {
+                Ok(_) => records.push([&[1], &value[..value.len() - rest.len()]].concat()),
+                Err(_) => break,
+            }
+        }
+        return records;
+    }
+
+    bytes
+        .split(|&byte| byte == b'\n')
+        .filter(|line| serde_json::from_slice::<GameEventsIOEvent>(line).is_ok())
+        .map(|line| [&[0], line].concat())
+        .collect()
+}
+
+/// Replace the spool contents with `records`
+///
+/// Writes to a temporary file first so a crash mid-write never leaves a
+/// half-written spool behind.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn rewrite(
+    path: &Path,
+    records: impl IntoIterator<Item = impl AsRef<[u8]>>,
+) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    {
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        write_header(&mut writer)?;
+        let mut buffer = Vec::new();
+        for record in records {
+            buffer.clear();
+            frame(&mut buffer, record.as_ref())?;
+            writer.write_all(&buffer)?;
+        }
+        writer.flush()?;
+    }
+    fs::rename(tmp_path, path)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_header(mut writer: impl Write) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&0u16.to_le_bytes())
+}
+
+/// Append `record` to `buffer` with its length and checksum
+#[cfg(not(target_arch = "wasm32"))]
+fn frame(buffer: &mut Vec<u8>, record: &[u8]) -> io::Result<()> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "event too large to spool");
+    let (&encoding, payload) = record.split_first().ok_or_else(invalid)?;
+    let len = u32::try_from(payload.len())
+        .ok()
+        .filter(|&len| len <= MAX_RECORD_LEN)
+        .ok_or_else(invalid)?;
+    buffer.extend_from_slice(&len.to_le_bytes());
+    buffer.extend_from_slice(&frame_checksum(len, encoding, payload).to_le_bytes());
+    buffer.push(encoding);
+    buffer.extend_from_slice(payload);
+    Ok(())
+}
+
+/// In the browser the spool is a `localStorage` entry named after its path
+///
+/// `localStorage` only holds text, so the spool stays JSON lines, and the
+/// client's format and key are not applied to it.
+#[cfg(target_arch = "wasm32")]
+mod web_storage {
+    use super::*;
+    use crate::web::{storage_get, storage_set};
+
+    fn key(path: &Path) -> String {
+        path.to_string_lossy().into_owned()
+    }
+
+    /// The JSON text of a record
+    fn line(record: &[u8]) -> io::Result<&str> {
+        match record.split_first() {
+            Some((0, json)) => std::str::from_utf8(json)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "localStorage spools hold JSON records only",
+            )),
+        }
+    }
+
+    pub(crate) fn append(path: &Path, record: &[u8]) -> io::Result<()> {
+        let mut contents = storage_get(&key(path))?.unwrap_or_default();
+        contents.push_str(line(record)?);
+        contents.push('\n');
+        storage_set(&key(path), &contents)
+    }
+
+    pub(crate) fn load(path: &Path) -> io::Result<Loaded> {
+        let contents = storage_get(&key(path))?.unwrap_or_default();
+        let records = contents
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| [&[0], line.as_bytes()].concat())
+            .collect();
+        Ok(Loaded {
+            records,
+            ..Loaded::default()
+        })
+    }
+
+    pub(crate) fn rewrite(
+        path: &Path,
+        records: impl IntoIterator<Item = impl AsRef<[u8]>>,
+    ) -> io::Result<()> {
+        let mut contents = String::new();
+        for record in records {
+            contents.push_str(line(record.as_ref())?);
+            contents.push('\n');
+        }
+        storage_set(&key(path), &contents)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_storage::{append, load, rewrite};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+
+    fn event(name: &'static str) -> GameEventsIOEvent {
+        GameEventsIOEventBuilder::default()
+            .event(name)
+            .build()
+            .unwrap()
+    }
+
+    fn record(name: &'static str) -> Vec<u8> {
+        Codec::default().encode(&event(name)).unwrap()
+    }
+
+    fn names(loaded: &Loaded) -> Vec<String> {
+        let codec = Codec::default();
+        let events = loaded.records.iter().filter_map(|r| codec.decode(r));
+        events.map(|e| e.event.to_string()).collect()
+    }
+
+    fn temp_path() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("spool-{}.bin", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_append_load_rewrite() {
+        let path = temp_path();
+        append(&path, &record("first")).unwrap();
+        #[cfg(feature = "msgpack")]
+        let format = PayloadFormat::MessagePack;
+        #[cfg(not(feature = "msgpack"))]
+        let format = PayloadFormat::Json;
+        // Records carry their own encoding, so formats can be mixed
+        #[allow(clippy::needless_update)]
+        let codec = Codec {
+            format,
+            ..Codec::default()
+        };
+        append(&path, &codec.encode(&event("second")).unwrap()).unwrap();
+
+        let loaded = load(&path).unwrap();
+        assert!(!loaded.needs_rewrite);
+        assert_eq!(names(&loaded), ["first", "second"]);
+
+        rewrite(&path, &loaded.records[1..]).unwrap();
+        assert_eq!(names(&load(&path).unwrap()), ["second"]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_damaged_records_are_skipped() {
+        let path = temp_path();
+        rewrite(&path, [record("first"), record("second"), record("third")]).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        let second = bytes
+            .windows(6)
+            .position(|window| window == b"second")
+            .unwrap();
+
+        // A record that fails its checksum is skipped, whether the payload
+        // or the encoding byte before it was damaged
+        let mut flipped = bytes.clone();
+        flipped[second - 1] |= 0x80;
+        fs::write(&path, &flipped).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(names(&loaded), ["first", "third"]);
+        assert_eq!(loaded.discarded, 1);
+        bytes[second] = b'S';
+        fs::write(&path, &bytes).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(names(&loaded), ["first", "third"]);
+        assert_eq!(loaded.discarded, 1);
+        assert!(loaded.needs_rewrite);
+
+        // A record cut short is discarded too, here in the same damaged
+        // stretch as the one before it
+        fs::write(&path, &bytes[..bytes.len() - 3]).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(names(&loaded), ["first"]);
+        assert_eq!(loaded.discarded, 1);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_recovery_after_bad_length() {
+        let path = temp_path();
+        rewrite(&path, [record("first"), record("second"), record("third")]).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        let second = bytes
+            .windows(6)
+            .position(|window| window == b"second")
+            .unwrap();
+        let mut framed = Vec::new();
+        frame(&mut framed, &record("second")).unwrap();
+        let position = framed.windows(6).position(|w| w == b"second").unwrap();
+
+        // The length now runs past the end of the file, so framing from it
+        // would lose the third record
+        let length = second - position;
+        bytes[length..length + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(names(&loaded), ["first", "third"]);
+        assert_eq!(loaded.discarded, 1);
+
+        // Garbage after a crash mid-write is skipped as one stretch
+        bytes.extend_from_slice(&[0xff; 64]);
+        framed.clear();
+        frame(&mut framed, &record("fourth")).unwrap();
+        bytes.extend_from_slice(&framed);
+        fs::write(&path, &bytes).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(names(&loaded), ["first", "third", "fourth"]);
+        assert_eq!(loaded.discarded, 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_zeroed_tail_is_discarded() {
+        let path = temp_path();
+        rewrite(&path, [record("first")]).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+
+        // A crash can leave the end of the file zeroed, which frames as
+        // empty records with a matching checksum
+        bytes.extend_from_slice(&[0; 90]);
+        fs::write(&path, &bytes).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.records.len(), 1);
+        assert_eq!(names(&loaded), ["first"]);
+        assert_eq!(loaded.discarded, 1);
+        assert!(loaded.needs_rewrite);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_versions() {
+        let path = temp_path();
+        let mut framed = Vec::new();
+        frame(&mut framed, &record("level_up")).unwrap();
+
+        // Header fields from a later minor revision are skipped
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&3u16.to_le_bytes());
+        bytes.extend_from_slice(b"new");
+        bytes.extend_from_slice(&framed);
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(load(&path).unwrap().records.len(), 1);
+
+        // Spools from an incompatible version are moved aside
+        bytes[8..10].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        assert!(load(&path).unwrap().records.is_empty());
+        let aside = path.with_extension(format!("v{}", VERSION + 1));
+        assert_eq!(fs::read(&aside).unwrap(), bytes);
+        assert!(!path.exists());
+        fs::remove_file(&aside).unwrap();
+
+        // Newline-delimited JSON from earlier releases
+        let line = serde_json::to_string(&event("level_up")).unwrap();
+        fs::write(&path, format!("{}\n{}\n", line, line)).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(names(&loaded), ["level_up", "level_up"]);
+        assert!(loaded.needs_rewrite);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_records() {
+        let key = SpoolKey::generate();
+        let sealed = Codec {
+            key: Some(key.clone()),
+            ..Codec::default()
+        };
+        let mut event = event("purchase");
+        event.user_id = "user123".to_string();
+        let record = sealed.encode(&event).unwrap();
+        assert!(!record.windows(7).any(|w| w == b"user123"));
+
+        assert_eq!(sealed.decode(&record).unwrap().user_id, "user123");
+        // Without the key, or with another one, sealed records are skipped
+        assert!(Codec::default().decode(&record).is_none());
+        let other = Codec {
+            key: Some(SpoolKey::generate()),
+            ..Codec::default()
+        };
+        assert!(other.decode(&record).is_none());
+        assert!(sealed.decode(&Codec::default().encode(&event).unwrap()).is_some());
+    }
+}
@@ -0,0 +1,123 @@
+use std::fmt;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+
+/// Bound to every sealed record, so spool ciphertext cannot pass for
+/// anything else encrypted with the same key
+const ASSOCIATED_DATA: &[u8] = b"game-events-io spool v1";
+
+const NONCE_LEN: usize = 12;
+
+/// AES-256-GCM key the spool is encrypted with
+///
+/// Each record gets a random nonce and an authentication tag, so a record
+/// that was tampered with, or written under another key, is skipped when the
+/// spool is loaded.
+#[derive(Clone)]
+pub struct SpoolKey {
+    cipher: Aes256Gcm,
+}
+
+impl SpoolKey {
+    pub fn new(key: [u8; 32]) -> Self {
+        SpoolKey {
+            cipher: Aes256Gcm::new(&key.into()),
+        }
+    }
+
+    /// A key from bytes kept elsewhere, which must be 32 long
+    pub fn from_slice(key: &[u8]) -> Result<Self, String> {
+        let key: [u8; 32] = key
+            .try_into()
+            .map_err(|_| format!("spool key must be 32 bytes, not {}", key.len()))?;
+        Ok(SpoolKey::new(key))
+    }
+
+    /// A new random key, for a [`SpoolKeyStore`] to save
+    pub fn generate() -> Self {
+        SpoolKey::new(rand::random())
+    }
+
+    /// Encrypt a record payload, prefixed with its nonce
+    pub(crate) fn seal(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let payload = Payload {
+            msg: plaintext,
+            aad: ASSOCIATED_DATA,
+        };
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .expect("AES-GCM encrypts any record the spool can hold");
+        [&nonce[..], &ciphertext].concat()
+    }
+
+    /// Decrypt a payload from [`seal`](Self::seal); `None` if it fails to
+    /// authenticate
+    pub(crate) fn open(&self, sealed: &[u8]) -> Option<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: ASSOCIATED_DATA,
+        };
+        self.cipher.decrypt(Nonce::from_slice(nonce), payload).ok()
+    }
+}
+
+impl fmt::Debug for SpoolKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SpoolKey(..)")
+    }
+}
+
+/// Where the spool key comes from, e.g. the platform keystore
+///
+/// Asked once, when the client is built; an error fails the build rather than
+/// spooling in plaintext. A [`SpoolKey`] is its own store.
+pub trait SpoolKeyStore: Send + Sync {
+    fn spool_key(&self) -> Result<SpoolKey, String>;
+}
+
+impl SpoolKeyStore for SpoolKey {
+    fn spool_key(&self) -> Result<SpoolKey, String> {
+        Ok(self.clone())
+    }
+}
+
+impl<F: Fn() -> Result<SpoolKey, String> + Send + Sync> SpoolKeyStore for F {
+    fn spool_key(&self) -> Result<SpoolKey, String> {
+        self()
+    }
+}
+
+impl fmt::Debug for dyn SpoolKeyStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SpoolKeyStore")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open() {
+        let key = SpoolKey::generate();
+        let sealed = key.seal(b"user123");
+        assert!(!sealed.windows(7).any(|window| window == b"user123"));
+        assert_eq!(key.open(&sealed).unwrap(), b"user123");
+        // Fresh nonce per record
+        assert_ne!(key.seal(b"user123"), sealed);
+
+        assert!(SpoolKey::generate().open(&sealed).is_none());
+        let mut tampered = sealed;
+        tampered[NONCE_LEN] ^= 1;
+        assert!(key.open(&tampered).is_none());
+
+        assert!(SpoolKey::from_slice(&[0; 16]).is_err());
+    }
+}
@@ -0,0 +1,54 @@
+//! Small pieces of SDK state (anonymous id, ...) persisted across restarts
+//!
+//! Each key is stored as a plain text file inside the configured state
+//! directory, or in the browser as a `localStorage` entry named
+//! `<state_dir>/<key>`.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Read a stored value, `None` if it was never written
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn read(dir: &Path, key: &str) -> io::Result<Option<String>> {
+    match fs::read_to_string(dir.join(key)) {
+        Ok(value) => Ok(Some(value.trim().to_string())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Store a value, creating the state directory if needed
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn write(dir: &Path, key: &str, value: &str) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(key), value)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn read(dir: &Path, key: &str) -> io::Result<Option<String>> {
+    let value = crate::web::storage_get(&dir.join(key).to_string_lossy())?;
+    Ok(value.map(|v| v.trim().to_string()))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn write(dir: &Path, key: &str, value: &str) -> io::Result<()> {
+    crate::web::storage_set(&dir.join(key).to_string_lossy(), value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_write() {
+        let dir = std::env::temp_dir().join(format!("state-{}", uuid::Uuid::new_v4()));
+        assert_eq!(read(&dir, "anonymous_id").unwrap(), None);
+
+        write(&dir, "anonymous_id", "abc").unwrap();
+        assert_eq!(read(&dir, "anonymous_id").unwrap().as_deref(), Some("abc"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,960 @@
+//! Where the client keeps its pending events and SDK state across restarts
+//!
+//! By default that is the spool file and the state directory; with the `sled`
+//! or `sqlite` feature both can live in an embedded database instead, and any
+//! other backend can be plugged in by implementing [`Storage`].
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(feature = "sqlite")]
+use std::sync::MutexGuard;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Mutex, PoisonError};
+#[cfg(feature = "sqlite")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::spool::{self, Codec};
+use crate::{state, GameEventsIOEvent};
+
+/// Records read per [`Storage::read_batch`] call when the client is built
+const LOAD_BATCH: usize = 1000;
+
+/// Backend for the queue of pending events and the small pieces of state
+/// (anonymous id, consent, remote config cache, ...) the client persists
+///
+/// Set one with [`GameEventsIOClientBuilder::storage`] for platforms the
+/// built-in backends do not fit, e.g. a console's save data API. Events reach
+/// the storage as opaque records, already encoded in the client's
+/// [`PayloadFormat`](crate::PayloadFormat) and sealed with its spool key, if
+/// any; the event itself is passed along for backends that index it.
+///
+/// The queue is first in, first out: the client appends events as they are
+/// logged, reads them back when it is built and acknowledges them once sent.
+///
+/// [`GameEventsIOClientBuilder::storage`]: crate::GameEventsIOClientBuilder::storage
+pub trait Storage: Send + Sync {
+    /// Name shown in the client's `Debug` output
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Add an event's record to the end of the queue
+    fn append(&self, event: &GameEventsIOEvent, record: &[u8]) -> io::Result<()>;
+
+    /// Up to `max` records, oldest first, skipping the first `offset`
+    fn read_batch(&self, offset: usize, max: usize) -> io::Result<Vec<Vec<u8>>>;
+
+    /// Delete the first `count` records, which have been sent
+    fn ack(&self, count: usize) -> io::Result<()>;
+
+    /// Replace every record, e.g. after events were purged
+    ///
+    /// Deletes and appends one record at a time unless overridden; backends
+    /// that can should do it atomically.
+    fn replace(
+        &self,
+        records: &mut dyn Iterator<Item = (&GameEventsIOEvent, Vec<u8>)>,
+    ) -> io::Result<()> {
+        self.ack(usize::MAX)?;
+        for (event, record) in records {
+            self.append(event, &record)?;
+        }
+        Ok(())
+    }
+
+    /// Read a state value, `None` if it was never written
+    fn read_state(&self, key: &str) -> io::Result<Option<String>>;
+
+    /// Store a state value
+    fn write_state(&self, key: &str, value: &str) -> io::Result<()>;
+
+    /// Drop damaged records and reclaim the space of deleted ones
+    ///
+    /// Run when the client is built, before the queue is read, and by
+    /// [`compact_storage`](crate::GameEventsIOClient::compact_storage). Does
+    /// nothing unless overridden.
+    fn compact(&self) -> io::Result<Compaction> {
+        Ok(Compaction::default())
+    }
+}
+
+/// What a [`Storage::compact`] pass kept and dropped
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Compaction {
+    /// Records that were intact and kept
+    pub salvaged: usize,
+
+    /// Damaged records that could not be read and were dropped; a damaged
+    /// stretch of the spool file counts as one
+    pub discarded: usize,
+}
+
+impl fmt::Debug for dyn Storage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The client's storage, if it has one, and how its events are encoded
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Store {
+    pub(crate) backend: Option<Arc<dyn Storage>>,
+    pub(crate) codec: Codec,
+
+    /// Whether the backend holds exactly the client's queue, in order, so
+    /// sent events can be acknowledged rather than the queue replaced
+    in_sync: bool,
+
+    /// Number of events at the back of the queue not stored yet
+    deferred: usize,
+}
+
+impl Store {
+    pub(crate) fn read_state(&self, key: &str) -> io::Result<Option<String>> {
+        match &self.backend {
+            Some(backend) => backend.read_state(key),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn write_state(&self, key: &str, value: &str) {
+        if let Some(backend) = &self.backend {
+            let _ = backend.write_state(key, value);
+        }
+    }
+
+    /// Every stored event, skipping records that fail to decode
+    ///
+    /// Also returns whether any records were read, in which case the queue
+    /// should be [replaced](Self::replace) to drop those that did not decode
+    /// and apply the current format and key; an empty backend is in sync
+    /// with the empty queue of a new client.
+    pub(crate) fn load(&mut self) -> io::Result<(Vec<GameEventsIOEvent>, bool)> {
+        let Some(backend) = &self.backend else {
+            return Ok((Vec::new(), false));
+        };
+        let mut events = Vec::new();
+        let mut read = 0;
+        loop {
+            let records = backend.read_batch(read, LOAD_BATCH)?;
+            read += records.len();
+            events.extend(records.iter().filter_map(|r| self.codec.decode(r)));
+            if records.len() < LOAD_BATCH {
+                self.in_sync = read == 0;
+                return Ok((events, read > 0));
+            }
+        }
+    }
+
+    /// Add the event just pushed onto the queue
+    ///
+    /// Returns whether the event was stored.
+    pub(crate) fn push(&mut self, event: &GameEventsIOEvent) -> bool {
+        let Some(backend) = &self.backend else {
+            return false;
+        };
+        let appended = self
+            .codec
+            .encode(event)
+            .and_then(|record| backend.append(event, &record));
+        self.in_sync &= appended.is_ok();
+        appended.is_ok()
+    }
+
+    /// Note that the event just pushed onto the queue is stored later, by
+    /// [`store_deferred`](Self::store_deferred)
+    pub(crate) fn defer(&mut self) {
+        if self.backend.is_some() {
+            self.deferred += 1;
+        }
+    }
+
+    /// Store the deferred events at the back of `queue`
+    pub(crate) fn store_deferred(&mut self, queue: &VecDeque<GameEventsIOEvent>) {
+        let deferred = std::mem::take(&mut self.deferred);
+        for event in queue.range(queue.len().saturating_sub(deferred)..) {
+            self.push(event);
+        }
+    }
+
+    /// Store exactly `events`
+    pub(crate) fn replace<'a>(&mut self, events: impl IntoIterator<Item = &'a GameEventsIOEvent>) {
+        self.deferred = 0;
+        if let Some(backend) = &self.backend {
+            let codec = &self.codec;
+            let mut records = events
+                .into_iter()
+                .filter_map(|event| Some((event, codec.encode(event).ok()?)));
+            self.in_sync = backend.replace(&mut records).is_ok();
+        }
+    }
+
+    /// Drop `count` sent events from the front of the queue, or store
+    /// `remaining` if the backend no longer matches the queue
+    pub(crate) fn settle<'a>(
+        &mut self,
+        count: usize,
+        remaining: impl IntoIterator<Item = &'a GameEventsIOEvent>,
+    ) {
+        let Some(backend) = &self.backend else {
+            return;
+        };
+        if self.in_sync && (count == 0 || backend.ack(count).is_ok()) {
+            return;
+        }
+        self.replace(remaining);
+    }
+
+    pub(crate) fn compact(&self) -> io::Result<Compaction> {
+        match &self.backend {
+            Some(backend) => backend.compact(),
+            None => Ok(Compaction::default()),
+        }
+    }
+
+    /// Note that the queue changed other than by [`push`](Self::push), so
+    /// the next [`settle`](Self::settle) replaces the stored queue
+    pub(crate) fn desync(&mut self) {
+        self.in_sync = false;
+    }
+}
+
+/// The spool file and the state directory
+///
+/// The spool is a versioned, checksummed binary file; each state key is a
+/// text file in the directory. In the browser both are `localStorage` entries.
+///
+/// The spool is parsed once, for the offsets of its records; later reads
+/// fetch only the records asked for, and acknowledged records are cut off
+/// the front of the file without parsing the rest.
+#[derive(Debug)]
+pub struct FileStorage {
+    spool_path: Option<PathBuf>,
+    state_dir: Option<PathBuf>,
+
+    /// Where each record's frame starts in the spool, once it has been read
+    #[cfg(not(target_arch = "wasm32"))]
+    offsets: Mutex<Option<Vec<u64>>>,
+}
+
+impl FileStorage {
+    pub fn new(spool_path: impl Into<PathBuf>, state_dir: impl Into<PathBuf>) -> Self {
+        FileStorage::with_paths(Some(spool_path.into()), Some(state_dir.into()))
+    }
+
+    pub(crate) fn with_paths(spool_path: Option<PathBuf>, state_dir: Option<PathBuf>) -> Self {
+        FileStorage {
+            spool_path,
+            state_dir,
+            #[cfg(not(target_arch = "wasm32"))]
+            offsets: Mutex::new(None),
+        }
+    }
+
+    /// Every record in the spool, rewriting it first if it is in a legacy
+    /// format or has damaged records, so the offsets match the file
+    fn load(path: &Path) -> io::Result<spool::Loaded> {
+        #[allow(unused_mut)]
+        let mut loaded = spool::load(path).map_err(|e| with_path(e, path))?;
+        if loaded.needs_rewrite {
+            spool::rewrite(path, &loaded.records)?;
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                loaded.offsets = spool::offsets(loaded.records.iter().map(Vec::len));
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Run `f` on the spool's record offsets, reading them the first time
+    ///
+    /// The offsets are forgotten if `f` fails, as the file may no longer
+    /// match them.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn with_offsets<T>(
+        &self,
+        path: &Path,
+        f: impl FnOnce(&mut Vec<u64>) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut cached = self.offsets.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut offsets = match cached.take() {
+            Some(offsets) => offsets,
+            None => FileStorage::load(path)?.offsets,
+        };
+        let result = f(&mut offsets);
+        if result.is_ok() {
+            *cached = Some(offsets);
+        }
+        result
+    }
+}
+
+/// Name the file an error came from
+fn with_path(e: io::Error, path: &Path) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+}
+
+impl Storage for FileStorage {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn append(&self, _event: &GameEventsIOEvent, record: &[u8]) -> io::Result<()> {
+        let Some(path) = &self.spool_path else {
+            return Ok(());
+        };
+        self.with_offsets(path, |offsets| {
+            offsets.push(spool::append(path, record)?);
+            Ok(())
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn append(&self, _event: &GameEventsIOEvent, record: &[u8]) -> io::Result<()> {
+        match &self.spool_path {
+            Some(path) => spool::append(path, record),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_batch(&self, offset: usize, max: usize) -> io::Result<Vec<Vec<u8>>> {
+        let Some(path) = &self.spool_path else {
+            return Ok(Vec::new());
+        };
+        self.with_offsets(path, |offsets| {
+            let Some(&start) = offsets.get(offset) else {
+                return Ok(Vec::new());
+            };
+            let end = offsets.get(offset.saturating_add(max)).copied();
+            spool::read_range(path, start, end).map_err(|e| with_path(e, path))
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read_batch(&self, offset: usize, max: usize) -> io::Result<Vec<Vec<u8>>> {
+        match &self.spool_path {
+            Some(path) => Ok(FileStorage::load(path)?
+                .records
+                .into_iter()
+                .skip(offset)
+                .take(max)
+                .collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn ack(&self, count: usize) -> io::Result<()> {
+        let Some(path) = &self.spool_path else {
+            return Ok(());
+        };
+        if count == 0 {
+            return Ok(());
+        }
+        self.with_offsets(path, |offsets| {
+            match offsets.get(count) {
+                Some(&at) => {
+                    spool::drop_front(path, at)?;
+                    offsets.drain(..count);
+                    for offset in offsets.iter_mut() {
+                        *offset = *offset - at + spool::HEADER_LEN;
+                    }
+                }
+                None => {
+                    spool::rewrite(path, std::iter::empty::<&[u8]>())?;
+                    offsets.clear();
+                }
+            }
+            Ok(())
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn ack(&self, count: usize) -> io::Result<()> {
+        match &self.spool_path {
+            Some(path) => {
+                let records = FileStorage::load(path)?.records;
+                spool::rewrite(path, records.iter().skip(count))
+            }
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn replace(
+        &self,
+        records: &mut dyn Iterator<Item = (&GameEventsIOEvent, Vec<u8>)>,
+    ) -> io::Result<()> {
+        let Some(path) = &self.spool_path else {
+            return Ok(());
+        };
+        let mut cached = self.offsets.lock().unwrap_or_else(PoisonError::into_inner);
+        *cached = None;
+        let mut lens = Vec::new();
+        spool::rewrite(
+            path,
+            records.map(|(_, record)| {
+                lens.push(record.len());
+                record
+            }),
+        )?;
+        *cached = Some(spool::offsets(lens));
+        Ok(())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn replace(
+        &self,
+        records: &mut dyn Iterator<Item = (&GameEventsIOEvent, Vec<u8>)>,
+    ) -> io::Result<()> {
+        match &self.spool_path {
+            Some(path) => spool::rewrite(path, records.map(|(_, record)| record)),
+            None => Ok(()),
+        }
+    }
+
+    fn read_state(&self, key: &str) -> io::Result<Option<String>> {
+        match &self.state_dir {
+            Some(dir) => state::read(dir, key).map_err(|e| with_path(e, dir)),
+            None => Ok(None),
+        }
+    }
+
+    fn write_state(&self, key: &str, value: &str) -> io::Result<()> {
+        match &self.state_dir {
+            Some(dir) => state::write(dir, key, value),
+            None => Ok(()),
+        }
+    }
+
+    /// Rewrite the spool without its damaged records, if it has any
+    fn compact(&self) -> io::Result<Compaction> {
+        let Some(path) = &self.spool_path else {
+            return Ok(Compaction::default());
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut cached = self.offsets.lock().unwrap_or_else(PoisonError::into_inner);
+        let loaded = FileStorage::load(path)?;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            *cached = Some(loaded.offsets);
+        }
+        Ok(Compaction {
+            salvaged: loaded.records.len(),
+            discarded: loaded.discarded,
+        })
+    }
+}
+
+/// Trees of a sled database, keyed by state key and by queue sequence number
+#[cfg(feature = "sled")]
+#[derive(Debug)]
+pub struct SledStorage {
+    db: sled::Db,
+    state: sled::Tree,
+    events: sled::Tree,
+}
+
+#[cfg(feature = "sled")]
+impl SledStorage {
+    /// Use the `game_events_io_state` and `game_events_io_events` trees of
+    /// `db`, which may be shared with the rest of the program
+    pub fn new(db: sled::Db) -> io::Result<Self> {
+        Ok(SledStorage {
+            state: db.open_tree("game_events_io_state")?,
+            events: db.open_tree("game_events_io_events")?,
+            db,
+        })
+    }
+
+    fn next_key(&self) -> io::Result<[u8; 8]> {
+        // Big-endian so the tree iterates in logging order
+        Ok(self.db.generate_id()?.to_be_bytes())
+    }
+}
+
+#[cfg(feature = "sled")]
+impl Storage for SledStorage {
+    fn name(&self) -> &str {
+        "sled"
+    }
+
+    fn append(&self, _event: &GameEventsIOEvent, record: &[u8]) -> io::Result<()> {
+        self.events.insert(self.next_key()?, record)?;
+        Ok(())
+    }
+
+    fn read_batch(&self, offset: usize, max: usize) -> io::Result<Vec<Vec<u8>>> {
+        let records = self.events.iter().values().skip(offset).take(max);
+        Ok(records
+            .map(|record| record.map(|r| r.to_vec()))
+            .collect::<Result<_, _>>()?)
+    }
+
+    fn ack(&self, count: usize) -> io::Result<()> {
+        let mut batch = sled::Batch::default();
+        for key in self.events.iter().keys().take(count) {
+            batch.remove(key?);
+        }
+        self.events.apply_batch(batch)?;
+        Ok(())
+    }
+
+    fn replace(
+        &self,
+        records: &mut dyn Iterator<Item = (&GameEventsIOEvent, Vec<u8>)>,
+    ) -> io::Result<()> {
+        // One batch, so a crash never leaves the old and new events mixed
+        let mut batch = sled::Batch::default();
+        for key in self.events.iter().keys() {
+            batch.remove(key?);
+        }
+        for (_, record) in records {
+            batch.insert(&self.next_key()?, record);
+        }
+        self.events.apply_batch(batch)?;
+        Ok(())
+    }
+
+    fn read_state(&self, key: &str) -> io::Result<Option<String>> {
+        let Some(value) = self.state.get(key)? else {
+            return Ok(None);
+        };
+        String::from_utf8(value.to_vec())
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn write_state(&self, key: &str, value: &str) -> io::Result<()> {
+        self.state.insert(key, value)?;
+        Ok(())
+    }
+}
+
+/// Default for how long unsent events are kept in a SQLite database
+#[cfg(feature = "sqlite")]
+pub(crate) const SQLITE_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Tables of a SQLite database in WAL mode
+///
+/// Each queued event is a row with its record and, for querying, its name,
+/// session id and time. Events older than the retention are pruned when the
+/// database is opened.
+#[cfg(feature = "sqlite")]
+#[derive(Debug)]
+pub struct SqliteStorage {
+    connection: Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStorage {
+    /// Open or create the database at `path`, pruning events older than
+    /// `retention`
+    pub fn open(path: impl AsRef<Path>, retention: Duration) -> io::Result<Self> {
+        let connection = rusqlite::Connection::open(path).map_err(sqlite_error)?;
+        connection
+            .pragma_update(None, "journal_mode", "WAL")
+            .map_err(sqlite_error)?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS game_events_io_state (
+                     key TEXT PRIMARY KEY,
+                     value TEXT NOT NULL
+                 );
+                 CREATE TABLE IF NOT EXISTS game_events_io_events (
+                     id INTEGER PRIMARY KEY AUTOINCREMENT,
+                     name TEXT NOT NULL,
+                     session_id TEXT NOT NULL,
+                     time INTEGER NOT NULL,
+                     record BLOB NOT NULL
+                 );
+                 CREATE INDEX IF NOT EXISTS game_events_io_events_session_id
+                     ON game_events_io_events (session_id);
+                 CREATE INDEX IF NOT EXISTS game_events_io_events_time
+                     ON game_events_io_events (time);",
+            )
+            .map_err(sqlite_error)?;
+
+        let cutoff = SystemTime::now()
+            .checked_sub(retention)
+            .and_then(|cutoff| cutoff.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |cutoff| cutoff.as_secs());
+        connection
+            .execute(
+                "DELETE FROM game_events_io_events WHERE time < ?1",
+                [to_sql_int(cutoff)],
+            )
+            .map_err(sqlite_error)?;
+        Ok(SqliteStorage {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    fn connection(&self) -> MutexGuard<'_, rusqlite::Connection> {
+        self.connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn sqlite_error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+/// SQLite integers are signed
+#[cfg(feature = "sqlite")]
+fn to_sql_int(value: impl TryInto<i64>) -> i64 {
+    value.try_into().unwrap_or(i64::MAX)
+}
+
+#[cfg(feature = "sqlite")]
+fn insert_event(
+    connection: &rusqlite::Connection,
+    event: &GameEventsIOEvent,
+    record: &[u8],
+) -> io::Result<()> {
+    connection
+        .prepare_cached(
+            "INSERT INTO game_events_io_events (name, session_id, time, record)
+             VALUES (?1, ?2, ?3, ?4)",
+        )
+        .and_then(|mut insert| {
+            insert.execute(rusqlite::params![
+                &*event.event,
+                event.session_id,
+                to_sql_int(event.time),
+                record,
+            ])
+        })
+        .map_err(sqlite_error)?;
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+impl Storage for SqliteStorage {
+    fn name(&self) -> &str {
+        "sqlite"
+    }
+
+    fn append(&self, event: &GameEventsIOEvent, record: &[u8]) -> io::Result<()> {
+        insert_event(&self.connection(), event, record)
+    }
+
+    fn read_batch(&self, offset: usize, max: usize) -> io::Result<Vec<Vec<u8>>> {
+        let connection = self.connection();
+        let mut select = connection
+            .prepare_cached(
+                "SELECT record FROM game_events_io_events ORDER BY id LIMIT ?1 OFFSET ?2",
+            )
+            .map_err(sqlite_error)?;
+        let records = select
+            .query_map([to_sql_int(max), to_sql_int(offset)], |row| row.get(0))
+            .map_err(sqlite_error)?;
+        records
+            .collect::<Result<_, _>>()
+            .map_err(sqlite_error)
+    }
+
+    fn ack(&self, count: usize) -> io::Result<()> {
+        self.connection()
+            .execute(
+                "DELETE FROM game_events_io_events WHERE id IN
+                     (SELECT id FROM game_events_io_events ORDER BY id LIMIT ?1)",
+                [to_sql_int(count)],
+            )
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn replace(
+        &self,
+        records: &mut dyn Iterator<Item = (&GameEventsIOEvent, Vec<u8>)>,
+    ) -> io::Result<()> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(sqlite_error)?;
+        transaction
+            .execute("DELETE FROM game_events_io_events", [])
+            .map_err(sqlite_error)?;
+        for (event, record) in records {
+            insert_event(&transaction, event, &record)?;
+        }
+        transaction.commit().map_err(sqlite_error)
+    }
+
+    fn read_state(&self, key: &str) -> io::Result<Option<String>> {
+        use rusqlite::OptionalExtension;
+
+        self.connection()
+            .query_row(
+                "SELECT value FROM game_events_io_state WHERE key = ?1",
+                [key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sqlite_error)
+    }
+
+    fn write_state(&self, key: &str, value: &str) -> io::Result<()> {
+        self.connection()
+            .execute(
+                "INSERT OR REPLACE INTO game_events_io_state (key, value) VALUES (?1, ?2)",
+                [key, value],
+            )
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    /// Fold the write-ahead log into the database and vacuum it; SQLite
+    /// verifies pages itself, so nothing is discarded
+    fn compact(&self) -> io::Result<Compaction> {
+        let connection = self.connection();
+        connection
+            .execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")
+            .map_err(sqlite_error)?;
+        let salvaged: i64 = connection
+            .query_row("SELECT COUNT(*) FROM game_events_io_events", [], |row| {
+                row.get(0)
+            })
+            .map_err(sqlite_error)?;
+        Ok(Compaction {
+            salvaged: usize::try_from(salvaged).unwrap_or_default(),
+            discarded: 0,
+        })
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOEventBuilder;
+
+    fn event(name: &'static str) -> GameEventsIOEvent {
+        GameEventsIOEventBuilder::default()
+            .event(name)
+            .build()
+            .unwrap()
+    }
+
+    /// Run the queue and state operations every backend must support
+    fn check_storage(storage: &dyn Storage) {
+        let codec = Codec::default();
+        let append = |name| {
+            let event = event(name);
+            storage
+                .append(&event, &codec.encode(&event).unwrap())
+                .unwrap();
+        };
+        let names = |offset, max| -> Vec<String> {
+            let records = storage.read_batch(offset, max).unwrap();
+            let events = records.iter().filter_map(|r| codec.decode(r));
+            events.map(|e| e.event.to_string()).collect()
+        };
+
+        assert_eq!(storage.read_state("anonymous_id").unwrap(), None);
+        storage.write_state("anonymous_id", "abc").unwrap();
+        storage.write_state("anonymous_id", "def").unwrap();
+        assert_eq!(
+            storage.read_state("anonymous_id").unwrap().as_deref(),
+            Some("def")
+        );
+
+        for name in ["first", "second", "third"] {
+            append(name);
+        }
+        assert_eq!(names(0, 10), ["first", "second", "third"]);
+        assert_eq!(names(1, 1), ["second"]);
+
+        storage.ack(2).unwrap();
+        assert_eq!(names(0, 10), ["third"]);
+        append("fourth");
+        assert_eq!(names(0, 10), ["third", "fourth"]);
+
+        let replacement = [event("fifth")];
+        let mut records = replacement
+            .iter()
+            .map(|event| (event, codec.encode(event).unwrap()));
+        storage.replace(&mut records).unwrap();
+        assert_eq!(names(0, 10), ["fifth"]);
+
+        // Nothing intact is lost to compaction
+        assert_eq!(storage.compact().unwrap().discarded, 0);
+        assert_eq!(names(0, 10), ["fifth"]);
+
+        // State and events are kept apart
+        assert_eq!(
+            storage.read_state("anonymous_id").unwrap().as_deref(),
+            Some("def")
+        );
+    }
+
+    #[test]
+    fn test_file_storage() {
+        let dir = std::env::temp_dir().join(format!("storage-{}", uuid::Uuid::new_v4()));
+        let storage = FileStorage::new(dir.join("events.spool"), &dir);
+        check_storage(&storage);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_storage_offsets() {
+        let dir = std::env::temp_dir().join(format!("storage-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.spool");
+        let codec = Codec::default();
+        let names = |records: Vec<Vec<u8>>| -> Vec<String> {
+            let events = records.iter().filter_map(|r| codec.decode(r));
+            events.map(|e| e.event.to_string()).collect()
+        };
+
+        // Records start after a longer header than this version writes
+        let mut bytes = b"GEIOSPL\0".to_vec();
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&3u16.to_le_bytes());
+        bytes.extend_from_slice(b"new");
+        std::fs::write(&path, &bytes).unwrap();
+        let storage = FileStorage::new(&path, &dir);
+        for i in 0..25 {
+            let event = event(if i % 2 == 0 { "even" } else { "odd" });
+            storage
+                .append(&event, &codec.encode(&event).unwrap())
+                .unwrap();
+        }
+
+        assert_eq!(names(storage.read_batch(3, 2).unwrap()), ["odd", "even"]);
+        assert_eq!(storage.read_batch(24, 10).unwrap().len(), 1);
+        assert!(storage.read_batch(25, 10).unwrap().is_empty());
+
+        storage.ack(5).unwrap();
+        assert_eq!(names(storage.read_batch(0, 2).unwrap()), ["odd", "even"]);
+        storage.ack(2).unwrap();
+        let event = event("last");
+        storage
+            .append(&event, &codec.encode(&event).unwrap())
+            .unwrap();
+        assert_eq!(storage.read_batch(0, 100).unwrap().len(), 19);
+        assert_eq!(names(storage.read_batch(18, 1).unwrap()), ["last"]);
+
+        // The file itself matches the offsets
+        let loaded = spool::load(&path).unwrap();
+        assert!(!loaded.needs_rewrite);
+        assert_eq!(loaded.offsets, spool::offsets(loaded.records.iter().map(Vec::len)));
+        assert_eq!(loaded.records, storage.read_batch(0, 100).unwrap());
+
+        storage.ack(usize::MAX).unwrap();
+        assert!(spool::load(&path).unwrap().records.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_storage_compaction() {
+        let dir = std::env::temp_dir().join(format!("storage-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("events.spool");
+        let storage = FileStorage::new(&path, &dir);
+        let codec = Codec::default();
+        let records: Vec<_> = ["first", "second", "third"]
+            .map(|name| codec.encode(&event(name)).unwrap())
+            .to_vec();
+        std::fs::create_dir_all(&dir).unwrap();
+        spool::rewrite(&path, &records).unwrap();
+        let mut bytes = std::fs::read(&path).unwrap();
+        let second = bytes.windows(6).position(|w| w == b"second").unwrap();
+        bytes[second] = b'S';
+        std::fs::write(&path, &bytes).unwrap();
+
+        let compaction = storage.compact().unwrap();
+        assert_eq!(
+            compaction,
+            Compaction {
+                salvaged: 2,
+                discarded: 1
+            }
+        );
+        let loaded = spool::load(&path).unwrap();
+        assert_eq!((loaded.records.len(), loaded.discarded), (2, 0));
+        assert!(!loaded.needs_rewrite);
+        assert_eq!(storage.compact().unwrap().discarded, 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_default_replace() {
+        /// Only the required methods, over the file storage
+        struct Minimal(FileStorage);
+        impl Storage for Minimal {
+            fn append(&self, event: &GameEventsIOEvent, record: &[u8]) -> io::Result<()> {
+                self.0.append(event, record)
+            }
+            fn read_batch(&self, offset: usize, max: usize) -> io::Result<Vec<Vec<u8>>> {
+                self.0.read_batch(offset, max)
+            }
+            fn ack(&self, count: usize) -> io::Result<()> {
+                self.0.ack(count)
+            }
+            fn read_state(&self, key: &str) -> io::Result<Option<String>> {
+                self.0.read_state(key)
+            }
+            fn write_state(&self, key: &str, value: &str) -> io::Result<()> {
+                self.0.write_state(key, value)
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("storage-{}", uuid::Uuid::new_v4()));
+        check_storage(&Minimal(FileStorage::new(dir.join("events.spool"), &dir)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "sled")]
+    #[test]
+    fn test_sled_storage() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        check_storage(&SledStorage::new(db).unwrap());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_storage() {
+        let path = std::env::temp_dir().join(format!("storage-{}.db", uuid::Uuid::new_v4()));
+        let retention = Duration::from_secs(24 * 60 * 60);
+        let storage = SqliteStorage::open(&path, retention).unwrap();
+        let journal_mode: String = storage
+            .connection()
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+        check_storage(&storage);
+
+        let codec = Codec::default();
+        let mut stale = event("stale");
+        stale.time -= 2 * 24 * 60 * 60;
+        let mut other_session = event("other_session");
+        other_session.session_id = "session-2".into();
+        for event in [stale, other_session] {
+            storage
+                .append(&event, &codec.encode(&event).unwrap())
+                .unwrap();
+        }
+        let sessions: i64 = storage
+            .connection()
+            .query_row(
+                "SELECT COUNT(DISTINCT session_id) FROM game_events_io_events",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(sessions, 2);
+
+        // Reopening prunes events past the retention
+        drop(storage);
+        let storage = SqliteStorage::open(&path, retention).unwrap();
+        assert_eq!(storage.read_batch(0, 10).unwrap().len(), 2);
+
+        drop(storage);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
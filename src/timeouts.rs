@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+/// How long requests to the backend may take before they fail with
+/// [`GameEventsIOError::Timeout`](crate::GameEventsIOError::Timeout)
+///
+/// `total` applies to every transport. `connect` and `read` configure the
+/// default reqwest transports; custom transports take theirs when created.
+/// reqwest cannot time out individual reads, so `read` caps each request at
+/// `connect + read` instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Builder)]
+#[builder(setter(into, strip_option))]
+#[builder(default)]
+pub struct Timeouts {
+    /// Give up connecting after this long
+    pub connect: Option<Duration>,
+
+    /// Give up waiting for the backend to respond after this long
+    pub read: Option<Duration>,
+
+    /// Give up on a request after this long, retries not included
+    /// (default: 15 seconds)
+    pub total: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts {
+            connect: None,
+            read: None,
+            total: Some(crate::REQUEST_TIMEOUT),
+        }
+    }
+}
+
+impl Timeouts {
+    /// The timeout for a request that must also finish by `deadline`
+    /// (a remaining duration)
+    pub(crate) fn for_request(&self, deadline: Option<Duration>) -> Option<Duration> {
+        self.total.into_iter().chain(deadline).min()
+    }
+
+    /// The longest a whole request may take with reqwest, which has no read
+    /// timeout of its own
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn reqwest_cap(&self) -> Option<Duration> {
+        let read_cap = self
+            .read
+            .map(|read| self.connect.unwrap_or_default().saturating_add(read));
+        self.total.into_iter().chain(read_cap).min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_timeouts() {
+        let timeouts = TimeoutsBuilder::default()
+            .connect(Duration::from_secs(2))
+            .read(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        assert_eq!(timeouts.total, Some(Duration::from_secs(15)));
+        assert_eq!(timeouts.reqwest_cap(), Some(Duration::from_secs(7)));
+        assert_eq!(
+            timeouts.for_request(Some(Duration::from_secs(3))),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(timeouts.for_request(None), Some(Duration::from_secs(15)));
+
+        let unlimited = Timeouts {
+            total: None,
+            ..Timeouts::default()
+        };
+        assert_eq!(unlimited.for_request(None), None);
+        assert_eq!(unlimited.reqwest_cap(), None);
+    }
+}
@@ -0,0 +1,222 @@
+use std::path::Path;
+
+use web_time::SystemTime;
+
+/// Directory the IANA time zone database is installed in on Unix systems
+#[cfg(unix)]
+const ZONEINFO_DIR: &str = "/usr/share/zoneinfo";
+
+/// The device's local time zone
+///
+/// Detected on a best-effort basis: on Unix from the `TZ` variable and
+/// `/etc/localtime`, in the browser from `Intl` and `Date`. Fields that
+/// cannot be determined are left out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LocalTimeZone {
+    /// IANA time zone name, e.g. "Europe/Berlin"
+    pub name: Option<String>,
+
+    /// Offset from UTC in minutes, e.g. 60 for UTC+01:00
+    pub utc_offset_minutes: Option<i32>,
+}
+
+impl LocalTimeZone {
+    /// Detect the time zone in effect at `at`
+    pub fn detect(at: SystemTime) -> Self {
+        detect(at)
+    }
+}
+
+#[cfg(unix)]
+fn detect(at: SystemTime) -> LocalTimeZone {
+    let tz = std::env::var("TZ").ok();
+    let link = std::fs::read_link("/etc/localtime").ok();
+    let mut name = zone_name(tz.as_deref(), link.as_deref());
+    if tz.is_none() && name.is_none() {
+        name = std::fs::read_to_string("/etc/timezone")
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+    }
+
+    // A zone named by TZ overrides /etc/localtime; POSIX rules in TZ are
+    // not interpreted
+    let zone_file = match (&tz, &name) {
+        (None, _) => Some(Path::new("/etc/localtime").to_path_buf()),
+        (Some(_), Some(name)) => Some(Path::new(ZONEINFO_DIR).join(name)),
+        (Some(_), None) => None,
+    };
+    let utc_offset_minutes = zone_file
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|tzif| utc_offset(&tzif, crate::clock::unix_secs(at) as i64))
+        .map(|secs| secs / 60);
+    LocalTimeZone {
+        name,
+        utc_offset_minutes,
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn detect(at: SystemTime) -> LocalTimeZone {
+    let millis = crate::clock::unix_millis(at) as f64;
+    let date = js_sys::Date::new(&millis.into());
+    let options = js_sys::Intl::DateTimeFormat::new(&js_sys::Array::new(), &js_sys::Object::new())
+        .resolved_options();
+    let name = js_sys::Reflect::get(&options, &"timeZone".into())
+        .ok()
+        .and_then(|zone| zone.as_string());
+    LocalTimeZone {
+        name,
+        // getTimezoneOffset is UTC minus local time
+        utc_offset_minutes: Some(-(date.get_timezone_offset() as i32)),
+    }
+}
+
+#[cfg(not(any(unix, target_arch = "wasm32")))]
+fn detect(_at: SystemTime) -> LocalTimeZone {
+    LocalTimeZone::default()
+}
+
+/// The zone name from a `TZ` value such as ":Europe/Berlin", or else from
+/// the target of the `/etc/localtime` link
+#[cfg_attr(not(unix), allow(dead_code))]
+fn zone_name(tz: Option<&str>, localtime_link: Option<&Path>) -> Option<String> {
+    if let Some(tz) = tz {
+        let tz = tz.strip_prefix(':').unwrap_or(tz);
+        // POSIX rules such as "CET-1CEST" have no IANA name
+        return match tz.split_once("zoneinfo/") {
+            Some((_, name)) => Some(name.to_string()),
+            None if tz.contains('/') && !tz.starts_with('/') => Some(tz.to_string()),
+            None if tz == "UTC" => Some(tz.to_string()),
+            None => None,
+        };
+    }
+    let link = localtime_link?.to_str()?;
+    let (_, name) = link.split_once("zoneinfo/")?;
+    Some(name.to_string())
+}
+
+/// UTC offset in seconds at `now` (a Unix timestamp) from a TZif file
+///
+/// Uses the transition table only, so for times after the last listed
+/// transition the offset of that transition applies.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn utc_offset(tzif: &[u8], now: i64) -> Option<i32> {
+    let header = TzifHeader::parse(tzif)?;
+    let (header, data, time_size) = if header.version >= b'2' {
+        // Skip the 32-bit block in favor of the 64-bit one that follows it
+        let rest = tzif.get(44 + header.data_len(4)..)?;
+        (TzifHeader::parse(rest)?, rest.get(44..)?, 8)
+    } else {
+        (header, tzif.get(44..)?, 4)
+    };
+
+    let times = data.get(..header.time_count * time_size)?;
+    let indexes = data.get(times.len()..times.len() + header.time_count)?;
+    let types = data.get(times.len() + indexes.len()..)?;
+    let transition = times
+        .chunks(time_size)
+        .map(|time| match time_size {
+            8 => i64::from_be_bytes(time.try_into().unwrap()),
+            _ => i32::from_be_bytes(time.try_into().unwrap()) as i64,
+        })
+        .take_while(|&time| time <= now)
+        .count();
+    // Before the first transition the first local time type applies
+    let local_type = match transition {
+        0 => 0,
+        n => *indexes.get(n - 1)? as usize,
+    };
+    let info = types.get(local_type * 6..local_type * 6 + 4)?;
+    Some(i32::from_be_bytes(info.try_into().unwrap()))
+}
+
+/// The counts from a TZif header
+struct TzifHeader {
+    version: u8,
+    ut_count: usize,
+    std_count: usize,
+    leap_count: usize,
+    time_count: usize,
+    type_count: usize,
+    char_count: usize,
+}
+
+impl TzifHeader {
+    fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(..4)? != b"TZif" {
+            return None;
+        }
+        let version = *data.get(4)?;
+        let count = |i: usize| {
+            let bytes = data.get(20 + i * 4..24 + i * 4)?;
+            Some(u32::from_be_bytes(bytes.try_into().unwrap()) as usize)
+        };
+        Some(TzifHeader {
+            version,
+            ut_count: count(0)?,
+            std_count: count(1)?,
+            leap_count: count(2)?,
+            time_count: count(3)?,
+            type_count: count(4)?,
+            char_count: count(5)?,
+        })
+    }
+
+    /// Length of the data block that follows the header
+    fn data_len(&self, time_size: usize) -> usize {
+        self.time_count * (time_size + 1)
+            + self.type_count * 6
+            + self.char_count
+            + self.leap_count * (time_size + 4)
+            + self.std_count
+            + self.ut_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A version 1 TZif file switching from UTC+1 to UTC+2 at `switch`
+    fn tzif(switch: i32) -> Vec<u8> {
+        let mut data = b"TZif".to_vec();
+        data.extend_from_slice(&[0; 16]);
+        for count in [0u32, 0, 0, 1, 2, 8] {
+            data.extend_from_slice(&count.to_be_bytes());
+        }
+        data.extend_from_slice(&switch.to_be_bytes());
+        data.push(1);
+        data.extend_from_slice(&3600i32.to_be_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(&7200i32.to_be_bytes());
+        data.extend_from_slice(&[1, 4]);
+        data.extend_from_slice(b"CET\0CEST");
+        data
+    }
+
+    #[test]
+    fn test_utc_offset() {
+        let file = tzif(1_000);
+        assert_eq!(utc_offset(&file, 999), Some(3600));
+        assert_eq!(utc_offset(&file, 1_000), Some(7200));
+        assert_eq!(utc_offset(&file, i64::MAX), Some(7200));
+        assert_eq!(utc_offset(b"not a zone file", 0), None);
+        assert_eq!(utc_offset(&file[..50], 0), None);
+    }
+
+    #[test]
+    fn test_zone_name() {
+        let link = Path::new("/usr/share/zoneinfo/America/New_York");
+        assert_eq!(
+            zone_name(None, Some(link)).as_deref(),
+            Some("America/New_York")
+        );
+        assert_eq!(
+            zone_name(Some(":Europe/Berlin"), Some(link)).as_deref(),
+            Some("Europe/Berlin")
+        );
+        assert_eq!(zone_name(Some("CET-1CEST"), Some(link)), None);
+        assert_eq!(zone_name(None, None), None);
+    }
+}
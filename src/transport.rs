@@ -0,0 +1,767 @@
+//! HTTP stacks the client can send requests with
+//!
+//! The client talks to the backend through an [`HttpTransport`] (blocking
+//! flushes) and an [`AsyncHttpTransport`] (async flushes). Both default to
+//! [`ReqwestTransport`]; enable the `ureq` or `hyper` feature to use the
+//! stack the game already links instead, or implement the traits for
+//! another one.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::tls::ReqwestTls;
+use crate::{GameEventsIOError, Proxy};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{Timeouts, TlsConfig};
+
+/// A POST request to the backend
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+
+    /// Give up after this long; transports without per-request timeouts
+    /// apply their own
+    pub timeout: Option<Duration>,
+}
+
+/// The backend's response, whatever its status
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+/// Future returned by [`AsyncHttpTransport::post`]
+#[cfg(not(target_arch = "wasm32"))]
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, GameEventsIOError>> + Send + 'a>>;
+
+/// Future returned by [`AsyncHttpTransport::post`]
+#[cfg(target_arch = "wasm32")]
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, GameEventsIOError>> + 'a>>;
+
+/// Sends requests for blocking flushes
+///
+/// Return `Err` only when no response was received; error statuses are
+/// classified by the client.
+pub trait HttpTransport: Send + Sync {
+    /// Name shown in the client's `Debug` output
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Send `request` and wait for the response
+    fn post(&self, request: HttpRequest) -> Result<HttpResponse, GameEventsIOError>;
+}
+
+/// Sends requests for async flushes
+pub trait AsyncHttpTransport: Send + Sync {
+    /// Name shown in the client's `Debug` output
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Send `request`, resolving to the response
+    fn post(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+impl fmt::Debug for dyn HttpTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl fmt::Debug for dyn AsyncHttpTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The parts of a successful response the client uses
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Received {
+    pub body: String,
+
+    /// `Date` header as a Unix timestamp
+    pub date: Option<u64>,
+
+    /// `X-Request-Id` header
+    pub request_id: Option<String>,
+}
+
+/// A sink's response
+impl From<String> for Received {
+    fn from(body: String) -> Self {
+        Received {
+            body,
+            ..Received::default()
+        }
+    }
+}
+
+impl HttpResponse {
+    /// The parts of a successful response, or the error a failed one stands
+    /// for
+    pub(crate) fn into_result(self) -> Result<Received, GameEventsIOError> {
+        if !self.status.is_success() {
+            return Err(GameEventsIOError::from_response(
+                self.status,
+                &self.headers,
+                self.body,
+            ));
+        }
+        let date = self
+            .headers
+            .get(reqwest::header::DATE)
+            .and_then(|date| date.to_str().ok())
+            .and_then(|date| crate::time_format::parse_http_date(date.trim()));
+        let request_id = self
+            .headers
+            .get("x-request-id")
+            .and_then(|id| id.to_str().ok())
+            .map(String::from);
+        Ok(Received {
+            body: self.body,
+            date,
+            request_id,
+        })
+    }
+}
+
+/// The default transport, built on reqwest
+///
+/// Its clients are created on first use, so an async-only client never
+/// spins up reqwest's blocking runtime inside someone else's executor.
+#[derive(Debug, Default)]
+pub struct ReqwestTransport {
+    #[cfg(not(target_arch = "wasm32"))]
+    blocking: std::sync::OnceLock<reqwest::blocking::Client>,
+    client: std::sync::OnceLock<reqwest::Client>,
+
+    /// Ignore the system's proxy settings
+    #[cfg(not(target_arch = "wasm32"))]
+    direct: bool,
+
+    /// Proxy to use instead of the system's
+    #[cfg(not(target_arch = "wasm32"))]
+    proxy: Option<reqwest::Proxy>,
+
+    /// TLS settings to use instead of the defaults
+    #[cfg(not(target_arch = "wasm32"))]
+    tls: Option<ReqwestTls>,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    timeouts: Timeouts,
+}
+
+/// Apply a [`ReqwestTransport`]'s settings to a blocking or async reqwest
+/// client builder, which share method names but not a trait
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! configure {
+    ($transport:expr, $builder:expr) => {{
+        let mut builder = $builder;
+        if let Some(timeout) = $transport.timeouts.reqwest_cap() {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = $transport.timeouts.connect {
+            builder = builder.connect_timeout(timeout);
+        }
+        if $transport.direct {
+            builder = builder.no_proxy();
+        }
+        if let Some(proxy) = &$transport.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        match &$transport.tls {
+            None => {}
+            #[cfg(not(feature = "rustls"))]
+            Some(ReqwestTls::Native {
+                roots,
+                only_custom_roots,
+                min_version,
+            }) => {
+                builder = builder.tls_built_in_root_certs(!only_custom_roots);
+                for root in roots {
+                    builder = builder.add_root_certificate(root.clone());
+                }
+                if let Some(version) = min_version {
+                    builder = builder.min_tls_version(*version);
+                }
+            }
+            #[cfg(feature = "rustls")]
+            Some(ReqwestTls::Rustls(config)) => {
+                builder = builder.use_preconfigured_tls(config.clone());
+            }
+        }
+        builder
+    }};
+}
+
+impl ReqwestTransport {
+    /// Use the system's proxy settings, including `NO_PROXY`
+    pub fn new() -> Self {
+        ReqwestTransport::default()
+    }
+
+    /// Connect through `proxy`
+    ///
+    /// [`Proxy::FromEnvironment`] uses reqwest's own detection, which also
+    /// honors `NO_PROXY`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_proxy(proxy: &Proxy) -> Result<Self, String> {
+        let mut transport = ReqwestTransport::new();
+        match proxy {
+            Proxy::FromEnvironment => {}
+            Proxy::Direct => transport.direct = true,
+            Proxy::Url { .. } => {
+                let Some((url, credentials)) = proxy.resolve(|_| None)? else {
+                    unreachable!("a proxy URL always resolves");
+                };
+                let mut reqwest_proxy = reqwest::Proxy::all(url.as_str())
+                    .map_err(|e| format!("invalid proxy URL {:?}: {}", url.as_str(), e))?;
+                if let Some((username, password)) = credentials {
+                    reqwest_proxy = reqwest_proxy.basic_auth(&username, &password);
+                }
+                transport.proxy = Some(reqwest_proxy);
+            }
+        }
+        Ok(transport)
+    }
+
+    /// Use `tls` instead of the default TLS settings
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_tls(mut self, tls: &TlsConfig) -> Result<Self, String> {
+        self.tls = Some(tls.for_reqwest()?);
+        // Surface settings the TLS backend rejects now rather than on the
+        // first request
+        configure!(self, reqwest::Client::builder())
+            .build()
+            .map_err(|e| format!("invalid TLS settings: {}", e))?;
+        Ok(self)
+    }
+
+    /// Use `timeouts` instead of the default 15 second limit per request
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// The timeout for a request asking for `timeout`, which replaces the
+    /// client's own
+    #[cfg(not(target_arch = "wasm32"))]
+    fn request_timeout(&self, timeout: Option<Duration>) -> Option<Duration> {
+        timeout.into_iter().chain(self.timeouts.reqwest_cap()).min()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn blocking_client(&self) -> reqwest::blocking::Client {
+        let mut builder = configure!(self, reqwest::blocking::Client::builder());
+        // Unlike the async client, the blocking one has a default timeout
+        if self.timeouts.reqwest_cap().is_none() {
+            builder = builder.timeout(None);
+        }
+        builder.build().expect("failed to build reqwest client")
+    }
+
+    fn async_client(&self) -> reqwest::Client {
+        let builder = reqwest::Client::builder();
+        // Browsers apply their own timeouts, proxies and TLS settings to
+        // fetch requests
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = configure!(self, builder);
+        builder.build().expect("failed to build reqwest client")
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HttpTransport for ReqwestTransport {
+    fn name(&self) -> &str {
+        "reqwest"
+    }
+
+    fn post(&self, mut request: HttpRequest) -> Result<HttpResponse, GameEventsIOError> {
+        request.timeout = self.request_timeout(request.timeout);
+        post_reqwest_blocking(
+            self.blocking.get_or_init(|| self.blocking_client()),
+            request,
+        )
+    }
+}
+
+impl AsyncHttpTransport for ReqwestTransport {
+    fn name(&self) -> &str {
+        "reqwest"
+    }
+
+    fn post(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let client = self.client.get_or_init(|| self.async_client());
+        Box::pin(async move {
+            #[cfg(not(target_arch = "wasm32"))]
+            let timeout = self.request_timeout(request.timeout);
+            let builder = client
+                .post(request.url)
+                .headers(request.headers)
+                .body(request.body);
+            // Browsers apply their own timeouts to fetch requests
+            #[cfg(not(target_arch = "wasm32"))]
+            let builder = match timeout {
+                Some(timeout) => builder.timeout(timeout),
+                None => builder,
+            };
+            let response = builder.send().await?;
+            Ok(HttpResponse {
+                status: response.status(),
+                headers: response.headers().clone(),
+                body: response.text().await?,
+            })
+        })
+    }
+}
+
+/// POST with a blocking reqwest client, as shared with the HTTP based sinks
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn post_reqwest_blocking(
+    client: &reqwest::blocking::Client,
+    request: HttpRequest,
+) -> Result<HttpResponse, GameEventsIOError> {
+    let mut builder = client
+        .post(request.url)
+        .headers(request.headers)
+        .body(request.body);
+    if let Some(timeout) = request.timeout {
+        builder = builder.timeout(timeout);
+    }
+    let response = builder.send()?;
+    Ok(HttpResponse {
+        status: response.status(),
+        headers: response.headers().clone(),
+        body: response.text()?,
+    })
+}
+
+/// Blocking transport built on ureq
+#[cfg(feature = "ureq")]
+#[derive(Debug)]
+pub struct UreqTransport {
+    agent: ureq::Agent,
+
+    /// `Proxy-Authorization` value for plain HTTP requests; ureq only sends
+    /// proxy credentials when tunnelling HTTPS
+    proxy_authorization: Option<String>,
+}
+
+#[cfg(feature = "ureq")]
+impl UreqTransport {
+    /// Use a new agent with the client's default timeout and the proxy from
+    /// the environment, if one is set and usable
+    pub fn new() -> Self {
+        UreqTransport::with_proxy(&Proxy::FromEnvironment)
+            .unwrap_or_else(|_| UreqTransport::with_proxy(&Proxy::Direct).unwrap())
+    }
+
+    /// Use a new agent with the client's default timeout that connects
+    /// through `proxy`
+    pub fn with_proxy(proxy: &Proxy) -> Result<Self, String> {
+        let mut builder = ureq::AgentBuilder::new().timeout(crate::REQUEST_TIMEOUT);
+        let mut proxy_authorization = None;
+        if let Some((url, credentials)) = proxy.resolve(|key| std::env::var(key).ok())? {
+            let invalid = |e: ureq::Error| format!("invalid proxy URL {:?}: {}", url.as_str(), e);
+            let host = url.host_str().unwrap_or_default();
+            let address = match url.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host.to_string(),
+            };
+            // ureq takes the credentials as they are, not percent-encoded
+            let proxy = match &credentials {
+                Some((username, password)) => {
+                    format!("{}://{}:{}@{}", url.scheme(), username, password, address)
+                }
+                None => format!("{}://{}", url.scheme(), address),
+            };
+            builder = builder.proxy(ureq::Proxy::new(proxy).map_err(invalid)?);
+            if url.scheme() == "http" {
+                proxy_authorization = credentials.map(|(username, password)| {
+                    let credentials = format!("{}:{}", username, password);
+                    format!("Basic {}", crate::segment::base64(credentials.as_bytes()))
+                });
+            }
+        }
+        Ok(UreqTransport {
+            agent: builder.build(),
+            proxy_authorization,
+        })
+    }
+
+    /// Share an agent (and its connection pool) the game already has
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        UreqTransport {
+            agent,
+            proxy_authorization: None,
+        }
+    }
+}
+
+#[cfg(feature = "ureq")]
+impl Default for UreqTransport {
+    fn default() -> Self {
+        UreqTransport::new()
+    }
+}
+
+#[cfg(feature = "ureq")]
+impl HttpTransport for UreqTransport {
+    fn name(&self) -> &str {
+        "ureq"
+    }
+
+    fn post(&self, request: HttpRequest) -> Result<HttpResponse, GameEventsIOError> {
+        let mut call = self.agent.post(&request.url);
+        if let Some(timeout) = request.timeout {
+            call = call.timeout(timeout);
+        }
+        for (name, value) in &request.headers {
+            if let Ok(value) = value.to_str() {
+                call = call.set(name.as_str(), value);
+            }
+        }
+        // HTTPS requests authenticate when the tunnel is set up instead
+        if let Some(authorization) = &self.proxy_authorization {
+            if request.url.starts_with("http://") {
+                call = call.set("Proxy-Authorization", authorization);
+            }
+        }
+        let response = match call.send_bytes(&request.body) {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(e) if is_timeout(&e) => return Err(GameEventsIOError::Timeout(Box::new(e))),
+            Err(e) => return Err(GameEventsIOError::Transport(Box::new(e))),
+        };
+
+        let status = StatusCode::from_u16(response.status())
+            .map_err(|e| GameEventsIOError::Transport(Box::new(e)))?;
+        let mut headers = HeaderMap::new();
+        for name in response.headers_names() {
+            let (Ok(name), Some(value)) = (
+                reqwest::header::HeaderName::try_from(name.as_str()),
+                response.header(&name),
+            ) else {
+                continue;
+            };
+            if let Ok(value) = value.parse() {
+                headers.append(name, value);
+            }
+        }
+        let body = response.into_string()?;
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// Whether an error, or one of its sources, is an I/O timeout
+#[cfg(feature = "ureq")]
+fn is_timeout(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if let Some(io) = error.downcast_ref::<std::io::Error>() {
+            if matches!(
+                io.kind(),
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+            ) {
+                return true;
+            }
+        }
+        source = error.source();
+    }
+    false
+}
+
+/// Connector of the clients [`HyperTransport`] sends requests with
+#[cfg(feature = "hyper")]
+pub type HyperConnector =
+    hyper_proxy::ProxyConnector<hyper_tls::HttpsConnector<hyper::client::HttpConnector>>;
+
+/// Async transport built on hyper, for use inside a Tokio runtime
+///
+/// Only HTTP proxies are supported; HTTPS requests tunnel through them with
+/// `CONNECT`.
+#[cfg(feature = "hyper")]
+#[derive(Clone, Debug)]
+pub struct HyperTransport {
+    client: hyper::Client<HyperConnector>,
+
+    /// Headers the proxy needs on plain HTTP requests, e.g. its credentials
+    proxy_headers: HeaderMap,
+}
+
+#[cfg(feature = "hyper")]
+impl HyperTransport {
+    /// Use a new client that speaks HTTP and HTTPS, through the proxy from
+    /// the environment if one is set and usable
+    pub fn new() -> Self {
+        HyperTransport::with_proxy(&Proxy::FromEnvironment)
+            .or_else(|_| HyperTransport::with_proxy(&Proxy::Direct))
+            .expect("failed to set up TLS")
+    }
+
+    /// Use a new client that connects through `proxy`
+    pub fn with_proxy(proxy: &Proxy) -> Result<Self, String> {
+        let https = hyper_tls::HttpsConnector::new();
+        let connector = match proxy.resolve(|key| std::env::var(key).ok())? {
+            None => hyper_proxy::ProxyConnector::new(https),
+            Some((url, credentials)) => {
+                if url.scheme() != "http" {
+                    return Err(format!(
+                        "unsupported proxy URL {:?}: hyper only supports http proxies",
+                        url.as_str()
+                    ));
+                }
+                let uri = url
+                    .as_str()
+                    .parse()
+                    .map_err(|e| format!("invalid proxy URL {:?}: {}", url.as_str(), e))?;
+                let mut proxy = hyper_proxy::Proxy::new(hyper_proxy::Intercept::All, uri);
+                if let Some((username, password)) = credentials {
+                    proxy.set_authorization(headers::Authorization::basic(&username, &password));
+                }
+                hyper_proxy::ProxyConnector::from_proxy(https, proxy)
+            }
+        }
+        .map_err(|e| format!("failed to set up TLS: {}", e))?;
+        let proxy_headers = connector
+            .proxies()
+            .first()
+            .map(|proxy| proxy.headers().clone())
+            .unwrap_or_default();
+        Ok(HyperTransport {
+            client: hyper::Client::builder().build(connector),
+            proxy_headers,
+        })
+    }
+
+    /// Share a client (and its connection pool) the game already has
+    pub fn with_client(client: hyper::Client<HyperConnector>) -> Self {
+        HyperTransport {
+            client,
+            proxy_headers: HeaderMap::new(),
+        }
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl Default for HyperTransport {
+    fn default() -> Self {
+        HyperTransport::new()
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl AsyncHttpTransport for HyperTransport {
+    fn name(&self) -> &str {
+        "hyper"
+    }
+
+    fn post(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let transport_error = |e: hyper::Error| GameEventsIOError::Transport(Box::new(e));
+            let mut http_request = hyper::Request::post(request.url.as_str())
+                .body(hyper::Body::from(request.body))
+                .map_err(|e| GameEventsIOError::Transport(Box::new(e)))?;
+            *http_request.headers_mut() = request.headers;
+            // HTTPS requests authenticate when the tunnel is set up instead
+            if http_request.uri().scheme_str() == Some("http") {
+                http_request
+                    .headers_mut()
+                    .extend(self.proxy_headers.clone());
+            }
+
+            let timeout = request.timeout.unwrap_or(crate::REQUEST_TIMEOUT);
+            let exchange = async {
+                let response = self
+                    .client
+                    .request(http_request)
+                    .await
+                    .map_err(transport_error)?;
+                let (parts, body) = response.into_parts();
+                let body = hyper::body::to_bytes(body).await.map_err(transport_error)?;
+                Ok(HttpResponse {
+                    status: parts.status,
+                    headers: parts.headers,
+                    body: String::from_utf8_lossy(&body).into_owned(),
+                })
+            };
+            tokio::time::timeout(timeout, exchange)
+                .await
+                .map_err(|e| GameEventsIOError::Timeout(Box::new(e)))?
+        })
+    }
+}
+
+/// Answer one request on a local port with `response`, returning its URL
+/// and the raw request
+///
+/// The request is read until its JSON array body is complete or the client
+/// hangs up.
+#[cfg(test)]
+pub(crate) fn serve_once(response: &'static str) -> (String, std::thread::JoinHandle<String>) {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/v1/events", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        loop {
+            let n = stream.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&request);
+            if text.contains("\r\n\r\n[") && text.ends_with(']') {
+                break;
+            }
+        }
+        stream.write_all(response.as_bytes()).unwrap();
+        String::from_utf8(request).unwrap()
+    });
+    (url, server)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_result() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            reqwest::header::DATE,
+            "Tue, 14 Nov 2023 22:13:20 GMT".parse().unwrap(),
+        );
+        headers.insert("x-request-id", "req-1".parse().unwrap());
+        let response = HttpResponse {
+            status: StatusCode::OK,
+            headers,
+            body: "ok".to_string(),
+        };
+        assert_eq!(
+            response.clone().into_result().unwrap(),
+            Received {
+                body: "ok".to_string(),
+                date: Some(1_700_000_000),
+                request_id: Some("req-1".to_string()),
+            }
+        );
+
+        let response = HttpResponse {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            ..response
+        };
+        assert!(matches!(
+            response.into_result(),
+            Err(GameEventsIOError::Server { status: 503, .. })
+        ));
+    }
+
+    #[cfg(any(feature = "ureq", feature = "hyper"))]
+    fn request(url: String) -> HttpRequest {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "key".parse().unwrap());
+        HttpRequest {
+            url,
+            headers,
+            body: b"[]".to_vec(),
+            timeout: Some(Duration::from_secs(5)),
+        }
+    }
+
+    #[cfg(feature = "ureq")]
+    #[test]
+    fn test_ureq_transport() {
+        let (url, server) = serve_once(
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 7\r\n\
+             Content-Length: 4\r\nConnection: close\r\n\r\nslow",
+        );
+        let response = UreqTransport::new().post(request(url)).unwrap();
+        assert!(server.join().unwrap().contains("x-api-key: key"));
+        assert_eq!(response.status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.body, "slow");
+        assert_eq!(
+            response.into_result().unwrap_err().retry_after(),
+            Some(Duration::from_secs(7))
+        );
+    }
+
+    #[cfg(feature = "ureq")]
+    #[test]
+    fn test_ureq_timeout() {
+        // Accepts connections but never answers
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut request = request(format!("http://{}/", listener.local_addr().unwrap()));
+        request.timeout = Some(Duration::from_millis(200));
+        assert!(matches!(
+            UreqTransport::new().post(request),
+            Err(GameEventsIOError::Timeout(_))
+        ));
+    }
+
+    #[cfg(feature = "ureq")]
+    #[test]
+    fn test_ureq_proxy() {
+        let (url, server) =
+            serve_once("HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok");
+        let proxy = Proxy::url(url.trim_end_matches("/v1/events")).basic_auth("alice", "secret");
+        let response = UreqTransport::with_proxy(&proxy)
+            .unwrap()
+            .post(request("http://collector.invalid/v1/events".into()))
+            .unwrap();
+        assert_eq!(response.body, "ok");
+        let request = server.join().unwrap().to_lowercase();
+        assert!(request.starts_with("post http://collector.invalid/v1/events "));
+        assert!(request.contains("proxy-authorization: basic ywxpy2u6c2vjcmv0"));
+    }
+
+    #[cfg(feature = "hyper")]
+    #[tokio::test]
+    async fn test_hyper_proxy() {
+        let (url, server) =
+            serve_once("HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok");
+        let proxy = Proxy::url(url.trim_end_matches("/v1/events")).basic_auth("alice", "secret");
+        let response = HyperTransport::with_proxy(&proxy)
+            .unwrap()
+            .post(request("http://collector.invalid/v1/events".into()))
+            .await
+            .unwrap();
+        assert_eq!(response.body, "ok");
+        let request = server.join().unwrap().to_lowercase();
+        assert!(request.starts_with("post http://collector.invalid/v1/events "));
+        assert!(request.contains("proxy-authorization: basic ywxpy2u6c2vjcmv0"));
+
+        assert!(HyperTransport::with_proxy(&Proxy::url("socks5://proxy.corp")).is_err());
+    }
+
+    #[cfg(feature = "hyper")]
+    #[tokio::test]
+    async fn test_hyper_transport() {
+        let (url, server) =
+            serve_once("HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok");
+        let response = HyperTransport::new().post(request(url)).await.unwrap();
+        assert!(server.join().unwrap().contains("x-api-key: key"));
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(response.body, "ok");
+    }
+}
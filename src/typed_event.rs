@@ -0,0 +1,21 @@
+use serde::Serialize;
+
+use crate::{GameEventsIOEvent, GameEventsIOEventBuilder, GameEventsIOEventBuilderError};
+
+/// A struct logged as an event, with its fields as event properties
+///
+/// Implement it with `#[derive(TypedEvent)]` (feature `derive`), which names
+/// the event after the struct in snake_case, and log it with
+/// [`GameEventsIOSession::push_typed`](crate::GameEventsIOSession::push_typed).
+pub trait TypedEvent: Serialize + Sized {
+    /// Name the event is logged under
+    const EVENT_NAME: &'static str;
+
+    /// Build a stand-alone event from this struct
+    fn to_event(&self) -> Result<GameEventsIOEvent, GameEventsIOEventBuilderError> {
+        GameEventsIOEventBuilder::default()
+            .event(Self::EVENT_NAME)
+            .event_properties(crate::to_properties(self))
+            .build()
+    }
+}
@@ -0,0 +1,183 @@
+use std::borrow::Cow;
+use std::ops::Deref;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::{
+    to_properties, AdAction, AdEvent, FlowType, GameEventsIOEvent, GameEventsIOEventBuilderError,
+    GameEventsIOSession, GameEventsIOSessionBuilder, ProgressionEvent, ProgressionStatus,
+    Properties, PurchaseEvent, ResourceEvent, TypedEvent, DEFAULT_IDLE_TIMEOUT,
+};
+
+/// A session whose user properties are a serde struct
+///
+/// Property names and types come from `P`, so they are checked at compile
+/// time instead of drifting between `set_user_property` call sites. The
+/// struct is serialized into the user properties of every event.
+///
+/// Read-only session accessors are available through `Deref`.
+#[derive(Clone, Debug)]
+pub struct TypedSession<P: Serialize> {
+    session: GameEventsIOSession,
+    properties: P,
+}
+
+impl<P: Serialize> TypedSession<P> {
+    /// Create a new session with user_id, session_id and user properties
+    pub fn new(user_id: impl Into<String>, session_id: impl Into<String>, properties: P) -> Self {
+        let mut builder = GameEventsIOSessionBuilder::default();
+        builder
+            .user_id(user_id)
+            .session_id(session_id)
+            .user_properties(to_properties(&properties));
+        Self::from_builder(&builder, properties)
+    }
+
+    /// Start a session for `user_id` like [`GameEventsIOSession::start`]
+    pub fn start(user_id: impl Into<String>, properties: P) -> Self {
+        let mut builder = GameEventsIOSessionBuilder::default();
+        builder
+            .user_id(user_id)
+            .idle_timeout(DEFAULT_IDLE_TIMEOUT)
+            .user_properties(to_properties(&properties));
+        Self::from_builder(&builder, properties)
+    }
+
+    fn from_builder(builder: &GameEventsIOSessionBuilder, properties: P) -> Self {
+        let mut session = builder
+            .build()
+            .expect("Failed to create GameEventsIOSession");
+        session.push_new_session_event();
+        TypedSession {
+            session,
+            properties,
+        }
+    }
+
+    /// Get the user properties
+    pub fn properties(&self) -> &P {
+        &self.properties
+    }
+
+    /// Replace the user properties used for subsequent events
+    pub fn set_properties(&mut self, properties: P) {
+        self.properties = properties;
+        self.sync_properties();
+    }
+
+    /// Change the user properties used for subsequent events in place
+    pub fn update_properties(&mut self, update: impl FnOnce(&mut P)) {
+        update(&mut self.properties);
+        self.sync_properties();
+    }
+
+    fn sync_properties(&mut self) {
+        self.session
+            .set_user_properties(to_properties(&self.properties));
+    }
+
+    /// Set or disable the idle timeout after which a new session is started
+    pub fn set_idle_timeout(&mut self, idle_timeout: Option<Duration>) {
+        self.session.set_idle_timeout(idle_timeout);
+    }
+
+    /// End the session with a `session_end` event
+    pub fn end(&mut self) {
+        self.session.end();
+    }
+
+    /// Add an event to the session
+    pub fn push_event(
+        &mut self,
+        event: impl Into<Cow<'static, str>>,
+        event_properties: Properties,
+    ) {
+        self.session.push_event(event, event_properties);
+    }
+
+    /// Add an event to the session, returning an error if it fails validation
+    pub fn try_push_event(
+        &mut self,
+        event: impl Into<Cow<'static, str>>,
+        event_properties: Properties,
+    ) -> Result<(), GameEventsIOEventBuilderError> {
+        self.session.try_push_event(event, event_properties)
+    }
+
+    /// Add a [`TypedEvent`] to the session
+    pub fn push_typed<E: TypedEvent>(&mut self, event: &E) {
+        self.session.push_typed(event);
+    }
+
+    /// Add a validated purchase event to the session
+    pub fn push_purchase(&mut self, purchase: PurchaseEvent) {
+        self.session.push_purchase(purchase);
+    }
+
+    /// Add an ad lifecycle event to the session
+    pub fn ad_event(&mut self, action: AdAction, ad: AdEvent) {
+        self.session.ad_event(action, ad);
+    }
+
+    /// Add a progression event to the session
+    pub fn progression(&mut self, status: ProgressionStatus, step: ProgressionEvent) {
+        self.session.progression(status, step);
+    }
+
+    /// Add a virtual economy event to the session
+    pub fn resource_event(&mut self, flow: FlowType, resource: ResourceEvent) {
+        self.session.resource_event(flow, resource);
+    }
+
+    /// Take up to `max_count` events from the session
+    pub fn take_events(&mut self, max_count: usize) -> Vec<GameEventsIOEvent> {
+        self.session.take_events(max_count)
+    }
+
+    /// Get back the untyped session
+    pub fn into_inner(self) -> GameEventsIOSession {
+        self.session
+    }
+}
+
+impl<P: Serialize> Deref for TypedSession<P> {
+    type Target = GameEventsIOSession;
+
+    fn deref(&self) -> &GameEventsIOSession {
+        &self.session
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct PlayerProperties {
+        level: u32,
+        vip: bool,
+    }
+
+    #[test]
+    fn test_properties_attached_to_events() {
+        let mut session = TypedSession::new(
+            "user123",
+            "session456",
+            PlayerProperties {
+                level: 1,
+                vip: false,
+            },
+        );
+        session.push_event("level_up", Properties::new());
+        session.update_properties(|p| p.level = 2);
+        session.push_event("level_up", Properties::new());
+
+        let events = session.take_events(3);
+        assert_eq!(events[0].user_properties.get("level").unwrap(), 1);
+        assert_eq!(events[1].user_properties.get("level").unwrap(), 1);
+        assert_eq!(events[2].user_properties.get("level").unwrap(), 2);
+        assert_eq!(events[2].user_properties.get("vip").unwrap(), false);
+        assert_eq!(session.properties().level, 2);
+    }
+}
@@ -0,0 +1,171 @@
+//! Browser support for `wasm32-unknown-unknown` builds
+//!
+//! Requests go through reqwest's fetch backend, timers use the global
+//! `setTimeout` and the spool and state directory are stored in
+//! `localStorage`, keyed by their configured paths.
+
+use std::cell::{Cell, RefCell};
+use std::io;
+use std::rc::Rc;
+use std::time::Duration;
+
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+
+use crate::{
+    EventPriority, FlushPolicy, FlushReport, GameEventsIOClient, GameEventsIOError,
+    GameEventsIOEvent,
+};
+
+/// Resolve after `duration`, in a window or a web worker
+pub(crate) async fn sleep(duration: Duration) {
+    let millis = duration.as_millis().min(i32::MAX as u128) as i32;
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let set_timeout = js_sys::Reflect::get(&js_sys::global(), &"setTimeout".into())
+            .ok()
+            .and_then(|f| f.dyn_into::<js_sys::Function>().ok());
+        if let Some(set_timeout) = set_timeout {
+            let _ = set_timeout.call2(&JsValue::NULL, &resolve, &millis.into());
+        }
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
+fn local_storage() -> io::Result<web_sys::Storage> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| io::Error::other("localStorage is not available"))
+}
+
+/// Read a `localStorage` entry
+pub(crate) fn storage_get(key: &str) -> io::Result<Option<String>> {
+    local_storage()?
+        .get_item(key)
+        .map_err(|e| io::Error::other(format!("{:?}", e)))
+}
+
+/// Write a `localStorage` entry
+pub(crate) fn storage_set(key: &str, value: &str) -> io::Result<()> {
+    local_storage()?
+        .set_item(key, value)
+        .map_err(|e| io::Error::other(format!("{:?}", e)))
+}
+
+struct Shared {
+    /// `None` while a flush is in flight
+    client: RefCell<Option<GameEventsIOClient>>,
+
+    /// Events logged while the client was busy flushing
+    inbox: RefCell<Vec<GameEventsIOEvent>>,
+
+    policy: RefCell<Option<FlushPolicy>>,
+
+    /// Bumped to stop the running auto-flush loop
+    generation: Cell<u64>,
+}
+
+/// Client wrapper for the browser, where nothing may block
+///
+/// Events can be logged at any time, including while a flush is waiting on
+/// the network. [`start_auto_flush`](Self::start_auto_flush) flushes on a
+/// timer, the browser equivalent of [`GameEventsIOClient::spawn_worker`].
+pub struct GameEventsIOWebClient {
+    shared: Rc<Shared>,
+}
+
+impl GameEventsIOWebClient {
+    /// Wrap a client
+    pub fn new(client: GameEventsIOClient) -> Self {
+        GameEventsIOWebClient {
+            shared: Rc::new(Shared {
+                client: RefCell::new(Some(client)),
+                inbox: RefCell::new(Vec::new()),
+                policy: RefCell::new(None),
+                generation: Cell::new(0),
+            }),
+        }
+    }
+
+    /// Queue an event, flushing early if it has [`EventPriority::Immediate`]
+    /// or the client's batch policy or the auto-flush policy says so
+    pub fn log_event(&self, event: GameEventsIOEvent) {
+        let immediate = event.priority == EventPriority::Immediate;
+        let (pending, due) = match self.shared.client.borrow_mut().as_mut() {
+            Some(client) => {
+                client.log_event(event);
+                (client.pending_events_count(), client.flush_due())
+            }
+            None => {
+                self.shared.inbox.borrow_mut().push(event);
+                return;
+            }
+        };
+        let max_pending = self
+            .shared
+            .policy
+            .borrow()
+            .as_ref()
+            .map(|policy| policy.max_pending_events);
+        if immediate || due || max_pending.is_some_and(|max| pending >= max) {
+            let shared = Rc::clone(&self.shared);
+            spawn_local(async move {
+                let _ = flush(&shared).await;
+            });
+        }
+    }
+
+    /// Send all buffered events
+    pub async fn flush(&self) -> Result<FlushReport, GameEventsIOError> {
+        flush(&self.shared).await
+    }
+
+    /// Flush every `policy.interval`, and as soon as `policy.max_pending_events`
+    /// are queued, until [`stop_auto_flush`](Self::stop_auto_flush) is called
+    /// or the wrapper is dropped
+    pub fn start_auto_flush(&self, policy: FlushPolicy) {
+        let generation = self.shared.generation.get() + 1;
+        self.shared.generation.set(generation);
+        let interval = policy.interval;
+        *self.shared.policy.borrow_mut() = Some(policy);
+
+        let shared = Rc::clone(&self.shared);
+        spawn_local(async move {
+            loop {
+                sleep(interval).await;
+                if shared.generation.get() != generation {
+                    break;
+                }
+                let _ = flush(&shared).await;
+            }
+        });
+    }
+
+    /// Stop flushing on a timer
+    pub fn stop_auto_flush(&self) {
+        self.shared.generation.set(self.shared.generation.get() + 1);
+        *self.shared.policy.borrow_mut() = None;
+    }
+
+    /// Run `f` with the wrapped client, unless a flush is in flight
+    pub fn with_client<R>(&self, f: impl FnOnce(&mut GameEventsIOClient) -> R) -> Option<R> {
+        self.shared.client.borrow_mut().as_mut().map(f)
+    }
+}
+
+impl Drop for GameEventsIOWebClient {
+    fn drop(&mut self) {
+        self.stop_auto_flush();
+    }
+}
+
+async fn flush(shared: &Shared) -> Result<FlushReport, GameEventsIOError> {
+    let Some(mut client) = shared.client.borrow_mut().take() else {
+        return Ok(FlushReport::skipped("Flush already in progress"));
+    };
+    let result = client.flush_async().await;
+    for event in shared.inbox.take() {
+        client.log_event(event);
+    }
+    *shared.client.borrow_mut() = Some(client);
+    result
+}
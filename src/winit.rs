@@ -0,0 +1,115 @@
+//! Desktop lifecycle events from winit (feature `winit`)
+//!
+//! Forward the relevant [`ApplicationHandler`](winit::application::ApplicationHandler)
+//! callbacks to a [`WinitLifecycle`] and it logs `app_start`, `app_focus`,
+//! `app_background` and `app_quit` on the session. Keyboard, mouse and touch
+//! input count as activity, so a player who is busy without triggering
+//! events does not have their session rotated for being idle.
+
+use std::collections::HashMap;
+
+use winit::event::WindowEvent;
+
+use crate::GameEventsIOSession;
+
+/// Tracks which lifecycle events were already logged
+#[derive(Clone, Debug, Default)]
+pub struct WinitLifecycle {
+    started: bool,
+    focused: Option<bool>,
+    quit: bool,
+}
+
+impl WinitLifecycle {
+    /// Create a tracker; nothing is logged until the first callback
+    pub fn new() -> Self {
+        WinitLifecycle::default()
+    }
+
+    /// Call from `ApplicationHandler::resumed`
+    ///
+    /// Logs `app_start` the first time and `app_focus` after a suspension.
+    pub fn resumed(&mut self, session: &mut GameEventsIOSession) {
+        if !self.started {
+            self.started = true;
+            session.push_event("app_start", HashMap::new());
+        } else {
+            self.set_focused(session, true);
+        }
+    }
+
+    /// Call from `ApplicationHandler::suspended`
+    pub fn suspended(&mut self, session: &mut GameEventsIOSession) {
+        self.set_focused(session, false);
+    }
+
+    /// Call from `ApplicationHandler::window_event`
+    pub fn window_event(&mut self, session: &mut GameEventsIOSession, event: &WindowEvent) {
+        match event {
+            WindowEvent::Focused(focused) => self.set_focused(session, *focused),
+            WindowEvent::KeyboardInput { .. }
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::Touch(_) => session.touch(),
+            WindowEvent::CloseRequested | WindowEvent::Destroyed => self.exiting(session),
+            _ => {}
+        }
+    }
+
+    /// Call from `ApplicationHandler::exiting`; logs `app_quit` and ends the session
+    pub fn exiting(&mut self, session: &mut GameEventsIOSession) {
+        if !self.quit {
+            self.quit = true;
+            session.push_event("app_quit", HashMap::new());
+            session.end();
+        }
+    }
+
+    fn set_focused(&mut self, session: &mut GameEventsIOSession, focused: bool) {
+        if self.quit || self.focused == Some(focused) {
+            return;
+        }
+        self.focused = Some(focused);
+        let name = if focused {
+            "app_focus"
+        } else {
+            "app_background"
+        };
+        session.push_event(name, HashMap::new());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEventsIOSessionBuilder;
+
+    #[test]
+    fn test_lifecycle_events() {
+        let mut session = GameEventsIOSessionBuilder::default().build().unwrap();
+        let mut lifecycle = WinitLifecycle::new();
+
+        lifecycle.resumed(&mut session);
+        lifecycle.window_event(&mut session, &WindowEvent::Focused(true));
+        lifecycle.window_event(&mut session, &WindowEvent::Focused(true));
+        lifecycle.window_event(&mut session, &WindowEvent::Focused(false));
+        lifecycle.window_event(&mut session, &WindowEvent::CloseRequested);
+        lifecycle.window_event(&mut session, &WindowEvent::Destroyed);
+
+        let names: Vec<_> = session
+            .take_events(usize::MAX)
+            .into_iter()
+            .map(|e| e.event)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "app_start",
+                "app_focus",
+                "app_background",
+                "app_quit",
+                "session_end"
+            ]
+        );
+    }
+}
@@ -0,0 +1,138 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{GameEventsIOClient, GameEventsIOEvent};
+
+/// Controls when a background worker flushes pending events
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(into))]
+#[builder(default)]
+pub struct FlushPolicy {
+    /// Flush at least this often while events are pending
+    pub interval: Duration,
+
+    /// Flush as soon as this many events are pending
+    pub max_pending_events: usize,
+}
+
+impl Default for FlushPolicy {
+    fn default() -> Self {
+        FlushPolicy {
+            interval: Duration::from_secs(10),
+            max_pending_events: 100,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+enum Command {
+    Event(Box<GameEventsIOEvent>),
+    Flush,
+    Stop,
+}
+
+/// Handle to a client running on a background thread
+///
+/// Dropping the worker asks the thread to make a final flush and exit
+/// without waiting for it. Use [`GameEventsIOWorker::stop`] to wait and
+/// get the client back.
+#[derive(Debug)]
+#[cfg(not(target_arch = "wasm32"))]
+pub struct GameEventsIOWorker {
+    sender: Sender<Command>,
+    thread: Option<JoinHandle<GameEventsIOClient>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl GameEventsIOWorker {
+    pub(crate) fn spawn(client: GameEventsIOClient, policy: FlushPolicy) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        let thread = thread::spawn(move || {
+            let mut client = client;
+            let mut next_flush = Instant::now() + policy.interval;
+
+            loop {
+                let timeout = next_flush.saturating_duration_since(Instant::now());
+                match receiver.recv_timeout(timeout) {
+                    Ok(Command::Event(event)) => {
+                        client.log_event(*event);
+                        if client.pending_events_count() < policy.max_pending_events {
+                            continue;
+                        }
+                    }
+                    Ok(Command::Flush) | Err(RecvTimeoutError::Timeout) => {}
+                    Ok(Command::Stop) => return client,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        let _ = client.flush();
+                        return client;
+                    }
+                }
+
+                let _ = client.flush();
+                next_flush = Instant::now() + policy.interval;
+            }
+        });
+
+        GameEventsIOWorker {
+            sender,
+            thread: Some(thread),
+        }
+    }
+
+    /// Queue an event on the worker
+    pub fn log_event(&self, event: GameEventsIOEvent) {
+        let _ = self.sender.send(Command::Event(Box::new(event)));
+    }
+
+    /// Ask the worker to flush now instead of waiting for the policy
+    pub fn flush(&self) {
+        let _ = self.sender.send(Command::Flush);
+    }
+
+    /// Stop the worker and return the client with any events still pending
+    pub fn stop(mut self) -> GameEventsIOClient {
+        let _ = self.sender.send(Command::Stop);
+        self.thread
+            .take()
+            .expect("worker thread already joined")
+            .join()
+            .expect("worker thread panicked")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameEventsIOClientBuilder, GameEventsIOEventBuilder};
+
+    #[test]
+    fn test_stop_returns_pending_events() {
+        let client = GameEventsIOClientBuilder::default()
+            .api_key("test_api_key")
+            .build()
+            .unwrap();
+        let policy = FlushPolicyBuilder::default()
+            .interval(Duration::from_secs(3600))
+            .build()
+            .unwrap();
+
+        let worker = client.spawn_worker(policy);
+        for _ in 0..3 {
+            let event = GameEventsIOEventBuilder::default()
+                .event("test_event")
+                .build()
+                .unwrap();
+            worker.log_event(event);
+        }
+
+        let client = worker.stop();
+        assert_eq!(client.pending_events_count(), 3);
+    }
+}